@@ -12,11 +12,29 @@ fn list_files_in_directory(directory: &str) -> impl Iterator<Item = PathBuf> {
         .map(|file| file.path().to_path_buf())
 }
 
+/// Entry points of the debugger & the disassembler, the `debugger` feature needs their sources
+const DEBUGGER_SOURCES: [&str; 4] = ["dbxface.c", "dbinput.c", "dmopcode.c", "dmwalk.c"];
+
 fn main() {
     let root = env::var("CARGO_MANIFEST_DIR").unwrap();
     let out_dir = env::var("OUT_DIR").unwrap();
+    let debugger = env::var_os("CARGO_FEATURE_DEBUGGER").is_some();
+
+    if debugger {
+        let missing = DEBUGGER_SOURCES
+            .iter()
+            .filter(|file| !Path::new(&format!("{}/acpica/src/{}", root, file)).exists())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            panic!(
+                "the debugger feature needs the db*.c & dm*.c sources of ACPICA 20200110 in [root]/acpica/src, {:?} are missing",
+                missing
+            );
+        }
+    }
 
     println!("cargo:rerun-if-changed=acpica.h");
+    println!("cargo:rerun-if-changed=acpica/src");
 
     let c_files = list_files_in_directory(&root)
         .filter(|path| path.extension().map_or(false, |f| f == "c"))
@@ -27,10 +45,18 @@ fn main() {
         .filter(|path| path.extension().map_or(false, |f| f == "h"))
         .for_each(|path| println!("cargo:rerun-if-changed={}", path.display()));
 
-    cc::Build::new()
+    let mut build = cc::Build::new();
+    if debugger {
+        build.define("ACPI_DEBUGGER", None);
+    }
+
+    build
         .no_default_flags(true)
         .flag("-nostdlib")
         .flag("-m64")
+        .flag("-mcmodel=kernel")
+        .flag("-fno-pic")
+        .flag("-fno-omit-frame-pointer")
         .flag("-nodefaultlibs")
         .flag("-Wno-unused-parameter")
         .flag("-g")
@@ -39,9 +65,14 @@ fn main() {
         .files(c_files)
         .compile("libapcica.a");
 
-    let bindings = bindgen::Builder::default()
+    let mut bindings = bindgen::Builder::default()
         .header(&format!("{}/acpica.h", root))
-        .use_core()
+        .use_core();
+    if debugger {
+        bindings = bindings.clang_arg("-DACPI_DEBUGGER");
+    }
+
+    let bindings = bindings
         .generate()
         .expect("Unable to generate acpica bindings");
 
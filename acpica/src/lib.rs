@@ -4,3 +4,59 @@
 #![allow(non_snake_case)]
 
 include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+/// Whether the AML debugger is built, with the `debugger` feature
+pub const DEBUGGER: bool = cfg!(acpica_debugger);
+
+/// Set up the AML debugger, nothing is done when it isn't built
+///
+/// # Safety
+/// ACPICA has to be initialized.
+pub unsafe fn initialize_debugger() -> ACPI_STATUS {
+    #[cfg(feature = "debugger")]
+    return AcpiInitializeDebugger();
+
+    #[cfg(not(feature = "debugger"))]
+    return 0;
+}
+
+/// Run the AML debugger until its `quit` command, false when it isn't built
+///
+/// # Safety
+/// The debugger has to be initialized with `initialize_debugger`.
+pub unsafe fn run_debugger() -> bool {
+    #[cfg(feature = "debugger")]
+    {
+        AcpiGbl_DbTerminateLoop = 0;
+        AcpiRunDebugger(core::ptr::null_mut());
+    }
+
+    DEBUGGER
+}
+
+/// Whether the debugger is single-stepping a control method
+///
+/// # Safety
+/// Only the debugger thread may call it.
+pub unsafe fn debugger_method_executing() -> bool {
+    #[cfg(feature = "debugger")]
+    return AcpiGbl_MethodExecuting != 0;
+
+    #[cfg(not(feature = "debugger"))]
+    return false;
+}
+
+/// The buffer the debugger reads its commands from, empty when it isn't built
+///
+/// # Safety
+/// Only the debugger thread may call it.
+pub unsafe fn debugger_line_buffer() -> &'static mut [u8] {
+    #[cfg(feature = "debugger")]
+    return core::slice::from_raw_parts_mut(
+        core::ptr::addr_of_mut!(AcpiGbl_DbLineBuf) as *mut u8,
+        ACPI_DB_LINE_BUFFER_SIZE as usize,
+    );
+
+    #[cfg(not(feature = "debugger"))]
+    return &mut [];
+}
@@ -0,0 +1,85 @@
+//! ACPI tables parser, independent from ACPICA so it can be used early during boot.
+//!
+//! Parsers only work on byte slices, finding & mapping the tables is left to the kernel.
+
+pub mod fadt;
+pub mod hpet;
+pub mod madt;
+pub mod mcfg;
+pub mod rsdp;
+pub mod sdt;
+pub mod slit;
+pub mod srat;
+
+#[cfg(test)]
+mod tests;
+
+pub use fadt::Fadt;
+pub use hpet::Hpet;
+pub use madt::Madt;
+pub use mcfg::Mcfg;
+pub use rsdp::Rsdp;
+pub use sdt::{GenericAddress, RootTable, Sdt, SdtHeader};
+pub use slit::Slit;
+pub use srat::Srat;
+
+use core::convert::TryFrom;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TableError {
+    /// The table is shorter than its header or than the length it claims
+    TooShort,
+    BadSignature,
+    BadChecksum,
+    /// No RSDP was given by the bootloader
+    NoRsdp,
+    /// The table couldn't be mapped
+    MapFailed,
+}
+
+pub type Result<T> = core::result::Result<T, TableError>;
+
+/// Sum of all the bytes, valid tables sum to 0
+pub fn checksum(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+/// Read a little-endian field of `N` bytes at `offset`
+fn field<const N: usize>(bytes: &[u8], offset: usize) -> Result<[u8; N]> {
+    bytes
+        .get(offset..offset + N)
+        .and_then(|field| <[u8; N]>::try_from(field).ok())
+        .ok_or(TableError::TooShort)
+}
+
+fn read_u8(bytes: &[u8], offset: usize) -> Result<u8> {
+    bytes.get(offset).copied().ok_or(TableError::TooShort)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Result<u16> {
+    field(bytes, offset).map(u16::from_le_bytes)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32> {
+    field(bytes, offset).map(u32::from_le_bytes)
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> Result<u64> {
+    field(bytes, offset).map(u64::from_le_bytes)
+}
+
+/// Iterate over the `(type, bytes)` of variable-length entries starting with a type & a length
+/// byte, like the ones of the MADT & SRAT. Stops at the first malformed entry.
+fn entries(mut bytes: &[u8]) -> impl Iterator<Item = (u8, &[u8])> {
+    core::iter::from_fn(move || {
+        let entry_type = *bytes.first()?;
+        let length = usize::from(*bytes.get(1)?);
+        if length < 2 || length > bytes.len() {
+            return None;
+        }
+
+        let (entry, rest) = bytes.split_at(length);
+        bytes = rest;
+        Some((entry_type, entry))
+    })
+}
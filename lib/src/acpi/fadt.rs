@@ -0,0 +1,85 @@
+use super::*;
+
+pub const SIGNATURE: &[u8; 4] = b"FACP";
+
+/// Fixed ACPI description table, only the fields needed by the kernel are decoded
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Fadt {
+    pub revision: u8,
+    pub firmware_ctrl: u64,
+    pub dsdt: u64,
+    pub preferred_pm_profile: u8,
+    pub sci_interrupt: u16,
+    pub smi_command: u32,
+    pub acpi_enable: u8,
+    pub acpi_disable: u8,
+    pub pm1a_event_block: u32,
+    pub pm1a_control_block: u32,
+    pub pm_timer_block: u32,
+    pub c2_latency: u16,
+    pub c3_latency: u16,
+    /// Index of the century register in the CMOS, 0 if there is none
+    pub century: u8,
+    pub boot_architecture_flags: u16,
+    pub flags: u32,
+    /// Only from revision 2
+    pub reset: Option<(GenericAddress, u8)>,
+}
+
+impl Fadt {
+    /// The hardware supports the reset register
+    pub const RESET_REG_SUPPORTED: u32 = 1 << 10;
+    /// No ACPI hardware, only reduced hardware features are available
+    pub const HW_REDUCED_ACPI: u32 = 1 << 20;
+
+    /// Legacy devices such as the RTC are present
+    pub const BOOT_LEGACY_DEVICES: u16 = 1 << 0;
+    /// An 8042 keyboard controller is present
+    pub const BOOT_8042: u16 = 1 << 1;
+    /// The CMOS RTC isn't present
+    pub const BOOT_CMOS_RTC_NOT_PRESENT: u16 = 1 << 5;
+
+    pub fn parse(sdt: Sdt<'_>) -> Result<Fadt> {
+        if &sdt.header.signature != SIGNATURE {
+            return Err(TableError::BadSignature);
+        }
+
+        // offsets are from the start of the table, fields after the flags are optional
+        let bytes = sdt.bytes();
+        let x_address = |offset| read_u64(bytes, offset).ok().filter(|&address| address != 0);
+
+        let reset = match (GenericAddress::parse(bytes, 116), read_u8(bytes, 128)) {
+            (Ok(register), Ok(value)) => Some((register, value)),
+            _ => None,
+        };
+
+        Ok(Fadt {
+            revision: sdt.header.revision,
+            firmware_ctrl: x_address(132).unwrap_or(u64::from(read_u32(bytes, 36)?)),
+            dsdt: x_address(140).unwrap_or(u64::from(read_u32(bytes, 40)?)),
+            preferred_pm_profile: read_u8(bytes, 45)?,
+            sci_interrupt: read_u16(bytes, 46)?,
+            smi_command: read_u32(bytes, 48)?,
+            acpi_enable: read_u8(bytes, 52)?,
+            acpi_disable: read_u8(bytes, 53)?,
+            pm1a_event_block: read_u32(bytes, 56)?,
+            pm1a_control_block: read_u32(bytes, 64)?,
+            pm_timer_block: read_u32(bytes, 76)?,
+            c2_latency: read_u16(bytes, 96)?,
+            c3_latency: read_u16(bytes, 98)?,
+            century: read_u8(bytes, 108)?,
+            boot_architecture_flags: read_u16(bytes, 109).unwrap_or(0),
+            flags: read_u32(bytes, 112).unwrap_or(0),
+            reset,
+        })
+    }
+
+    /// Reset register & the value to write to it, if the firmware supports it
+    pub fn reset_register(&self) -> Option<(GenericAddress, u8)> {
+        if self.flags & Self::RESET_REG_SUPPORTED != 0 {
+            self.reset
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use super::*;
+
+pub const SIGNATURE: &[u8; 4] = b"HPET";
+
+/// High precision event timer description table
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Hpet {
+    pub event_timer_block_id: u32,
+    pub base_address: GenericAddress,
+    pub hpet_number: u8,
+    /// Minimum clock tick in periodic mode
+    pub minimum_tick: u16,
+    pub page_protection: u8,
+}
+
+impl Hpet {
+    pub fn parse(sdt: Sdt<'_>) -> Result<Hpet> {
+        if &sdt.header.signature != SIGNATURE {
+            return Err(TableError::BadSignature);
+        }
+
+        let body = sdt.body();
+        Ok(Hpet {
+            event_timer_block_id: read_u32(body, 0)?,
+            base_address: GenericAddress::parse(body, 4)?,
+            hpet_number: read_u8(body, 16)?,
+            minimum_tick: read_u16(body, 17)?,
+            page_protection: read_u8(body, 19)?,
+        })
+    }
+}
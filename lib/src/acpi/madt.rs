@@ -0,0 +1,195 @@
+use super::*;
+
+pub const SIGNATURE: &[u8; 4] = b"APIC";
+
+/// Multiple APIC description table
+#[derive(Copy, Clone, Debug)]
+pub struct Madt<'a> {
+    pub local_apic_address: u32,
+    pub flags: u32,
+    entries: &'a [u8],
+}
+
+/// Polarity & trigger mode of an interrupt, as encoded in MADT entries
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct InterruptFlags(pub u16);
+
+impl InterruptFlags {
+    /// `None` when the bus default is used
+    pub fn active_low(&self) -> Option<bool> {
+        match self.0 & 0b11 {
+            0b01 => Some(false),
+            0b11 => Some(true),
+            _ => None,
+        }
+    }
+
+    /// `None` when the bus default is used
+    pub fn level_triggered(&self) -> Option<bool> {
+        match (self.0 >> 2) & 0b11 {
+            0b01 => Some(false),
+            0b11 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MadtEntry {
+    LocalApic {
+        processor_uid: u8,
+        apic_id: u8,
+        flags: u32,
+    },
+    IoApic {
+        id: u8,
+        address: u32,
+        gsi_base: u32,
+    },
+    InterruptSourceOverride {
+        bus: u8,
+        source: u8,
+        gsi: u32,
+        flags: InterruptFlags,
+    },
+    NmiSource {
+        flags: InterruptFlags,
+        gsi: u32,
+    },
+    LocalApicNmi {
+        processor_uid: u8,
+        flags: InterruptFlags,
+        lint: u8,
+    },
+    LocalApicAddressOverride {
+        address: u64,
+    },
+    LocalX2Apic {
+        x2apic_id: u32,
+        flags: u32,
+        processor_uid: u32,
+    },
+    Unknown {
+        entry_type: u8,
+    },
+}
+
+impl MadtEntry {
+    /// Processor is usable
+    pub const LOCAL_APIC_ENABLED: u32 = 1 << 0;
+    /// Processor can be enabled at runtime
+    pub const LOCAL_APIC_ONLINE_CAPABLE: u32 = 1 << 1;
+
+    fn parse(entry_type: u8, bytes: &[u8]) -> Result<MadtEntry> {
+        Ok(match entry_type {
+            0 => MadtEntry::LocalApic {
+                processor_uid: read_u8(bytes, 2)?,
+                apic_id: read_u8(bytes, 3)?,
+                flags: read_u32(bytes, 4)?,
+            },
+            1 => MadtEntry::IoApic {
+                id: read_u8(bytes, 2)?,
+                address: read_u32(bytes, 4)?,
+                gsi_base: read_u32(bytes, 8)?,
+            },
+            2 => MadtEntry::InterruptSourceOverride {
+                bus: read_u8(bytes, 2)?,
+                source: read_u8(bytes, 3)?,
+                gsi: read_u32(bytes, 4)?,
+                flags: InterruptFlags(read_u16(bytes, 8)?),
+            },
+            3 => MadtEntry::NmiSource {
+                flags: InterruptFlags(read_u16(bytes, 2)?),
+                gsi: read_u32(bytes, 4)?,
+            },
+            4 => MadtEntry::LocalApicNmi {
+                processor_uid: read_u8(bytes, 2)?,
+                flags: InterruptFlags(read_u16(bytes, 3)?),
+                lint: read_u8(bytes, 5)?,
+            },
+            5 => MadtEntry::LocalApicAddressOverride {
+                address: read_u64(bytes, 4)?,
+            },
+            9 => MadtEntry::LocalX2Apic {
+                x2apic_id: read_u32(bytes, 4)?,
+                flags: read_u32(bytes, 8)?,
+                processor_uid: read_u32(bytes, 12)?,
+            },
+            entry_type => MadtEntry::Unknown { entry_type },
+        })
+    }
+}
+
+impl<'a> Madt<'a> {
+    pub fn parse(sdt: Sdt<'a>) -> Result<Madt<'a>> {
+        if &sdt.header.signature != SIGNATURE {
+            return Err(TableError::BadSignature);
+        }
+
+        let body = sdt.body();
+        Ok(Madt {
+            local_apic_address: read_u32(body, 0)?,
+            flags: read_u32(body, 4)?,
+            entries: body.get(8..).ok_or(TableError::TooShort)?,
+        })
+    }
+
+    /// Entries of the table, malformed entries are skipped
+    pub fn entries(&self) -> impl Iterator<Item = MadtEntry> + 'a {
+        entries(self.entries)
+            .filter_map(|(entry_type, bytes)| MadtEntry::parse(entry_type, bytes).ok())
+    }
+
+    /// Physical address of the local APICs, taking the 64-bit override into account
+    pub fn local_apic_address(&self) -> u64 {
+        self.entries()
+            .find_map(|entry| match entry {
+                MadtEntry::LocalApicAddressOverride { address } => Some(address),
+                _ => None,
+            })
+            .unwrap_or_else(|| u64::from(self.local_apic_address))
+    }
+
+    /// `(id, address, gsi_base)` of every I/O APIC
+    pub fn io_apics(&self) -> impl Iterator<Item = (u8, u32, u32)> + 'a {
+        self.entries().filter_map(|entry| match entry {
+            MadtEntry::IoApic {
+                id,
+                address,
+                gsi_base,
+            } => Some((id, address, gsi_base)),
+            _ => None,
+        })
+    }
+
+    /// Local APIC ids of the usable processors
+    pub fn processors(&self) -> impl Iterator<Item = u32> + 'a {
+        self.entries().filter_map(|entry| {
+            let usable = MadtEntry::LOCAL_APIC_ENABLED | MadtEntry::LOCAL_APIC_ONLINE_CAPABLE;
+            match entry {
+                MadtEntry::LocalApic { apic_id, flags, .. } if flags & usable != 0 => {
+                    Some(u32::from(apic_id))
+                }
+                MadtEntry::LocalX2Apic {
+                    x2apic_id, flags, ..
+                } if flags & usable != 0 => Some(x2apic_id),
+                _ => None,
+            }
+        })
+    }
+
+    /// GSI & flags an ISA IRQ is connected to, ISA IRQs are identity mapped unless overridden
+    pub fn isa_irq(&self, irq: u8) -> (u32, InterruptFlags) {
+        self.entries()
+            .find_map(|entry| match entry {
+                MadtEntry::InterruptSourceOverride {
+                    bus: 0,
+                    source,
+                    gsi,
+                    flags,
+                } if source == irq => Some((gsi, flags)),
+                _ => None,
+            })
+            .unwrap_or((u32::from(irq), InterruptFlags(0)))
+    }
+}
@@ -0,0 +1,44 @@
+use super::*;
+
+pub const SIGNATURE: &[u8; 4] = b"MCFG";
+
+const ENTRY_SIZE: usize = 16;
+
+/// PCI express memory mapped configuration space table
+#[derive(Copy, Clone, Debug)]
+pub struct Mcfg<'a> {
+    entries: &'a [u8],
+}
+
+/// Configuration space of a range of buses of a PCI segment
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct McfgEntry {
+    pub base_address: u64,
+    pub segment: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+}
+
+impl<'a> Mcfg<'a> {
+    pub fn parse(sdt: Sdt<'a>) -> Result<Mcfg<'a>> {
+        if &sdt.header.signature != SIGNATURE {
+            return Err(TableError::BadSignature);
+        }
+
+        // 8 reserved bytes precede the entries
+        Ok(Mcfg {
+            entries: sdt.body().get(8..).ok_or(TableError::TooShort)?,
+        })
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = McfgEntry> + 'a {
+        self.entries
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| McfgEntry {
+                base_address: read_u64(entry, 0).unwrap(),
+                segment: read_u16(entry, 8).unwrap(),
+                start_bus: read_u8(entry, 10).unwrap(),
+                end_bus: read_u8(entry, 11).unwrap(),
+            })
+    }
+}
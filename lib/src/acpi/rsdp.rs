@@ -0,0 +1,51 @@
+use super::*;
+
+const SIGNATURE: &[u8; 8] = b"RSD PTR ";
+const V1_SIZE: usize = 20;
+
+/// Root system description pointer
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Rsdp {
+    pub oem_id: [u8; 6],
+    pub revision: u8,
+    pub rsdt_address: u32,
+    /// Only present from revision 2
+    pub xsdt_address: Option<u64>,
+}
+
+impl Rsdp {
+    /// Parse & validate an RSDP, like the copy given by the bootloader
+    pub fn parse(bytes: &[u8]) -> Result<Rsdp> {
+        if bytes.len() < V1_SIZE {
+            return Err(TableError::TooShort);
+        }
+
+        if bytes[..8] != SIGNATURE[..] {
+            return Err(TableError::BadSignature);
+        }
+
+        if checksum(&bytes[..V1_SIZE]) != 0 {
+            return Err(TableError::BadChecksum);
+        }
+
+        let revision = read_u8(bytes, 15)?;
+        let xsdt_address = if revision >= 2 {
+            let length = usize::try_from(read_u32(bytes, 20)?).unwrap();
+            let extended = bytes.get(..length).ok_or(TableError::TooShort)?;
+            if checksum(extended) != 0 {
+                return Err(TableError::BadChecksum);
+            }
+
+            Some(read_u64(bytes, 24)?).filter(|&address| address != 0)
+        } else {
+            None
+        };
+
+        Ok(Rsdp {
+            oem_id: field(bytes, 9)?,
+            revision,
+            rsdt_address: read_u32(bytes, 16)?,
+            xsdt_address,
+        })
+    }
+}
@@ -0,0 +1,136 @@
+use super::*;
+
+/// Header shared by every system description table
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SdtHeader {
+    pub signature: [u8; 4],
+    pub length: u32,
+    pub revision: u8,
+    pub checksum: u8,
+    pub oem_id: [u8; 6],
+    pub oem_table_id: [u8; 8],
+    pub oem_revision: u32,
+    pub creator_id: u32,
+    pub creator_revision: u32,
+}
+
+impl SdtHeader {
+    pub const SIZE: usize = 36;
+
+    /// Parse the header, the checksum of the table isn't verified
+    pub fn parse(bytes: &[u8]) -> Result<SdtHeader> {
+        Ok(SdtHeader {
+            signature: field(bytes, 0)?,
+            length: read_u32(bytes, 4)?,
+            revision: read_u8(bytes, 8)?,
+            checksum: read_u8(bytes, 9)?,
+            oem_id: field(bytes, 10)?,
+            oem_table_id: field(bytes, 16)?,
+            oem_revision: read_u32(bytes, 24)?,
+            creator_id: read_u32(bytes, 28)?,
+            creator_revision: read_u32(bytes, 32)?,
+        })
+    }
+}
+
+/// A whole table whose length & checksum have been verified
+#[derive(Copy, Clone, Debug)]
+pub struct Sdt<'a> {
+    pub header: SdtHeader,
+    bytes: &'a [u8],
+}
+
+impl<'a> Sdt<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Sdt<'a>> {
+        let header = SdtHeader::parse(bytes)?;
+        let length = usize::try_from(header.length).unwrap();
+        if length < SdtHeader::SIZE || length > bytes.len() {
+            return Err(TableError::TooShort);
+        }
+
+        let bytes = &bytes[..length];
+        if checksum(bytes) != 0 {
+            return Err(TableError::BadChecksum);
+        }
+
+        Ok(Sdt { header, bytes })
+    }
+
+    /// Same as `parse`, also checking the signature
+    pub fn parse_with_signature(bytes: &'a [u8], signature: &[u8; 4]) -> Result<Sdt<'a>> {
+        let sdt = Sdt::parse(bytes)?;
+        if &sdt.header.signature != signature {
+            return Err(TableError::BadSignature);
+        }
+
+        Ok(sdt)
+    }
+
+    /// The whole table, header included
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Content of the table following the header
+    pub fn body(&self) -> &'a [u8] {
+        &self.bytes[SdtHeader::SIZE..]
+    }
+}
+
+/// The RSDT, with 4 bytes entries, or the XSDT, with 8 bytes entries
+#[derive(Copy, Clone, Debug)]
+pub struct RootTable<'a> {
+    pub sdt: Sdt<'a>,
+    entry_size: usize,
+}
+
+impl<'a> RootTable<'a> {
+    pub fn parse(bytes: &'a [u8], entry_size: usize) -> Result<RootTable<'a>> {
+        let signature = if entry_size == 8 { b"XSDT" } else { b"RSDT" };
+        Ok(RootTable {
+            sdt: Sdt::parse_with_signature(bytes, signature)?,
+            entry_size,
+        })
+    }
+
+    /// Physical addresses of the tables
+    pub fn entries(&self) -> impl Iterator<Item = u64> + 'a {
+        let entry_size = self.entry_size;
+        self.sdt
+            .body()
+            .chunks_exact(entry_size)
+            .map(move |entry| match entry_size {
+                8 => read_u64(entry, 0).unwrap(),
+                _ => u64::from(read_u32(entry, 0).unwrap()),
+            })
+    }
+}
+
+/// Location of a register, as described in the tables
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GenericAddress {
+    /// 0 for system memory, 1 for system I/O, 2 for the PCI configuration space
+    pub address_space: u8,
+    pub bit_width: u8,
+    pub bit_offset: u8,
+    pub access_size: u8,
+    pub address: u64,
+}
+
+impl GenericAddress {
+    pub const SIZE: usize = 12;
+
+    pub const SYSTEM_MEMORY: u8 = 0;
+    pub const SYSTEM_IO: u8 = 1;
+    pub const PCI_CONFIGURATION: u8 = 2;
+
+    pub fn parse(bytes: &[u8], offset: usize) -> Result<GenericAddress> {
+        Ok(GenericAddress {
+            address_space: read_u8(bytes, offset)?,
+            bit_width: read_u8(bytes, offset + 1)?,
+            bit_offset: read_u8(bytes, offset + 2)?,
+            access_size: read_u8(bytes, offset + 3)?,
+            address: read_u64(bytes, offset + 4)?,
+        })
+    }
+}
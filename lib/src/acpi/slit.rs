@@ -0,0 +1,39 @@
+use super::*;
+
+pub const SIGNATURE: &[u8; 4] = b"SLIT";
+
+/// System locality information table, the distances between proximity domains
+#[derive(Copy, Clone, Debug)]
+pub struct Slit<'a> {
+    pub locality_count: usize,
+    matrix: &'a [u8],
+}
+
+impl<'a> Slit<'a> {
+    pub fn parse(sdt: Sdt<'a>) -> Result<Slit<'a>> {
+        if &sdt.header.signature != SIGNATURE {
+            return Err(TableError::BadSignature);
+        }
+
+        let body = sdt.body();
+        let locality_count =
+            usize::try_from(read_u64(body, 0)?).map_err(|_| TableError::TooShort)?;
+        let size = locality_count
+            .checked_mul(locality_count)
+            .ok_or(TableError::TooShort)?;
+
+        Ok(Slit {
+            locality_count,
+            matrix: body.get(8..8 + size).ok_or(TableError::TooShort)?,
+        })
+    }
+
+    /// Relative distance from `from` to `to`, 10 being the distance of a locality to itself
+    pub fn distance(&self, from: usize, to: usize) -> Option<u8> {
+        if from < self.locality_count && to < self.locality_count {
+            Some(self.matrix[from * self.locality_count + to])
+        } else {
+            None
+        }
+    }
+}
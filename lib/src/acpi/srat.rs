@@ -0,0 +1,97 @@
+use super::*;
+
+pub const SIGNATURE: &[u8; 4] = b"SRAT";
+
+/// System resource affinity table, the proximity domain of processors & memory ranges
+#[derive(Copy, Clone, Debug)]
+pub struct Srat<'a> {
+    entries: &'a [u8],
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SratEntry {
+    ProcessorAffinity {
+        proximity_domain: u32,
+        apic_id: u8,
+        flags: u32,
+    },
+    MemoryAffinity {
+        proximity_domain: u32,
+        base_address: u64,
+        length: u64,
+        flags: u32,
+    },
+    X2ApicAffinity {
+        proximity_domain: u32,
+        x2apic_id: u32,
+        flags: u32,
+    },
+    Unknown {
+        entry_type: u8,
+    },
+}
+
+impl SratEntry {
+    /// The entry is valid, other entries have to be ignored
+    pub const ENABLED: u32 = 1 << 0;
+    /// The memory range can be hot-plugged
+    pub const MEMORY_HOT_PLUGGABLE: u32 = 1 << 1;
+    pub const MEMORY_NON_VOLATILE: u32 = 1 << 2;
+
+    fn parse(entry_type: u8, bytes: &[u8]) -> Result<SratEntry> {
+        Ok(match entry_type {
+            0 => {
+                // the proximity domain is split between bits 0-7 & bits 8-31
+                let low = u32::from(read_u8(bytes, 2)?);
+                let high = field::<3>(bytes, 9)?;
+                SratEntry::ProcessorAffinity {
+                    proximity_domain: low | u32::from_le_bytes([0, high[0], high[1], high[2]]),
+                    apic_id: read_u8(bytes, 3)?,
+                    flags: read_u32(bytes, 4)?,
+                }
+            }
+            1 => SratEntry::MemoryAffinity {
+                proximity_domain: read_u32(bytes, 2)?,
+                base_address: read_u64(bytes, 8)?,
+                length: read_u64(bytes, 16)?,
+                flags: read_u32(bytes, 28)?,
+            },
+            2 => SratEntry::X2ApicAffinity {
+                proximity_domain: read_u32(bytes, 4)?,
+                x2apic_id: read_u32(bytes, 8)?,
+                flags: read_u32(bytes, 12)?,
+            },
+            entry_type => SratEntry::Unknown { entry_type },
+        })
+    }
+
+    /// Whether the entry has to be taken into account
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            SratEntry::ProcessorAffinity { flags, .. }
+            | SratEntry::MemoryAffinity { flags, .. }
+            | SratEntry::X2ApicAffinity { flags, .. } => flags & Self::ENABLED != 0,
+            SratEntry::Unknown { .. } => false,
+        }
+    }
+}
+
+impl<'a> Srat<'a> {
+    pub fn parse(sdt: Sdt<'a>) -> Result<Srat<'a>> {
+        if &sdt.header.signature != SIGNATURE {
+            return Err(TableError::BadSignature);
+        }
+
+        // 12 reserved bytes precede the entries
+        Ok(Srat {
+            entries: sdt.body().get(12..).ok_or(TableError::TooShort)?,
+        })
+    }
+
+    /// Enabled entries of the table, malformed ones are skipped
+    pub fn entries(&self) -> impl Iterator<Item = SratEntry> + 'a {
+        entries(self.entries)
+            .filter_map(|(entry_type, bytes)| SratEntry::parse(entry_type, bytes).ok())
+            .filter(SratEntry::is_enabled)
+    }
+}
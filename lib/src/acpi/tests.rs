@@ -0,0 +1,457 @@
+//! The APIC, FACP & MCFG blobs were dumped from `/sys/firmware/acpi/tables` in a Firecracker VM
+//! with one CPU. Linux doesn't export the RSDP & the XSDT, they are rebuilt from the addresses
+//! logged at boot. The SRAT & SLIT are built like the ones of QEMU with two NUMA nodes, the HPET
+//! like the one of its PC machine.
+
+use super::madt::MadtEntry;
+use super::mcfg::McfgEntry;
+use super::srat::SratEntry;
+use super::*;
+
+const FIRECRACKER_MADT: &[u8] = include_bytes!("../../testdata/acpi/firecracker-apic.dat");
+const FIRECRACKER_FADT: &[u8] = include_bytes!("../../testdata/acpi/firecracker-facp.dat");
+const FIRECRACKER_MCFG: &[u8] = include_bytes!("../../testdata/acpi/firecracker-mcfg.dat");
+
+/// Physical addresses of the FACP, APIC & MCFG in the XSDT of the VM
+const FIRECRACKER_TABLES: [u64; 3] = [0xa_0c83, 0xa_0d97, 0xa_0dd7];
+const FIRECRACKER_XSDT_ADDRESS: u64 = 0xa_0e13;
+
+/// Table with a valid checksum, with the OEM fields of the VM
+fn table(signature: &[u8; 4], revision: u8, oem_table_id: &[u8; 8], body: &[u8]) -> Vec<u8> {
+    let length = u32::try_from(SdtHeader::SIZE + body.len()).unwrap();
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(signature);
+    bytes.extend_from_slice(&length.to_le_bytes());
+    bytes.extend_from_slice(&[revision, 0]);
+    bytes.extend_from_slice(b"FIRECK");
+    bytes.extend_from_slice(oem_table_id);
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(b"FCAT");
+    bytes.extend_from_slice(&0x2024_0119u32.to_le_bytes());
+    bytes.extend_from_slice(body);
+
+    bytes[9] = 0u8.wrapping_sub(checksum(&bytes));
+    bytes
+}
+
+fn firecracker_xsdt() -> Vec<u8> {
+    let body = FIRECRACKER_TABLES
+        .iter()
+        .flat_map(|address| address.to_le_bytes())
+        .collect::<Vec<_>>();
+    table(b"XSDT", 1, b"FCMVXSDT", &body)
+}
+
+fn firecracker_rsdp() -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RSD PTR ");
+    bytes.push(0);
+    bytes.extend_from_slice(b"FIRECK");
+    bytes.push(2);
+    bytes.extend_from_slice(&0u32.to_le_bytes());
+    bytes.extend_from_slice(&36u32.to_le_bytes());
+    bytes.extend_from_slice(&FIRECRACKER_XSDT_ADDRESS.to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+
+    bytes[8] = 0u8.wrapping_sub(checksum(&bytes[..20]));
+    bytes[32] = 0u8.wrapping_sub(checksum(&bytes));
+    bytes
+}
+
+fn processor_affinity(proximity_domain: u32, apic_id: u8) -> Vec<u8> {
+    let domain = proximity_domain.to_le_bytes();
+    let mut entry = vec![0, 16, domain[0], apic_id];
+    entry.extend_from_slice(&SratEntry::ENABLED.to_le_bytes());
+    entry.extend_from_slice(&[0, domain[1], domain[2], domain[3]]);
+    entry.extend_from_slice(&0u32.to_le_bytes());
+    entry
+}
+
+fn memory_affinity(proximity_domain: u32, base_address: u64, length: u64, flags: u32) -> Vec<u8> {
+    let mut entry = vec![1, 40];
+    entry.extend_from_slice(&proximity_domain.to_le_bytes());
+    entry.extend_from_slice(&[0; 2]);
+    entry.extend_from_slice(&base_address.to_le_bytes());
+    entry.extend_from_slice(&length.to_le_bytes());
+    entry.extend_from_slice(&[0; 4]);
+    entry.extend_from_slice(&flags.to_le_bytes());
+    entry.extend_from_slice(&[0; 8]);
+    entry
+}
+
+/// Two nodes with a CPU & 128MB each, the memory hole below 1MB is left out
+fn qemu_srat() -> Vec<u8> {
+    let mut body = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    body.extend(processor_affinity(0, 0));
+    body.extend(processor_affinity(1, 1));
+    body.extend(memory_affinity(0, 0, 0xa_0000, SratEntry::ENABLED));
+    body.extend(memory_affinity(
+        0,
+        0x10_0000,
+        0x7f0_0000,
+        SratEntry::ENABLED,
+    ));
+    body.extend(memory_affinity(
+        1,
+        0x800_0000,
+        0x800_0000,
+        SratEntry::ENABLED,
+    ));
+    // QEMU pads the table with disabled entries
+    body.extend(memory_affinity(0, 0, 0, 0));
+    table(b"SRAT", 1, b"BXPCSRAT", &body)
+}
+
+/// Event timer block id of QEMU's HPET, its registers at 0xfed00000 & no minimum tick
+fn qemu_hpet() -> Vec<u8> {
+    let mut body = 0x8086_a201u32.to_le_bytes().to_vec();
+    body.extend_from_slice(&[GenericAddress::SYSTEM_MEMORY, 0, 0, 0]);
+    body.extend_from_slice(&0xfed0_0000u64.to_le_bytes());
+    body.extend_from_slice(&[0, 0, 0, 0]);
+    table(b"HPET", 1, b"BXPCHPET", &body)
+}
+
+fn qemu_slit() -> Vec<u8> {
+    let mut body = 2u64.to_le_bytes().to_vec();
+    body.extend_from_slice(&[10, 20, 20, 10]);
+    table(b"SLIT", 1, b"BXPCSLIT", &body)
+}
+
+#[test]
+fn rsdp() {
+    let rsdp = Rsdp::parse(&firecracker_rsdp()).unwrap();
+    assert_eq!(&rsdp.oem_id, b"FIRECK");
+    assert_eq!(rsdp.revision, 2);
+    assert_eq!(rsdp.rsdt_address, 0);
+    assert_eq!(rsdp.xsdt_address, Some(FIRECRACKER_XSDT_ADDRESS));
+}
+
+#[test]
+fn rsdp_revision_0_has_no_xsdt() {
+    let mut bytes = firecracker_rsdp()[..20].to_vec();
+    bytes[15] = 0;
+    bytes[16..20].copy_from_slice(&0xa_0e00u32.to_le_bytes());
+    bytes[8] = 0;
+    bytes[8] = 0u8.wrapping_sub(checksum(&bytes));
+
+    let rsdp = Rsdp::parse(&bytes).unwrap();
+    assert_eq!(rsdp.rsdt_address, 0xa_0e00);
+    assert_eq!(rsdp.xsdt_address, None);
+}
+
+#[test]
+fn rsdp_rejects_bad_checksums() {
+    // covered by the checksum of revision 0
+    let mut bytes = firecracker_rsdp();
+    bytes[16] ^= 1;
+    assert_eq!(Rsdp::parse(&bytes), Err(TableError::BadChecksum));
+
+    // only covered by the extended checksum
+    let mut bytes = firecracker_rsdp();
+    bytes[24] ^= 1;
+    assert_eq!(Rsdp::parse(&bytes), Err(TableError::BadChecksum));
+}
+
+#[test]
+fn rsdp_rejects_bad_signature() {
+    let mut bytes = firecracker_rsdp();
+    bytes[0] = b'X';
+    assert_eq!(Rsdp::parse(&bytes), Err(TableError::BadSignature));
+}
+
+#[test]
+fn rsdp_rejects_truncated() {
+    let bytes = firecracker_rsdp();
+    assert_eq!(Rsdp::parse(&bytes[..19]), Err(TableError::TooShort));
+    assert_eq!(Rsdp::parse(&bytes[..30]), Err(TableError::TooShort));
+}
+
+#[test]
+fn xsdt() {
+    let bytes = firecracker_xsdt();
+    let xsdt = RootTable::parse(&bytes, 8).unwrap();
+    assert_eq!(&xsdt.sdt.header.oem_table_id, b"FCMVXSDT");
+    assert_eq!(xsdt.entries().collect::<Vec<_>>(), FIRECRACKER_TABLES);
+
+    // an XSDT isn't an RSDT
+    assert_eq!(
+        RootTable::parse(&bytes, 4).err(),
+        Some(TableError::BadSignature)
+    );
+}
+
+#[test]
+fn captured_tables_are_valid() {
+    for (bytes, signature) in [
+        (FIRECRACKER_MADT, madt::SIGNATURE),
+        (FIRECRACKER_FADT, fadt::SIGNATURE),
+        (FIRECRACKER_MCFG, mcfg::SIGNATURE),
+    ] {
+        let sdt = Sdt::parse_with_signature(bytes, signature).unwrap();
+        assert_eq!(sdt.bytes().len(), bytes.len());
+        assert_eq!(&sdt.header.oem_id, b"FIRECK");
+    }
+}
+
+#[test]
+fn sdt_rejects_bad_checksum() {
+    for bytes in [FIRECRACKER_MADT, FIRECRACKER_FADT, FIRECRACKER_MCFG] {
+        let mut bytes = bytes.to_vec();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x80;
+        assert_eq!(Sdt::parse(&bytes).err(), Some(TableError::BadChecksum));
+    }
+}
+
+#[test]
+fn sdt_rejects_truncated() {
+    // shorter than the length in the header
+    let bytes = &FIRECRACKER_MADT[..FIRECRACKER_MADT.len() - 1];
+    assert_eq!(Sdt::parse(bytes).err(), Some(TableError::TooShort));
+
+    // shorter than the header
+    assert_eq!(
+        Sdt::parse(&FIRECRACKER_MADT[..20]).err(),
+        Some(TableError::TooShort)
+    );
+
+    // claims to be shorter than its header
+    let mut bytes = FIRECRACKER_MADT.to_vec();
+    bytes[4..8].copy_from_slice(&16u32.to_le_bytes());
+    assert_eq!(Sdt::parse(&bytes).err(), Some(TableError::TooShort));
+}
+
+#[test]
+fn sdt_rejects_wrong_signature() {
+    assert_eq!(
+        Sdt::parse_with_signature(FIRECRACKER_MADT, fadt::SIGNATURE).err(),
+        Some(TableError::BadSignature)
+    );
+
+    let sdt = Sdt::parse(FIRECRACKER_FADT).unwrap();
+    assert_eq!(Madt::parse(sdt).err(), Some(TableError::BadSignature));
+}
+
+#[test]
+fn madt() {
+    let madt = Madt::parse(Sdt::parse(FIRECRACKER_MADT).unwrap()).unwrap();
+    assert_eq!(madt.local_apic_address(), 0xfee0_0000);
+    assert_eq!(
+        madt.entries().collect::<Vec<_>>(),
+        [
+            MadtEntry::IoApic {
+                id: 0,
+                address: 0xfec0_0000,
+                gsi_base: 0,
+            },
+            MadtEntry::LocalApic {
+                processor_uid: 0,
+                apic_id: 0,
+                flags: MadtEntry::LOCAL_APIC_ENABLED,
+            },
+        ]
+    );
+    assert_eq!(madt.io_apics().collect::<Vec<_>>(), [(0, 0xfec0_0000, 0)]);
+    assert_eq!(madt.processors().collect::<Vec<_>>(), [0]);
+
+    // no interrupt source override, ISA IRQs are identity mapped
+    let (gsi, flags) = madt.isa_irq(4);
+    assert_eq!(gsi, 4);
+    assert_eq!(flags.active_low(), None);
+    assert_eq!(flags.level_triggered(), None);
+}
+
+#[test]
+fn madt_interrupt_source_overrides() {
+    // the timer & the SCI overrides of QEMU
+    let mut body = FIRECRACKER_MADT[SdtHeader::SIZE..].to_vec();
+    body.extend_from_slice(&[2, 10, 0, 0, 2, 0, 0, 0, 0, 0]);
+    body.extend_from_slice(&[2, 10, 0, 9, 9, 0, 0, 0, 0x0d, 0]);
+    let bytes = table(b"APIC", 6, b"BXPCAPIC", &body);
+    let madt = Madt::parse(Sdt::parse(&bytes).unwrap()).unwrap();
+
+    let (gsi, flags) = madt.isa_irq(0);
+    assert_eq!(
+        (gsi, flags.active_low(), flags.level_triggered()),
+        (2, None, None)
+    );
+
+    let (gsi, flags) = madt.isa_irq(9);
+    assert_eq!(gsi, 9);
+    assert_eq!(flags.active_low(), Some(false));
+    assert_eq!(flags.level_triggered(), Some(true));
+}
+
+#[test]
+fn madt_stops_at_malformed_entry() {
+    // an entry claiming to be longer than the table ends the list
+    let mut body = FIRECRACKER_MADT[SdtHeader::SIZE..].to_vec();
+    body.extend_from_slice(&[0, 200, 1, 1]);
+    let bytes = table(b"APIC", 6, b"BXPCAPIC", &body);
+    let madt = Madt::parse(Sdt::parse(&bytes).unwrap()).unwrap();
+    assert_eq!(madt.entries().count(), 2);
+}
+
+#[test]
+fn madt_rejects_truncated() {
+    let bytes = table(b"APIC", 6, b"BXPCAPIC", &[0; 6]);
+    let sdt = Sdt::parse(&bytes).unwrap();
+    assert_eq!(Madt::parse(sdt).err(), Some(TableError::TooShort));
+}
+
+#[test]
+fn fadt() {
+    let fadt = Fadt::parse(Sdt::parse(FIRECRACKER_FADT).unwrap()).unwrap();
+    assert_eq!(fadt.revision, 6);
+    assert_eq!(fadt.dsdt, 0x9_fd30);
+    assert_eq!(fadt.firmware_ctrl, 0);
+    assert_eq!(fadt.sci_interrupt, 0);
+    assert_eq!(fadt.pm_timer_block, 0);
+    assert_ne!(fadt.flags & Fadt::HW_REDUCED_ACPI, 0);
+    // the reset register is given but not supported
+    assert!(fadt.reset.is_some());
+    assert_eq!(fadt.reset_register(), None);
+}
+
+#[test]
+fn fadt_rejects_truncated() {
+    // a revision 1 FADT stops after the flags, a table stopping before the century is invalid
+    let body = &FIRECRACKER_FADT[SdtHeader::SIZE..100];
+    let bytes = table(b"FACP", 1, b"BXPCFACP", body);
+    assert_eq!(
+        Fadt::parse(Sdt::parse(&bytes).unwrap()),
+        Err(TableError::TooShort)
+    );
+
+    let body = &FIRECRACKER_FADT[SdtHeader::SIZE..116];
+    let bytes = table(b"FACP", 1, b"BXPCFACP", body);
+    let fadt = Fadt::parse(Sdt::parse(&bytes).unwrap()).unwrap();
+    assert_eq!(fadt.reset, None);
+}
+
+#[test]
+fn mcfg() {
+    let mcfg = Mcfg::parse(Sdt::parse(FIRECRACKER_MCFG).unwrap()).unwrap();
+    assert_eq!(
+        mcfg.entries().collect::<Vec<_>>(),
+        [McfgEntry {
+            base_address: 0xeec0_0000,
+            segment: 0,
+            start_bus: 0,
+            end_bus: 0,
+        }]
+    );
+}
+
+#[test]
+fn hpet() {
+    let bytes = qemu_hpet();
+    let hpet = Hpet::parse(Sdt::parse(&bytes).unwrap()).unwrap();
+    assert_eq!(
+        hpet,
+        Hpet {
+            event_timer_block_id: 0x8086_a201,
+            base_address: GenericAddress {
+                address_space: GenericAddress::SYSTEM_MEMORY,
+                bit_width: 0,
+                bit_offset: 0,
+                access_size: 0,
+                address: 0xfed0_0000,
+            },
+            hpet_number: 0,
+            minimum_tick: 0,
+            page_protection: 0,
+        }
+    );
+}
+
+#[test]
+fn hpet_rejects_truncated() {
+    let bytes = qemu_hpet();
+    let body = &bytes[SdtHeader::SIZE..bytes.len() - 1];
+    let truncated = table(b"HPET", 1, b"BXPCHPET", body);
+    assert_eq!(
+        Hpet::parse(Sdt::parse(&truncated).unwrap()),
+        Err(TableError::TooShort)
+    );
+}
+
+#[test]
+fn hpet_rejects_bad_checksum() {
+    let mut bytes = qemu_hpet();
+    bytes[SdtHeader::SIZE + 4] ^= 1;
+    assert_eq!(Sdt::parse(&bytes).err(), Some(TableError::BadChecksum));
+}
+
+#[test]
+fn hpet_rejects_wrong_signature() {
+    let sdt = Sdt::parse(FIRECRACKER_MCFG).unwrap();
+    assert_eq!(Hpet::parse(sdt), Err(TableError::BadSignature));
+}
+
+#[test]
+fn srat() {
+    let bytes = qemu_srat();
+    let srat = Srat::parse(Sdt::parse(&bytes).unwrap()).unwrap();
+    let entries = srat.entries().collect::<Vec<_>>();
+
+    // the disabled entry is skipped
+    assert_eq!(entries.len(), 5);
+    assert_eq!(
+        entries[1],
+        SratEntry::ProcessorAffinity {
+            proximity_domain: 1,
+            apic_id: 1,
+            flags: SratEntry::ENABLED,
+        }
+    );
+    assert_eq!(
+        entries[4],
+        SratEntry::MemoryAffinity {
+            proximity_domain: 1,
+            base_address: 0x800_0000,
+            length: 0x800_0000,
+            flags: SratEntry::ENABLED,
+        }
+    );
+}
+
+#[test]
+fn srat_processor_domain_is_split() {
+    let mut body = vec![1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+    body.extend(processor_affinity(0x0102_0304, 7));
+    let bytes = table(b"SRAT", 3, b"BXPCSRAT", &body);
+    let srat = Srat::parse(Sdt::parse(&bytes).unwrap()).unwrap();
+    assert_eq!(
+        srat.entries().collect::<Vec<_>>(),
+        [SratEntry::ProcessorAffinity {
+            proximity_domain: 0x0102_0304,
+            apic_id: 7,
+            flags: SratEntry::ENABLED,
+        }]
+    );
+}
+
+#[test]
+fn slit() {
+    let bytes = qemu_slit();
+    let slit = Slit::parse(Sdt::parse(&bytes).unwrap()).unwrap();
+    assert_eq!(slit.locality_count, 2);
+    assert_eq!(slit.distance(0, 0), Some(10));
+    assert_eq!(slit.distance(0, 1), Some(20));
+    assert_eq!(slit.distance(1, 0), Some(20));
+    assert_eq!(slit.distance(2, 0), None);
+}
+
+#[test]
+fn slit_rejects_truncated() {
+    // 3 localities need a 9 bytes matrix
+    let mut body = 3u64.to_le_bytes().to_vec();
+    body.extend_from_slice(&[10, 20, 20, 10]);
+    let bytes = table(b"SLIT", 1, b"BXPCSLIT", &body);
+    assert_eq!(
+        Slit::parse(Sdt::parse(&bytes).unwrap()).err(),
+        Some(TableError::TooShort)
+    );
+}
@@ -3,3 +3,4 @@ pub mod interrupts;
 pub mod io;
 pub mod msr;
 pub mod paging;
+pub mod tsc;
@@ -1,6 +1,10 @@
 #[macro_export]
 macro_rules! cpuid {
-    ($addr:expr) => {{
+    ($addr:expr) => {
+        $crate::cpuid!($addr, 0u32)
+    };
+
+    ($addr:expr, $subleaf:expr) => {{
         let eax : u32;
         let ebx : u32;
         let ecx : u32;
@@ -13,7 +17,7 @@ macro_rules! cpuid {
                              "pop rbx",
                              out(reg) ebx,
                              inout("eax") $addr => eax,
-                             out("ecx") ecx,
+                             inout("ecx") $subleaf => ecx,
                              out("edx") edx);
         };
 
@@ -1,3 +1,5 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+
 #[macro_export]
 macro_rules! int {
     ($vector:expr) => {
@@ -36,8 +38,47 @@ macro_rules! isr {
                 use lib::{enable_interrupts, disable_interrupts};
                 disable_interrupts!();
                 f();
+                lib::asm::interrupts::run_interrupt_exit_hook();
                 enable_interrupts!();
             }
         })*
     };
 }
+
+#[macro_export]
+macro_rules! interrupts_enabled {
+    () => {{
+        let rflags: u64;
+        core::arch::asm!("pushfq", "pop {}", out(reg) rflags);
+        (rflags & (1 << 9)) != 0
+    }};
+}
+
+/// Run `f` with interrupts disabled on the current CPU, restoring the previous state afterward
+pub fn without_interrupts<R>(f: impl FnOnce() -> R) -> R {
+    unsafe {
+        let were_enabled = interrupts_enabled!();
+        disable_interrupts!();
+        let result = f();
+        if were_enabled {
+            enable_interrupts!();
+        }
+        result
+    }
+}
+
+static INTERRUPT_EXIT_HOOK: AtomicUsize = AtomicUsize::new(0);
+
+/// Set a function called at the end of every `isr!` handler, with interrupts disabled
+pub fn set_interrupt_exit_hook(hook: fn()) {
+    INTERRUPT_EXIT_HOOK.store(hook as usize, Ordering::Release);
+}
+
+#[doc(hidden)]
+pub fn run_interrupt_exit_hook() {
+    let hook = INTERRUPT_EXIT_HOOK.load(Ordering::Acquire);
+    if hook != 0 {
+        let hook: fn() = unsafe { core::mem::transmute(hook) };
+        hook();
+    }
+}
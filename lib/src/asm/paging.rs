@@ -15,3 +15,49 @@ macro_rules! get_cr3 {
         }
     }
 }
+
+#[macro_export]
+macro_rules! set_cr0 {
+    ($value:expr) => {
+        core::arch::asm!("mov cr0, {}", in(reg) $value);
+    }
+}
+
+#[macro_export]
+macro_rules! get_cr0 {
+    () => {
+        {
+            let cr0: usize;
+            core::arch::asm!("mov {}, cr0", out(reg) cr0);
+            cr0
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! set_cr4 {
+    ($value:expr) => {
+        core::arch::asm!("mov cr4, {}", in(reg) $value);
+    }
+}
+
+#[macro_export]
+macro_rules! get_cr4 {
+    () => {
+        {
+            let cr4: usize;
+            core::arch::asm!("mov {}, cr4", out(reg) cr4);
+            cr4
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! invpcid {
+    ($kind:expr, $pcid:expr, $addr:expr) => {
+        {
+            let descriptor: [u64; 2] = [$pcid as u64, $addr as u64];
+            core::arch::asm!("invpcid {}, [{}]", in(reg) $kind as u64, in(reg) &descriptor);
+        }
+    }
+}
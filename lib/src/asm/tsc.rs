@@ -0,0 +1,9 @@
+#[macro_export]
+macro_rules! rdtsc {
+    () => {{
+        let eax: u32;
+        let edx: u32;
+        unsafe { core::arch::asm!("rdtsc", out("edx") edx, out("eax") eax) }
+        (u64::from(edx) << 32) | u64::from(eax)
+    }};
+}
@@ -0,0 +1,45 @@
+//! Parsers of the archives of the initial ramdisk, `newc` cpio & ustar. They hand every entry to
+//! a callback, building a filesystem out of them is left to the kernel.
+
+pub mod cpio;
+pub mod tar;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ArchiveError {
+    /// Neither a cpio nor a tar archive
+    UnknownFormat,
+    BadHeader,
+    BadChecksum,
+    /// An entry goes past the end of the archive
+    Truncated,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    File,
+    Directory,
+    Symlink,
+}
+
+/// File of an archive, `data` is the target of symbolic links. The path is relative to the root
+/// of the archive & may only live as long as the callback.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Entry<'p, 'a> {
+    pub path: &'p str,
+    pub kind: EntryKind,
+    pub data: &'a [u8],
+}
+
+/// Call `f` with the entries of a cpio or a tar archive, in the order they are stored
+pub fn parse<'a>(archive: &'a [u8], f: impl FnMut(Entry<'_, 'a>)) -> Result<(), ArchiveError> {
+    if cpio::is_cpio(archive) {
+        cpio::parse(archive, f)
+    } else if tar::is_tar(archive) {
+        tar::parse(archive, f)
+    } else {
+        Err(ArchiveError::UnknownFormat)
+    }
+}
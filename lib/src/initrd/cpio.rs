@@ -0,0 +1,81 @@
+//! `newc` cpio archives, as made by `find . | cpio -o -H newc`
+
+use super::{ArchiveError, Entry, EntryKind};
+
+use core::convert::TryFrom;
+
+const MAGIC: &[u8] = b"070701";
+/// Same format with a checksum of the data, it isn't verified
+const MAGIC_CRC: &[u8] = b"070702";
+const HEADER_SIZE: usize = 110;
+const TRAILER: &str = "TRAILER!!!";
+
+const MODE_TYPE_MASK: u32 = 0o170_000;
+const MODE_DIRECTORY: u32 = 0o040_000;
+const MODE_FILE: u32 = 0o100_000;
+const MODE_SYMLINK: u32 = 0o120_000;
+
+pub fn is_cpio(archive: &[u8]) -> bool {
+    archive.starts_with(MAGIC) || archive.starts_with(MAGIC_CRC)
+}
+
+pub fn parse<'a>(archive: &'a [u8], mut f: impl FnMut(Entry<'_, 'a>)) -> Result<(), ArchiveError> {
+    let mut offset = 0;
+    loop {
+        let header = archive
+            .get(offset..offset + HEADER_SIZE)
+            .ok_or(ArchiveError::Truncated)?;
+        if !is_cpio(header) {
+            return Err(ArchiveError::BadHeader);
+        }
+
+        // every field is 8 hexadecimal digits, after the magic
+        let field = |index: usize| -> Result<usize, ArchiveError> {
+            let start = MAGIC.len() + index * 8;
+            let digits = core::str::from_utf8(&header[start..start + 8])
+                .map_err(|_| ArchiveError::BadHeader)?;
+            let value = u32::from_str_radix(digits, 16).map_err(|_| ArchiveError::BadHeader)?;
+            Ok(usize::try_from(value).unwrap())
+        };
+
+        let mode = u32::try_from(field(1)?).unwrap();
+        let size = field(6)?;
+        let name_size = field(11)?;
+
+        let name_start = offset + HEADER_SIZE;
+        let name = archive
+            .get(name_start..name_start + name_size)
+            .ok_or(ArchiveError::Truncated)?;
+        // the size includes the nul terminator
+        let name = core::str::from_utf8(&name[..name_size.saturating_sub(1)])
+            .map_err(|_| ArchiveError::BadHeader)?;
+
+        let data_start = align4(name_start + name_size);
+        let data = archive
+            .get(data_start..data_start + size)
+            .ok_or(ArchiveError::Truncated)?;
+        offset = align4(data_start + size);
+
+        if name == TRAILER {
+            return Ok(());
+        }
+
+        let kind = match mode & MODE_TYPE_MASK {
+            MODE_DIRECTORY => EntryKind::Directory,
+            MODE_FILE => EntryKind::File,
+            MODE_SYMLINK => EntryKind::Symlink,
+            // devices, FIFOs & sockets have no use without a userspace
+            _ => continue,
+        };
+
+        f(Entry {
+            path: name,
+            kind,
+            data,
+        });
+    }
+}
+
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
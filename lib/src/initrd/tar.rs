@@ -0,0 +1,114 @@
+//! POSIX ustar archives, as made by `tar --format=ustar -cf`
+
+use super::{ArchiveError, Entry, EntryKind};
+
+use core::convert::TryFrom;
+
+const BLOCK_SIZE: usize = 512;
+const MAGIC: &[u8] = b"ustar";
+const MAGIC_OFFSET: usize = 257;
+const NAME_SIZE: usize = 100;
+const PREFIX_SIZE: usize = 155;
+
+const TYPE_FILE: u8 = b'0';
+/// Files of old archives have no type
+const TYPE_OLD_FILE: u8 = 0;
+const TYPE_SYMLINK: u8 = b'2';
+const TYPE_DIRECTORY: u8 = b'5';
+
+pub fn is_tar(archive: &[u8]) -> bool {
+    archive.get(MAGIC_OFFSET..MAGIC_OFFSET + MAGIC.len()) == Some(MAGIC)
+}
+
+pub fn parse<'a>(archive: &'a [u8], mut f: impl FnMut(Entry<'_, 'a>)) -> Result<(), ArchiveError> {
+    let mut offset = 0;
+    loop {
+        let header = archive
+            .get(offset..offset + BLOCK_SIZE)
+            .ok_or(ArchiveError::Truncated)?;
+
+        // the archive ends with two empty blocks
+        if header.iter().all(|&byte| byte == 0) {
+            return Ok(());
+        }
+
+        if !is_tar(header) {
+            return Err(ArchiveError::BadHeader);
+        }
+
+        if octal(&header[148..156])? != checksum(header) {
+            return Err(ArchiveError::BadChecksum);
+        }
+
+        let size = octal(&header[124..136])?;
+        let data_start = offset + BLOCK_SIZE;
+        let data = archive
+            .get(data_start..data_start + size)
+            .ok_or(ArchiveError::Truncated)?;
+        offset = data_start + size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+
+        let kind = match header[156] {
+            TYPE_FILE | TYPE_OLD_FILE => EntryKind::File,
+            TYPE_DIRECTORY => EntryKind::Directory,
+            TYPE_SYMLINK => EntryKind::Symlink,
+            // hard links, devices & extended headers aren't supported
+            _ => continue,
+        };
+
+        let prefix = string(&header[345..345 + PREFIX_SIZE])?;
+        let name = string(&header[0..NAME_SIZE])?;
+        let data = match kind {
+            EntryKind::Symlink => string(&header[157..257])?.as_bytes(),
+            _ => data,
+        };
+
+        // long paths are split in two, the prefix holds the directories
+        let mut joined = [0; PREFIX_SIZE + 1 + NAME_SIZE];
+        let path = if prefix.is_empty() {
+            name
+        } else {
+            join(&mut joined, prefix, name)
+        };
+
+        f(Entry { path, kind, data });
+    }
+}
+
+/// `prefix/name` in `buffer`, which is big enough for the longest prefix & name
+fn join<'b>(buffer: &'b mut [u8], prefix: &str, name: &str) -> &'b str {
+    let length = prefix.len() + 1 + name.len();
+    buffer[..prefix.len()].copy_from_slice(prefix.as_bytes());
+    buffer[prefix.len()] = b'/';
+    buffer[prefix.len() + 1..length].copy_from_slice(name.as_bytes());
+    // two strings joined by a slash are valid UTF-8
+    core::str::from_utf8(&buffer[..length]).unwrap()
+}
+
+/// Nul-terminated or nul-padded string field
+fn string(field: &[u8]) -> Result<&str, ArchiveError> {
+    let length = field.iter().position(|&c| c == 0).unwrap_or(field.len());
+    core::str::from_utf8(&field[..length]).map_err(|_| ArchiveError::BadHeader)
+}
+
+/// Octal number field, padded with spaces or nul characters
+fn octal(field: &[u8]) -> Result<usize, ArchiveError> {
+    let digits = core::str::from_utf8(field).map_err(|_| ArchiveError::BadHeader)?;
+    let digits = digits.trim_matches(|c| c == ' ' || c == '\0');
+    let value = u64::from_str_radix(digits, 8).map_err(|_| ArchiveError::BadHeader)?;
+    usize::try_from(value).map_err(|_| ArchiveError::BadHeader)
+}
+
+/// Sum of the header bytes, with the checksum field counted as spaces
+fn checksum(header: &[u8]) -> usize {
+    header
+        .iter()
+        .enumerate()
+        .map(|(index, &byte)| {
+            if (148..156).contains(&index) {
+                usize::from(b' ')
+            } else {
+                usize::from(byte)
+            }
+        })
+        .sum()
+}
@@ -0,0 +1,221 @@
+//! `lambix.tar` was made by GNU tar 1.34 with `--format=ustar --blocking-factor=1`, `lambix.cpio`
+//! by bsdtar with `--format newc`, out of the same tree: two directories with a file each, a
+//! symbolic link & a file whose path is too long for the name field of ustar headers. The broken
+//! archives are edited copies of these.
+
+use super::*;
+
+const LAMBIX_TAR: &[u8] = include_bytes!("../../testdata/initrd/lambix.tar");
+const LAMBIX_CPIO: &[u8] = include_bytes!("../../testdata/initrd/lambix.cpio");
+
+const LONG_DIRECTORY: &str =
+    "usr/share/lambix/documentation-that-is-nested-deep-enough-for-ustar-to-split-its-path-in-two";
+const INIT: &[u8] = b"#!/bin/sh\necho hello\n";
+const HOSTNAME: &[u8] = b"lambix\n";
+const README: &[u8] = b"the name field only holds 100 bytes\n";
+
+const TAR_BLOCK_SIZE: usize = 512;
+/// Offset of the header of `bin/init` in `lambix.tar`
+const TAR_INIT_HEADER: usize = 512;
+const CPIO_HEADER_SIZE: usize = 110;
+
+type Entries = Vec<(String, EntryKind, Vec<u8>)>;
+
+fn entries(archive: &[u8]) -> (Entries, Result<(), ArchiveError>) {
+    let mut entries = Vec::new();
+    let result = parse(archive, |entry| {
+        entries.push((String::from(entry.path), entry.kind, entry.data.to_vec()))
+    });
+    (entries, result)
+}
+
+/// The entries of the test tree, directories named with `directory_suffix`
+fn expected(directory_suffix: &str) -> Entries {
+    let directory = |path: &str| {
+        (
+            format!("{}{}", path, directory_suffix),
+            EntryKind::Directory,
+            Vec::new(),
+        )
+    };
+    let file = |path: &str, data: &[u8]| (String::from(path), EntryKind::File, data.to_vec());
+
+    vec![
+        directory("bin"),
+        file("bin/init", INIT),
+        directory("etc"),
+        file("etc/hostname", HOSTNAME),
+        (String::from("sbin"), EntryKind::Symlink, b"bin".to_vec()),
+        directory("usr"),
+        directory("usr/share"),
+        directory("usr/share/lambix"),
+        directory(LONG_DIRECTORY),
+        file(&format!("{}/README.txt", LONG_DIRECTORY), README),
+    ]
+}
+
+/// Offset of the cpio header of the entry named `name`
+fn cpio_header(archive: &[u8], name: &str) -> usize {
+    let mut needle = name.as_bytes().to_vec();
+    needle.push(0);
+    archive
+        .windows(needle.len())
+        .position(|window| window == &needle[..])
+        .unwrap()
+        - CPIO_HEADER_SIZE
+}
+
+/// Write the checksum of a ustar header like tar does, 6 octal digits, a nul & a space
+fn set_tar_checksum(header: &mut [u8]) {
+    header[148..156].copy_from_slice(b"        ");
+    let sum: usize = header.iter().map(|&byte| usize::from(byte)).sum();
+    header[148..156].copy_from_slice(format!("{:06o}\0 ", sum).as_bytes());
+}
+
+#[test]
+fn cpio_newc() {
+    assert!(cpio::is_cpio(LAMBIX_CPIO));
+    assert!(!tar::is_tar(LAMBIX_CPIO));
+    assert_eq!(entries(LAMBIX_CPIO), (expected(""), Ok(())));
+}
+
+#[test]
+fn cpio_with_checksums() {
+    // the `070702` format only adds a checksum of the data, which isn't verified
+    let mut archive = LAMBIX_CPIO.to_vec();
+    for offset in 0..archive.len() - 6 {
+        if archive[offset..].starts_with(b"070701") {
+            archive[offset..offset + 6].copy_from_slice(b"070702");
+        }
+    }
+    assert_eq!(entries(&archive), (expected(""), Ok(())));
+}
+
+#[test]
+fn cpio_skips_devices() {
+    let mut archive = LAMBIX_CPIO.to_vec();
+    let mode = cpio_header(&archive, "etc/hostname") + 6 + 8;
+    // character device
+    archive[mode..mode + 8].copy_from_slice(b"000021a4");
+
+    let mut expected = expected("");
+    expected.remove(3);
+    assert_eq!(entries(&archive), (expected, Ok(())));
+}
+
+#[test]
+fn cpio_rejects_truncated() {
+    let end = cpio_header(LAMBIX_CPIO, "TRAILER!!!") + CPIO_HEADER_SIZE + "TRAILER!!!\0".len();
+    for length in 0..end {
+        let (_, result) = entries(&LAMBIX_CPIO[..length]);
+        // an empty archive is no archive
+        let error = if length < 6 {
+            ArchiveError::UnknownFormat
+        } else {
+            ArchiveError::Truncated
+        };
+        assert_eq!(result, Err(error), "{} bytes", length);
+    }
+}
+
+#[test]
+fn cpio_rejects_bad_header() {
+    let mut archive = LAMBIX_CPIO.to_vec();
+    let size = cpio_header(&archive, "bin/init") + 6 + 6 * 8;
+    archive[size] = b'g';
+    let (entries_before, result) = entries(&archive);
+    assert_eq!(entries_before, expected("")[..1]);
+    assert_eq!(result, Err(ArchiveError::BadHeader));
+
+    let mut archive = LAMBIX_CPIO.to_vec();
+    let header = cpio_header(&archive, "etc");
+    archive[header..header + 6].copy_from_slice(b"070707");
+    assert_eq!(entries(&archive).1, Err(ArchiveError::BadHeader));
+}
+
+#[test]
+fn tar_ustar() {
+    assert!(tar::is_tar(LAMBIX_TAR));
+    assert!(!cpio::is_cpio(LAMBIX_TAR));
+    assert_eq!(entries(LAMBIX_TAR), (expected("/"), Ok(())));
+}
+
+#[test]
+fn tar_joins_prefix_and_name() {
+    // the archive stores `README.txt` in the name & the directories in the prefix
+    let header = LAMBIX_TAR.len() - 4 * TAR_BLOCK_SIZE;
+    assert_eq!(&LAMBIX_TAR[header..header + 11], b"README.txt\0");
+
+    let (entries, _) = entries(LAMBIX_TAR);
+    assert_eq!(entries[9].0, format!("{}/README.txt", LONG_DIRECTORY));
+}
+
+#[test]
+fn tar_joins_longest_prefix_and_name() {
+    let mut archive = LAMBIX_TAR[TAR_INIT_HEADER..TAR_INIT_HEADER + 2 * TAR_BLOCK_SIZE].to_vec();
+    archive.extend_from_slice(&[0; 2 * TAR_BLOCK_SIZE]);
+    let prefix = "p".repeat(155);
+    let name = "n".repeat(100);
+    archive[0..100].copy_from_slice(name.as_bytes());
+    archive[345..500].copy_from_slice(prefix.as_bytes());
+    set_tar_checksum(&mut archive[..TAR_BLOCK_SIZE]);
+
+    let path = format!("{}/{}", prefix, name);
+    assert_eq!(
+        entries(&archive),
+        (vec![(path, EntryKind::File, INIT.to_vec())], Ok(()))
+    );
+}
+
+#[test]
+fn tar_skips_hard_links() {
+    let mut archive = LAMBIX_TAR.to_vec();
+    let header = &mut archive[TAR_INIT_HEADER..TAR_INIT_HEADER + TAR_BLOCK_SIZE];
+    header[156] = b'1';
+    set_tar_checksum(header);
+
+    let mut expected = expected("/");
+    expected.remove(1);
+    assert_eq!(entries(&archive), (expected, Ok(())));
+}
+
+#[test]
+fn tar_rejects_bad_checksum() {
+    let mut archive = LAMBIX_TAR.to_vec();
+    archive[TAR_INIT_HEADER] = b'B';
+    let (entries_before, result) = entries(&archive);
+    assert_eq!(entries_before, expected("/")[..1]);
+    assert_eq!(result, Err(ArchiveError::BadChecksum));
+
+    let mut archive = LAMBIX_TAR.to_vec();
+    archive[TAR_INIT_HEADER + 148] = b'9';
+    assert_eq!(entries(&archive).1, Err(ArchiveError::BadHeader));
+}
+
+#[test]
+fn tar_rejects_truncated() {
+    // in the data of `bin/init`
+    let data = TAR_INIT_HEADER + TAR_BLOCK_SIZE;
+    assert_eq!(
+        entries(&LAMBIX_TAR[..data + INIT.len() - 1]).1,
+        Err(ArchiveError::Truncated)
+    );
+    // in a header
+    assert_eq!(
+        entries(&LAMBIX_TAR[..data + TAR_BLOCK_SIZE + 100]).1,
+        Err(ArchiveError::Truncated)
+    );
+    // without the empty blocks at the end
+    let (entries, result) = entries(&LAMBIX_TAR[..LAMBIX_TAR.len() - 2 * TAR_BLOCK_SIZE]);
+    assert_eq!(entries, expected("/"));
+    assert_eq!(result, Err(ArchiveError::Truncated));
+}
+
+#[test]
+fn unknown_format() {
+    assert_eq!(
+        entries(&[0; 2 * TAR_BLOCK_SIZE]).1,
+        Err(ArchiveError::UnknownFormat)
+    );
+    assert_eq!(entries(b"").1, Err(ArchiveError::UnknownFormat));
+}
@@ -1,6 +1,11 @@
-#![no_std]
+#![cfg_attr(not(test), no_std)]
 
+pub mod acpi;
 pub mod asm;
 pub mod ffi;
+pub mod initrd;
+pub mod multiboot;
 pub mod per_cpu;
 pub mod sync;
+
+pub use asm::interrupts::without_interrupts;
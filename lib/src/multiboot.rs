@@ -0,0 +1,59 @@
+//! Multiboot2 tag parsers. They only work on the data of a tag, after its type & size header,
+//! walking the boot information & mapping what the tags point to is left to the kernel.
+
+pub mod memmap;
+pub mod tags;
+
+#[cfg(test)]
+mod tests;
+
+pub use memmap::{Memory, MemoryMap, MemoryType};
+pub use tags::*;
+
+use core::convert::TryFrom;
+
+fn field<const N: usize>(data: &[u8], offset: usize) -> Option<[u8; N]> {
+    data.get(offset..offset + N)
+        .and_then(|field| <[u8; N]>::try_from(field).ok())
+}
+
+fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    field(data, offset).map(u16::from_le_bytes)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    field(data, offset).map(u32::from_le_bytes)
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    field(data, offset).map(u64::from_le_bytes)
+}
+
+/// Physical address stored on 64 bits
+fn read_address(data: &[u8], offset: usize) -> Option<usize> {
+    usize::try_from(read_u64(data, offset)?).ok()
+}
+
+/// Physical address stored on 32 bits
+fn read_address32(data: &[u8], offset: usize) -> Option<usize> {
+    usize::try_from(read_u32(data, offset)?).ok()
+}
+
+/// A nul-terminated UTF-8 string, the terminator isn't included
+pub fn c_str(data: &[u8]) -> Option<&str> {
+    let length = data.iter().position(|&c| c == 0).unwrap_or(data.len());
+    core::str::from_utf8(&data[..length]).ok()
+}
+
+/// Physical address in a 32-bit or 64-bit pointer tag, like the EFI system table
+pub fn pointer(data: &[u8], is_64: bool) -> Option<usize> {
+    if is_64 {
+        read_address(data, 0)
+    } else {
+        read_address32(data, 0)
+    }
+}
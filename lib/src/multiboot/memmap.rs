@@ -0,0 +1,78 @@
+use super::{read_address, read_u32, read_u64};
+
+use core::convert::TryFrom;
+
+const ENTRY_VERSION: u32 = 0;
+/// Size of the version 0 entries, later versions can only add fields at the end
+const ENTRY_SIZE: usize = 24;
+const HEADER_SIZE: usize = 8;
+
+/// Memory map the bootloader got from the firmware
+#[derive(Copy, Clone, Debug)]
+pub struct MemoryMap<'t> {
+    entry_size: usize,
+    entries: &'t [u8],
+}
+
+impl<'t> MemoryMap<'t> {
+    /// Only version 0 of the entries is known, they can't be smaller than its entries
+    pub fn from_bytes(data: &'t [u8]) -> Option<MemoryMap<'t>> {
+        let entry_size = usize::try_from(read_u32(data, 0)?).ok()?;
+        let entry_version = read_u32(data, 4)?;
+        let entries = data.get(HEADER_SIZE..)?;
+
+        if entry_version != ENTRY_VERSION
+            || entry_size < ENTRY_SIZE
+            || entries.len() % entry_size != 0
+        {
+            return None;
+        }
+
+        Some(MemoryMap {
+            entry_size,
+            entries,
+        })
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = Memory> + 't {
+        self.entries
+            .chunks_exact(self.entry_size)
+            .filter_map(|entry| {
+                Some(Memory {
+                    base_addr: read_address(entry, 0)?,
+                    length: read_u64(entry, 8)?,
+                    mem_type: MemoryType::from(read_u32(entry, 16)?),
+                })
+            })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Memory {
+    /// Physical address of the start of the region
+    pub base_addr: usize,
+    pub length: u64,
+    pub mem_type: MemoryType,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemoryType {
+    /// Any type not listed here, like 2
+    Reserved,
+    AvailableRAM,
+    ACPIInformation,
+    ReservedToPreserve,
+    DefectiveRAM,
+}
+
+impl From<u32> for MemoryType {
+    fn from(value: u32) -> MemoryType {
+        match value {
+            1 => MemoryType::AvailableRAM,
+            3 => MemoryType::ACPIInformation,
+            4 => MemoryType::ReservedToPreserve,
+            5 => MemoryType::DefectiveRAM,
+            _ => MemoryType::Reserved,
+        }
+    }
+}
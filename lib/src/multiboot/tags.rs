@@ -0,0 +1,398 @@
+//! Typed content of the multiboot2 tags. Each structure is parsed from the data of its tag, after
+//! the type & size header, & checks its size & version.
+
+use super::{c_str, read_address, read_address32, read_u16, read_u32, read_u64, read_u8};
+
+use core::convert::TryFrom;
+use core::ops::Range;
+
+const BIOS_NO_PARTITION: u32 = 0xffff_ffff;
+
+const ELF64_SECTION_HEADER_SIZE: usize = 64;
+const SHT_SYMTAB: u32 = 2;
+
+const EFI_MEMORY_DESCRIPTOR_VERSION: u32 = 1;
+const EFI_MEMORY_DESCRIPTOR_SIZE: usize = 40;
+
+const SMBIOS2_ANCHOR: &[u8] = b"_SM_";
+const SMBIOS3_ANCHOR: &[u8] = b"_SM3_";
+
+/// A file loaded in physical memory by the bootloader
+#[derive(Copy, Clone, Debug)]
+pub struct Module<'t> {
+    pub start: usize,
+    pub end: usize,
+    /// String given with the module, without the nul terminator
+    pub cmdline: &'t str,
+}
+
+impl<'t> Module<'t> {
+    pub fn from_bytes(data: &'t [u8]) -> Option<Module<'t>> {
+        Some(Module {
+            start: read_address32(data, 0)?,
+            end: read_address32(data, 4)?,
+            cmdline: c_str(data.get(8..)?)?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.end.saturating_sub(self.start)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Amount of lower & upper memory, in KiB
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BasicMemInfo {
+    /// Memory starting at 0, at most 640 KiB
+    pub lower: u32,
+    /// Memory starting at 1 MiB, up to the first hole
+    pub upper: u32,
+}
+
+impl BasicMemInfo {
+    pub fn from_bytes(data: &[u8]) -> Option<BasicMemInfo> {
+        Some(BasicMemInfo {
+            lower: read_u32(data, 0)?,
+            upper: read_u32(data, 4)?,
+        })
+    }
+}
+
+/// BIOS disk the kernel was loaded from
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct BiosBootDevice {
+    /// BIOS drive number, like 0x80 for the first hard disk
+    pub drive: u32,
+    pub partition: Option<u32>,
+    pub sub_partition: Option<u32>,
+}
+
+impl BiosBootDevice {
+    pub fn from_bytes(data: &[u8]) -> Option<BiosBootDevice> {
+        let partition = |offset| {
+            read_u32(data, offset)
+                .map(|partition| Some(partition).filter(|&p| p != BIOS_NO_PARTITION))
+        };
+
+        Some(BiosBootDevice {
+            drive: read_u32(data, 0)?,
+            partition: partition(4)?,
+            sub_partition: partition(8)?,
+        })
+    }
+}
+
+/// Section headers of the kernel ELF image, only 64-bit headers are accepted
+#[derive(Copy, Clone, Debug)]
+pub struct ElfSymbols<'t> {
+    /// Index of the section holding the section names
+    pub string_table_index: u32,
+    headers: &'t [u8],
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ElfSection {
+    /// Offset of the name in the section name table
+    pub name: u32,
+    pub kind: u32,
+    pub flags: u64,
+    pub address: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    pub info: u32,
+    pub alignment: u64,
+    pub entry_size: u64,
+}
+
+impl<'t> ElfSymbols<'t> {
+    pub fn from_bytes(data: &'t [u8]) -> Option<ElfSymbols<'t>> {
+        // GRUB uses 32-bit fields, not the 16-bit ones of the specification
+        let count = usize::try_from(read_u32(data, 0)?).unwrap();
+        let entry_size = usize::try_from(read_u32(data, 4)?).unwrap();
+        let string_table_index = read_u32(data, 8)?;
+
+        if entry_size != ELF64_SECTION_HEADER_SIZE {
+            return None;
+        }
+
+        let headers = data.get(12..12 + count.checked_mul(entry_size)?)?;
+        Some(ElfSymbols {
+            string_table_index,
+            headers,
+        })
+    }
+
+    pub fn sections(&self) -> impl Iterator<Item = ElfSection> + 't {
+        self.headers
+            .chunks_exact(ELF64_SECTION_HEADER_SIZE)
+            .filter_map(|header| {
+                Some(ElfSection {
+                    name: read_u32(header, 0)?,
+                    kind: read_u32(header, 4)?,
+                    flags: read_u64(header, 8)?,
+                    address: read_u64(header, 16)?,
+                    offset: read_u64(header, 24)?,
+                    size: read_u64(header, 32)?,
+                    link: read_u32(header, 40)?,
+                    info: read_u32(header, 44)?,
+                    alignment: read_u64(header, 48)?,
+                    entry_size: read_u64(header, 56)?,
+                })
+            })
+    }
+
+    /// Symbol table of the kernel & the string table holding the names of its symbols
+    pub fn symbol_table(&self) -> Option<(ElfSection, ElfSection)> {
+        let symbols = self.sections().find(|section| section.kind == SHT_SYMTAB)?;
+        let names = self.sections().nth(usize::try_from(symbols.link).ok()?)?;
+        Some((symbols, names))
+    }
+}
+
+impl ElfSection {
+    /// Physical memory holding the section, the bootloader also loads the sections that aren't
+    /// part of the image
+    pub fn range(&self) -> Range<usize> {
+        let start = usize::try_from(self.address).unwrap();
+        let size = usize::try_from(self.size).unwrap();
+        start..start + size
+    }
+}
+
+/// VBE controller & mode information, as returned by the VBE functions 00h & 01h
+#[derive(Copy, Clone, Debug)]
+pub struct VbeInfo<'t> {
+    pub mode: u16,
+    pub interface_segment: u16,
+    pub interface_offset: u16,
+    pub interface_length: u16,
+    pub control_info: &'t [u8; 512],
+    pub mode_info: &'t [u8; 256],
+}
+
+impl<'t> VbeInfo<'t> {
+    pub fn from_bytes(data: &'t [u8]) -> Option<VbeInfo<'t>> {
+        Some(VbeInfo {
+            mode: read_u16(data, 0)?,
+            interface_segment: read_u16(data, 2)?,
+            interface_offset: read_u16(data, 4)?,
+            interface_length: read_u16(data, 6)?,
+            control_info: <&[u8; 512]>::try_from(data.get(8..520)?).ok()?,
+            mode_info: <&[u8; 256]>::try_from(data.get(520..776)?).ok()?,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct FramebufferInfo<'t> {
+    pub address: usize,
+    /// Bytes per line
+    pub pitch: u32,
+    /// Width in pixels, or in characters for EGA text
+    pub width: u32,
+    pub height: u32,
+    pub bits_per_pixel: u8,
+    pub kind: FramebufferKind<'t>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum FramebufferKind<'t> {
+    Indexed {
+        palette: &'t [PaletteColor],
+    },
+    Rgb {
+        red: ColorField,
+        green: ColorField,
+        blue: ColorField,
+    },
+    EgaText,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PaletteColor {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+/// Position of a color component in a pixel
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ColorField {
+    /// Offset of the lowest bit
+    pub position: u8,
+    pub size: u8,
+}
+
+impl<'t> FramebufferInfo<'t> {
+    pub fn from_bytes(data: &'t [u8]) -> Option<FramebufferInfo<'t>> {
+        let color_field = |offset| {
+            Some(ColorField {
+                position: read_u8(data, offset)?,
+                size: read_u8(data, offset + 1)?,
+            })
+        };
+
+        let kind = match read_u8(data, 21)? {
+            0 => {
+                // GRUB stores the number of colors on 16 bits
+                let count = usize::from(read_u16(data, 24)?);
+                let palette = data.get(26..26 + count * core::mem::size_of::<PaletteColor>())?;
+                FramebufferKind::Indexed {
+                    palette: unsafe {
+                        core::slice::from_raw_parts(palette.as_ptr() as *const PaletteColor, count)
+                    },
+                }
+            }
+            1 => FramebufferKind::Rgb {
+                red: color_field(24)?,
+                green: color_field(26)?,
+                blue: color_field(28)?,
+            },
+            2 => FramebufferKind::EgaText,
+            _ => return None,
+        };
+
+        Some(FramebufferInfo {
+            address: read_address(data, 0)?,
+            pitch: read_u32(data, 8)?,
+            width: read_u32(data, 12)?,
+            height: read_u32(data, 16)?,
+            bits_per_pixel: read_u8(data, 20)?,
+            kind,
+        })
+    }
+}
+
+/// APM BIOS interface, as returned by the APM connect functions
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ApmTable {
+    pub version: u16,
+    pub code_segment: u16,
+    pub offset: u32,
+    pub code_segment_16: u16,
+    pub data_segment: u16,
+    pub flags: u16,
+    pub code_segment_length: u16,
+    pub code_segment_16_length: u16,
+    pub data_segment_length: u16,
+}
+
+impl ApmTable {
+    pub fn from_bytes(data: &[u8]) -> Option<ApmTable> {
+        Some(ApmTable {
+            version: read_u16(data, 0)?,
+            code_segment: read_u16(data, 2)?,
+            offset: read_u32(data, 4)?,
+            code_segment_16: read_u16(data, 8)?,
+            data_segment: read_u16(data, 10)?,
+            flags: read_u16(data, 12)?,
+            code_segment_length: read_u16(data, 14)?,
+            code_segment_16_length: read_u16(data, 16)?,
+            data_segment_length: read_u16(data, 18)?,
+        })
+    }
+}
+
+/// Copy of the SMBIOS entry point & tables
+#[derive(Copy, Clone, Debug)]
+pub struct SmbiosTables<'t> {
+    pub major: u8,
+    pub minor: u8,
+    /// Starts with the entry point structure
+    pub tables: &'t [u8],
+}
+
+impl<'t> SmbiosTables<'t> {
+    pub fn from_bytes(data: &'t [u8]) -> Option<SmbiosTables<'t>> {
+        let major = read_u8(data, 0)?;
+        let minor = read_u8(data, 1)?;
+        let tables = data.get(8..)?;
+
+        let anchor = if major >= 3 {
+            SMBIOS3_ANCHOR
+        } else {
+            SMBIOS2_ANCHOR
+        };
+        if !tables.starts_with(anchor) {
+            return None;
+        }
+
+        Some(SmbiosTables {
+            major,
+            minor,
+            tables,
+        })
+    }
+}
+
+/// Network configuration the bootloader got
+#[derive(Copy, Clone, Debug)]
+pub struct NetInfo<'t> {
+    /// DHCP ACK packet, as received
+    pub dhcp_ack: &'t [u8],
+}
+
+impl<'t> NetInfo<'t> {
+    pub fn from_bytes(data: &'t [u8]) -> Option<NetInfo<'t>> {
+        Some(NetInfo { dhcp_ack: data })
+    }
+}
+
+/// UEFI memory map, only given when the boot services weren't terminated
+#[derive(Copy, Clone, Debug)]
+pub struct EfiMemoryMap<'t> {
+    descriptor_size: usize,
+    descriptors: &'t [u8],
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EfiMemoryDescriptor {
+    /// `EFI_MEMORY_TYPE`, like 7 for conventional memory
+    pub kind: u32,
+    pub physical_start: u64,
+    pub virtual_start: u64,
+    /// Number of 4 KiB pages
+    pub page_count: u64,
+    pub attributes: u64,
+}
+
+impl<'t> EfiMemoryMap<'t> {
+    pub fn from_bytes(data: &'t [u8]) -> Option<EfiMemoryMap<'t>> {
+        let descriptor_size = usize::try_from(read_u32(data, 0)?).unwrap();
+        let version = read_u32(data, 4)?;
+        let descriptors = data.get(8..)?;
+
+        // descriptors can grow in later versions of UEFI, new fields are at the end
+        if version != EFI_MEMORY_DESCRIPTOR_VERSION
+            || descriptor_size < EFI_MEMORY_DESCRIPTOR_SIZE
+            || descriptors.len() % descriptor_size != 0
+        {
+            return None;
+        }
+
+        Some(EfiMemoryMap {
+            descriptor_size,
+            descriptors,
+        })
+    }
+
+    pub fn descriptors(&self) -> impl Iterator<Item = EfiMemoryDescriptor> + 't {
+        self.descriptors
+            .chunks_exact(self.descriptor_size)
+            .filter_map(|descriptor| {
+                Some(EfiMemoryDescriptor {
+                    kind: read_u32(descriptor, 0)?,
+                    physical_start: read_u64(descriptor, 8)?,
+                    virtual_start: read_u64(descriptor, 16)?,
+                    page_count: read_u64(descriptor, 24)?,
+                    attributes: read_u64(descriptor, 32)?,
+                })
+            })
+    }
+}
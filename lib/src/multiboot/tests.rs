@@ -0,0 +1,511 @@
+//! The boot information is built like the one GRUB gives when QEMU boots the ISO with `-m 4G`:
+//! the memory map is QEMU's e820 one, the boot device the CD-ROM & the framebuffer the one of the
+//! standard VGA at 1024x768.
+
+use super::*;
+
+const TAG_CMDLINE: u32 = 1;
+const TAG_BOOTLOADER_NAME: u32 = 2;
+const TAG_MODULE: u32 = 3;
+const TAG_BASIC_MEMINFO: u32 = 4;
+const TAG_BIOS_BOOT_DEVICE: u32 = 5;
+const TAG_MEMMAP: u32 = 6;
+const TAG_FRAMEBUFFER: u32 = 8;
+const TAG_ELF_SECTIONS: u32 = 9;
+const TAG_APM: u32 = 10;
+
+const SHT_SYMTAB: u32 = 2;
+
+/// Base, length & type of the entries of QEMU's memory map
+const QEMU_MEMMAP: [(u64, u64, u32); 8] = [
+    (0x0, 0x9_fc00, 1),
+    (0x9_fc00, 0x400, 2),
+    (0xf_0000, 0x1_0000, 2),
+    (0x10_0000, 0xbfee_0000, 1),
+    (0xbffe_0000, 0x2_0000, 2),
+    (0xfeff_c000, 0x4000, 2),
+    (0xfffc_0000, 0x4_0000, 2),
+    (0x1_0000_0000, 0x4000_0000, 1),
+];
+
+fn memmap(entry_size: u32, entry_version: u32, entries: &[(u64, u64, u32)]) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&entry_size.to_le_bytes());
+    data.extend_from_slice(&entry_version.to_le_bytes());
+    for &(base, length, kind) in entries {
+        let start = data.len();
+        data.extend_from_slice(&base.to_le_bytes());
+        data.extend_from_slice(&length.to_le_bytes());
+        data.extend_from_slice(&kind.to_le_bytes());
+        data.resize(start + entry_size as usize, 0);
+    }
+    data
+}
+
+fn module(start: u32, end: u32, cmdline: &str) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&start.to_le_bytes());
+    data.extend_from_slice(&end.to_le_bytes());
+    data.extend_from_slice(cmdline.as_bytes());
+    data.push(0);
+    data
+}
+
+fn section_header(name: u32, kind: u32, address: u64, size: u64, link: u32) -> Vec<u8> {
+    let mut header = Vec::new();
+    header.extend_from_slice(&name.to_le_bytes());
+    header.extend_from_slice(&kind.to_le_bytes());
+    header.extend_from_slice(&0u64.to_le_bytes());
+    header.extend_from_slice(&address.to_le_bytes());
+    header.extend_from_slice(&0u64.to_le_bytes());
+    header.extend_from_slice(&size.to_le_bytes());
+    header.extend_from_slice(&link.to_le_bytes());
+    header.extend_from_slice(&0u32.to_le_bytes());
+    header.extend_from_slice(&8u64.to_le_bytes());
+    header.extend_from_slice(&0u64.to_le_bytes());
+    header
+}
+
+/// Null, `.text`, `.symtab` & `.strtab` section headers, GRUB stores the count on 32 bits
+fn elf_sections(entry_size: u32) -> Vec<u8> {
+    let headers = [
+        section_header(0, 0, 0, 0, 0),
+        section_header(1, 1, 0x20_0000, 0x8_0000, 0),
+        section_header(7, SHT_SYMTAB, 0x60_0000, 0x1_2000, 3),
+        section_header(15, 3, 0x61_2000, 0x9000, 0),
+    ];
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&(headers.len() as u32).to_le_bytes());
+    data.extend_from_slice(&entry_size.to_le_bytes());
+    data.extend_from_slice(&3u32.to_le_bytes());
+    for header in headers.iter() {
+        data.extend_from_slice(&header[..entry_size as usize]);
+    }
+    data
+}
+
+fn rgb_framebuffer() -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&0xfd00_0000u64.to_le_bytes());
+    data.extend_from_slice(&4096u32.to_le_bytes());
+    data.extend_from_slice(&1024u32.to_le_bytes());
+    data.extend_from_slice(&768u32.to_le_bytes());
+    data.extend_from_slice(&[32, 1, 0, 0]);
+    data.extend_from_slice(&[16, 8, 8, 8, 0, 8]);
+    data
+}
+
+fn apm_table() -> Vec<u8> {
+    [0x0102u16, 0xf000, 0, 0]
+        .iter()
+        .flat_map(|field| field.to_le_bytes())
+        .chain(0xf000u16.to_le_bytes())
+        .chain(0x0040u16.to_le_bytes())
+        .chain(0x0003u16.to_le_bytes())
+        .chain([0xff, 0xff, 0xff, 0xff, 0x00, 0x04])
+        .collect()
+}
+
+/// Boot information of GRUB, tags are aligned on 8 bytes & end with a tag of type 0
+fn qemu_grub_mbi() -> Vec<u8> {
+    let mut basic_meminfo = 639u32.to_le_bytes().to_vec();
+    basic_meminfo.extend_from_slice(&3_144_576u32.to_le_bytes());
+    let bios_boot_device = [0xe0u32, 0xffff_ffff, 0xffff_ffff]
+        .iter()
+        .flat_map(|field| field.to_le_bytes())
+        .collect::<Vec<_>>();
+
+    let tags = [
+        (TAG_CMDLINE, b"nokaslr shell=ttyS0\0".to_vec()),
+        (TAG_BOOTLOADER_NAME, b"GRUB 2.06\0".to_vec()),
+        (TAG_MODULE, module(0x10_4000, 0x10_6e00, "initrd")),
+        (TAG_MODULE, module(0x10_7000, 0x10_7400, "acpi")),
+        (TAG_BASIC_MEMINFO, basic_meminfo),
+        (TAG_BIOS_BOOT_DEVICE, bios_boot_device),
+        (TAG_MEMMAP, memmap(24, 0, &QEMU_MEMMAP)),
+        (TAG_FRAMEBUFFER, rgb_framebuffer()),
+        (TAG_ELF_SECTIONS, elf_sections(64)),
+        (TAG_APM, apm_table()),
+        (0, Vec::new()),
+    ];
+
+    let mut mbi = vec![0; 8];
+    for (kind, data) in tags.iter() {
+        mbi.extend_from_slice(&kind.to_le_bytes());
+        mbi.extend_from_slice(&(8 + data.len() as u32).to_le_bytes());
+        mbi.extend_from_slice(data);
+        mbi.resize((mbi.len() + 7) & !7, 0);
+    }
+
+    let total_size = mbi.len() as u32;
+    mbi[..4].copy_from_slice(&total_size.to_le_bytes());
+    mbi
+}
+
+/// Data of the tags of a type, the way the kernel walks them
+fn tags(mbi: &[u8], kind: u32) -> Vec<&[u8]> {
+    let mut found = Vec::new();
+    let mut offset = 8;
+    loop {
+        let tag_kind = read_u32(mbi, offset).unwrap();
+        let size = read_u32(mbi, offset + 4).unwrap() as usize;
+        if tag_kind == 0 {
+            return found;
+        }
+        if tag_kind == kind {
+            found.push(&mbi[offset + 8..offset + size]);
+        }
+        offset = (offset + size + 7) & !7;
+    }
+}
+
+fn tag(mbi: &[u8], kind: u32) -> &[u8] {
+    tags(mbi, kind)[0]
+}
+
+#[test]
+fn memmap_has_every_entry() {
+    let mbi = qemu_grub_mbi();
+    let memmap = MemoryMap::from_bytes(tag(&mbi, TAG_MEMMAP)).unwrap();
+    let entries = memmap.entries().collect::<Vec<_>>();
+
+    // the count used to be the size of the entries divided by itself, which is 1
+    assert_eq!(entries.len(), QEMU_MEMMAP.len());
+    for (entry, &(base, length, kind)) in entries.iter().zip(QEMU_MEMMAP.iter()) {
+        assert_eq!(entry.base_addr as u64, base);
+        assert_eq!(entry.length, length);
+        assert_eq!(entry.mem_type, MemoryType::from(kind));
+    }
+
+    let available = entries
+        .iter()
+        .filter(|entry| entry.mem_type == MemoryType::AvailableRAM)
+        .map(|entry| entry.length)
+        .sum::<u64>();
+    assert_eq!(available, 0x9_fc00 + 0xbfee_0000 + 0x4000_0000);
+}
+
+#[test]
+fn memmap_unknown_types_are_reserved() {
+    let data = memmap(
+        24,
+        0,
+        &[(0, 0x1000, 2), (0x1000, 0x1000, 12), (0x2000, 0x1000, 3)],
+    );
+    let types = MemoryMap::from_bytes(&data)
+        .unwrap()
+        .entries()
+        .map(|entry| entry.mem_type)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            MemoryType::Reserved,
+            MemoryType::Reserved,
+            MemoryType::ACPIInformation
+        ]
+    );
+}
+
+#[test]
+fn memmap_larger_entries_keep_known_fields() {
+    let data = memmap(32, 0, &QEMU_MEMMAP);
+    let memmap = MemoryMap::from_bytes(&data).unwrap();
+    assert_eq!(memmap.entries().count(), QEMU_MEMMAP.len());
+    assert_eq!(memmap.entries().last().unwrap().base_addr, 0x1_0000_0000);
+}
+
+#[test]
+fn memmap_rejects_wrong_version() {
+    assert!(MemoryMap::from_bytes(&memmap(24, 1, &QEMU_MEMMAP)).is_none());
+}
+
+#[test]
+fn memmap_rejects_wrong_entry_size() {
+    assert!(MemoryMap::from_bytes(&memmap(20, 0, &QEMU_MEMMAP)).is_none());
+    assert!(MemoryMap::from_bytes(&memmap(0, 0, &[])).is_none());
+}
+
+#[test]
+fn memmap_rejects_truncated() {
+    let data = memmap(24, 0, &QEMU_MEMMAP);
+    assert!(MemoryMap::from_bytes(&data[..data.len() - 4]).is_none());
+    assert!(MemoryMap::from_bytes(&data[..6]).is_none());
+}
+
+#[test]
+fn strings() {
+    let mbi = qemu_grub_mbi();
+    assert_eq!(c_str(tag(&mbi, TAG_CMDLINE)), Some("nokaslr shell=ttyS0"));
+    assert_eq!(c_str(tag(&mbi, TAG_BOOTLOADER_NAME)), Some("GRUB 2.06"));
+    assert_eq!(c_str(b"no terminator"), Some("no terminator"));
+    assert_eq!(c_str(b"\xff\xfe\0"), None);
+}
+
+#[test]
+fn modules() {
+    let mbi = qemu_grub_mbi();
+    let modules = tags(&mbi, TAG_MODULE)
+        .into_iter()
+        .map(|data| Module::from_bytes(data).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(modules.len(), 2);
+    assert_eq!((modules[0].start, modules[0].end), (0x10_4000, 0x10_6e00));
+    assert_eq!(modules[0].cmdline, "initrd");
+    assert_eq!(modules[0].len(), 0x2e00);
+    assert_eq!(modules[1].cmdline, "acpi");
+}
+
+#[test]
+fn module_rejects_truncated() {
+    let data = module(0x10_4000, 0x10_6e00, "initrd");
+    assert!(Module::from_bytes(&data[..6]).is_none());
+    assert!(Module::from_bytes(&data[..8]).unwrap().cmdline.is_empty());
+}
+
+#[test]
+fn module_end_before_start_is_empty() {
+    let data = module(0x2000, 0x1000, "");
+    assert!(Module::from_bytes(&data).unwrap().is_empty());
+}
+
+#[test]
+fn basic_meminfo() {
+    let mbi = qemu_grub_mbi();
+    let data = tag(&mbi, TAG_BASIC_MEMINFO);
+    assert_eq!(
+        BasicMemInfo::from_bytes(data),
+        Some(BasicMemInfo {
+            lower: 639,
+            upper: 3_144_576,
+        })
+    );
+    assert!(BasicMemInfo::from_bytes(&data[..7]).is_none());
+}
+
+#[test]
+fn bios_boot_device() {
+    let mbi = qemu_grub_mbi();
+    let data = tag(&mbi, TAG_BIOS_BOOT_DEVICE);
+    assert_eq!(
+        BiosBootDevice::from_bytes(data),
+        Some(BiosBootDevice {
+            drive: 0xe0,
+            partition: None,
+            sub_partition: None,
+        })
+    );
+    assert!(BiosBootDevice::from_bytes(&data[..8]).is_none());
+}
+
+#[test]
+fn elf_symbols() {
+    let mbi = qemu_grub_mbi();
+    let symbols = ElfSymbols::from_bytes(tag(&mbi, TAG_ELF_SECTIONS)).unwrap();
+    assert_eq!(symbols.string_table_index, 3);
+    assert_eq!(symbols.sections().count(), 4);
+
+    let (table, names) = symbols.symbol_table().unwrap();
+    assert_eq!(table.kind, SHT_SYMTAB);
+    assert_eq!(table.range(), 0x60_0000..0x61_2000);
+    assert_eq!(names.name, 15);
+    assert_eq!(names.range(), 0x61_2000..0x61_b000);
+}
+
+#[test]
+fn elf_symbols_rejects_wrong_entry_size() {
+    // 32-bit section headers
+    assert!(ElfSymbols::from_bytes(&elf_sections(40)).is_none());
+}
+
+#[test]
+fn elf_symbols_rejects_truncated() {
+    let data = elf_sections(64);
+    assert!(ElfSymbols::from_bytes(&data[..data.len() - 1]).is_none());
+    assert!(ElfSymbols::from_bytes(&data[..10]).is_none());
+}
+
+#[test]
+fn rgb_framebuffer_info() {
+    let mbi = qemu_grub_mbi();
+    let framebuffer = FramebufferInfo::from_bytes(tag(&mbi, TAG_FRAMEBUFFER)).unwrap();
+    assert_eq!(framebuffer.address, 0xfd00_0000);
+    assert_eq!(
+        (framebuffer.pitch, framebuffer.width, framebuffer.height),
+        (4096, 1024, 768)
+    );
+    assert_eq!(framebuffer.bits_per_pixel, 32);
+
+    match framebuffer.kind {
+        FramebufferKind::Rgb { red, green, blue } => {
+            assert_eq!(
+                red,
+                ColorField {
+                    position: 16,
+                    size: 8
+                }
+            );
+            assert_eq!(
+                green,
+                ColorField {
+                    position: 8,
+                    size: 8
+                }
+            );
+            assert_eq!(
+                blue,
+                ColorField {
+                    position: 0,
+                    size: 8
+                }
+            );
+        }
+        kind => panic!("expected an RGB framebuffer, got {:?}", kind),
+    }
+}
+
+#[test]
+fn indexed_framebuffer_info() {
+    let mut data = rgb_framebuffer()[..24].to_vec();
+    data[21] = 0;
+    data.extend_from_slice(&2u16.to_le_bytes());
+    data.extend_from_slice(&[0, 0, 0, 0xff, 0xff, 0xff]);
+
+    match FramebufferInfo::from_bytes(&data).unwrap().kind {
+        FramebufferKind::Indexed { palette } => assert_eq!(
+            palette,
+            [
+                PaletteColor {
+                    red: 0,
+                    green: 0,
+                    blue: 0
+                },
+                PaletteColor {
+                    red: 0xff,
+                    green: 0xff,
+                    blue: 0xff
+                }
+            ]
+        ),
+        kind => panic!("expected an indexed framebuffer, got {:?}", kind),
+    }
+
+    // the palette is shorter than its count
+    assert!(FramebufferInfo::from_bytes(&data[..data.len() - 1]).is_none());
+}
+
+#[test]
+fn framebuffer_info_rejects_unknown_type() {
+    let mut data = rgb_framebuffer();
+    data[21] = 3;
+    assert!(FramebufferInfo::from_bytes(&data).is_none());
+}
+
+#[test]
+fn framebuffer_info_rejects_truncated() {
+    let data = rgb_framebuffer();
+    assert!(FramebufferInfo::from_bytes(&data[..data.len() - 1]).is_none());
+    assert!(FramebufferInfo::from_bytes(&data[..20]).is_none());
+}
+
+#[test]
+fn vbe_info() {
+    let mut data = vec![0; 776];
+    data[..2].copy_from_slice(&0x4118u16.to_le_bytes());
+    data[8..12].copy_from_slice(b"VESA");
+
+    let vbe = VbeInfo::from_bytes(&data).unwrap();
+    assert_eq!(vbe.mode, 0x4118);
+    assert_eq!(&vbe.control_info[..4], b"VESA");
+    assert!(VbeInfo::from_bytes(&data[..775]).is_none());
+}
+
+#[test]
+fn apm() {
+    let mbi = qemu_grub_mbi();
+    let data = tag(&mbi, TAG_APM);
+    let apm = ApmTable::from_bytes(data).unwrap();
+    assert_eq!(apm.version, 0x0102);
+    assert_eq!(apm.code_segment, 0xf000);
+    assert_eq!(apm.data_segment_length, 0x0400);
+    assert!(ApmTable::from_bytes(&data[..19]).is_none());
+}
+
+#[test]
+fn smbios_tables() {
+    let mut data = vec![2, 8, 0, 0, 0, 0, 0, 0];
+    data.extend_from_slice(b"_SM_\x1f\x1f\x02\x08");
+    let smbios = SmbiosTables::from_bytes(&data).unwrap();
+    assert_eq!((smbios.major, smbios.minor), (2, 8));
+    assert!(smbios.tables.starts_with(b"_SM_"));
+
+    data[0] = 3;
+    assert!(SmbiosTables::from_bytes(&data).is_none());
+    assert!(SmbiosTables::from_bytes(&data[..8]).is_none());
+}
+
+fn efi_memmap(descriptor_size: u32, version: u32) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&descriptor_size.to_le_bytes());
+    data.extend_from_slice(&version.to_le_bytes());
+    for &(kind, start, pages) in [(7u32, 0x10_0000u64, 0x700u64), (9, 0x7fb7_e000, 0x10)].iter() {
+        let offset = data.len();
+        data.extend_from_slice(&kind.to_le_bytes());
+        data.extend_from_slice(&0u32.to_le_bytes());
+        data.extend_from_slice(&start.to_le_bytes());
+        data.extend_from_slice(&0u64.to_le_bytes());
+        data.extend_from_slice(&pages.to_le_bytes());
+        data.extend_from_slice(&0xfu64.to_le_bytes());
+        data.resize(offset + descriptor_size as usize, 0);
+    }
+    data
+}
+
+#[test]
+fn efi_memory_map() {
+    // OVMF's descriptors are 48 bytes
+    let data = efi_memmap(48, 1);
+    let descriptors = EfiMemoryMap::from_bytes(&data)
+        .unwrap()
+        .descriptors()
+        .collect::<Vec<_>>();
+
+    assert_eq!(descriptors.len(), 2);
+    assert_eq!(
+        descriptors[1],
+        EfiMemoryDescriptor {
+            kind: 9,
+            physical_start: 0x7fb7_e000,
+            virtual_start: 0,
+            page_count: 0x10,
+            attributes: 0xf,
+        }
+    );
+}
+
+#[test]
+fn efi_memory_map_rejects_wrong_version() {
+    assert!(EfiMemoryMap::from_bytes(&efi_memmap(48, 2)).is_none());
+}
+
+#[test]
+fn efi_memory_map_rejects_wrong_descriptor_size() {
+    assert!(EfiMemoryMap::from_bytes(&efi_memmap(32, 1)).is_none());
+}
+
+#[test]
+fn efi_memory_map_rejects_truncated() {
+    let data = efi_memmap(48, 1);
+    assert!(EfiMemoryMap::from_bytes(&data[..data.len() - 8]).is_none());
+    assert!(EfiMemoryMap::from_bytes(&data[..4]).is_none());
+}
+
+#[test]
+fn pointers() {
+    let data = 0x7fbe_e018u64.to_le_bytes();
+    assert_eq!(pointer(&data, true), Some(0x7fbe_e018));
+    assert_eq!(pointer(&data[..4], false), Some(0x7fbe_e018));
+    assert_eq!(pointer(&data[..4], true), None);
+}
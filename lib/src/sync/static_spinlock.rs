@@ -57,6 +57,15 @@ impl<T> StaticSpinlock<T> {
             }
         }
     }
+
+    /// Release the lock without a guard, for locks whose guard was forgotten because they are
+    /// taken and released from different places (e.g. from C code).
+    ///
+    /// # Safety
+    /// The lock must be held, and the data must not be accessed through the forgotten guard anymore.
+    pub unsafe fn force_unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
 }
 
 unsafe impl<T> Sync for StaticSpinlock<T> {}
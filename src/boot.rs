@@ -2,3 +2,6 @@
 pub mod early_kprintln;
 pub mod bootstrap;
 pub mod multiboot;
+#[macro_use]
+pub mod params;
+pub mod kaslr;
@@ -1,6 +1,8 @@
+use crate::boot;
 use crate::drivers;
 use crate::kernel;
 use crate::kernel_main;
+use crate::kwarn;
 
 use ::alloc::boxed::Box;
 use ::lib::*;
@@ -13,16 +15,32 @@ pub unsafe extern "C" fn kernel_bootstrap() -> ! {
     disable_interrupts!();
 
     kernel::mem::setup_memory();
+    boot::params::init();
+    kernel::unwind::init();
     kernel::idt::setup_idt();
+    if let Err(err) = drivers::acpi::tables::init() {
+        kwarn!("acpi: can't read the ACPI tables, {:?}", err);
+    }
     kernel::apic::setup_apic();
-
-    early_kprintln!(
-        "eh_frame={:?}, eh_frame_hdr={:?}",
-        kernel::get_eh_frame_ptr(),
-        kernel::get_eh_frame_hdr_ptr()
-    );
+    kernel::cpu::init_current();
+    kernel::mem::pcid::init();
+    kernel::mem::numa::init();
+    kernel::mem::frame::init();
+    kernel::idle::init();
+    kernel::deferred::init();
+    kernel::initrd::init();
 
     drivers::acpi::setup_acpi();
+    kernel::time::init();
+    drivers::acpi::enable_acpi();
+    kernel::power::init();
+    drivers::acpi::prt::init();
+    drivers::acpi::namespace::enumerate();
+    drivers::serial::init();
+    drivers::acpi::processor::init();
+    drivers::acpi::thermal::init();
+    kernel::shell::init();
+    kernel::mem::pcid::run_boot_benchmark();
 
     exec_with_new_stack(kernel_main);
 }
@@ -1,24 +1,15 @@
-use core::fmt::{Arguments, Result, Write};
-use lib::*;
+use crate::drivers::serial;
 
-struct IOPort(u32);
+use core::fmt::Arguments;
 
-impl Write for IOPort {
-    fn write_str(&mut self, buf: &str) -> Result {
-        for c in buf.chars() {
-            unsafe {
-                io_write_port!(u8, 0x3F8, c);
-            }
-        }
-
-        Ok(())
-    }
+/// Write synchronously on COM1, see `serial::write_polled`
+pub fn write_to_serial(args: Arguments) {
+    serial::write_polled(args);
 }
 
-pub fn write_to_serial(args: Arguments) {
-    let mut port = IOPort(0x3F8);
-    port.write_fmt(args)
-        .expect("failed to write to serial port");
+/// Wait for a byte to be received on COM1
+pub fn read_from_serial() -> u8 {
+    serial::PORTS[0].read_byte()
 }
 
 #[macro_export]
@@ -0,0 +1,97 @@
+//! Kernel address space layout randomization.
+//!
+//! The boot stub moves the kernel by a random multiple of 2M inside `KERNEL_START..KERNEL_END`,
+//! with the relocation table `tools/relocs` adds to the image after the link. `init` then moves
+//! the start of the heap, of vmalloc & of the page tables mapping by a random amount inside their
+//! regions. Booting with `nokaslr` keeps everything at the addresses of the `symbols` file.
+
+use crate::boot::params::Flag;
+use crate::kernel::config::*;
+use crate::kinfo;
+
+use core::convert::TryFrom;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+kernel_param! {
+    /// Keep the kernel at its link address, the boot stub reads it before the parameters are set &
+    /// only knows the bare `nokaslr`
+    static NOKASLR: Flag = Flag(false), "nokaslr";
+}
+
+extern "C" {
+    static kaslr_offset: u32;
+    static kaslr_seed: u32;
+}
+
+static HEAP_SLIDE: AtomicUsize = AtomicUsize::new(0);
+static VMALLOC_SLIDE: AtomicUsize = AtomicUsize::new(0);
+static PAGE_MAP_SLIDE: AtomicUsize = AtomicUsize::new(0);
+
+/// The heap & vmalloc move by a multiple of it
+const SLIDE_ALIGN: usize = 1 << 30;
+const VMALLOC_MAX_SLIDE: usize = 1 << 40;
+/// Size mapped by an entry of the root page table
+const PAGE_MAP_SIZE: usize = 1 << 39;
+
+/// Whether the boot stub randomized the kernel base
+pub fn is_enabled() -> bool {
+    unsafe { kaslr_seed != 0 }
+}
+
+/// Where the kernel runs, it is linked at `KERNEL_START`
+pub fn kernel_base() -> usize {
+    KERNEL_START + usize::try_from(unsafe { kaslr_offset }).unwrap()
+}
+
+/// Start of the heap, in the physical memory mapping region
+pub fn heap_base() -> usize {
+    PHYSICAL_MEMORY_MAPPING_BASE + HEAP_SLIDE.load(Ordering::Relaxed)
+}
+
+pub fn vmalloc_base() -> usize {
+    VMALLOC_BASE + VMALLOC_SLIDE.load(Ordering::Relaxed)
+}
+
+/// Where the page tables of the current address space are mapped
+pub fn page_map_base() -> usize {
+    PAGE_MAP_BASE + PAGE_MAP_SLIDE.load(Ordering::Relaxed)
+}
+
+/// Randomize the memory regions, before the paging is set up
+pub fn init() {
+    if !is_enabled() {
+        kinfo!("kaslr: disabled");
+        return;
+    }
+
+    let mut state = u64::from(unsafe { kaslr_seed });
+    let mut slide = |max: usize, align: usize| {
+        let slots = u64::try_from(max / align + 1).unwrap();
+        usize::try_from(splitmix64(&mut state) % slots).unwrap() * align
+    };
+
+    let heap_region = PHYSICAL_MEMORY_MAPPING_END - PHYSICAL_MEMORY_MAPPING_BASE;
+    HEAP_SLIDE.store(slide(heap_region / 2, SLIDE_ALIGN), Ordering::Relaxed);
+    VMALLOC_SLIDE.store(slide(VMALLOC_MAX_SLIDE, SLIDE_ALIGN), Ordering::Relaxed);
+    // the page tables mapping uses a whole entry of the root table
+    PAGE_MAP_SLIDE.store(
+        slide(PAGE_MAP_END - PAGE_MAP_BASE - PAGE_MAP_SIZE, PAGE_MAP_SIZE),
+        Ordering::Relaxed,
+    );
+
+    kinfo!(
+        "kaslr: kernel at 0x{:x}, heap at 0x{:x}, vmalloc at 0x{:x}, page tables at 0x{:x}",
+        kernel_base(),
+        heap_base(),
+        vmalloc_base(),
+        page_map_base()
+    );
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -1,7 +1,15 @@
-pub mod memmap;
+//! Multiboot2 boot information, copied to the heap early during the boot. The tags are parsed by
+//! `lib::multiboot`, `Tag` only checks their type.
 
+mod commands;
+
+use crate::drivers::acpi::tables::Rsdp;
 use crate::kernel::mem::addr::*;
+use crate::kinfo;
+use lib::multiboot::{c_str, pointer};
+pub use lib::multiboot::{memmap, tags};
 use memmap::*;
+pub use tags::*;
 
 use core::convert::TryFrom;
 use core::fmt;
@@ -44,6 +52,17 @@ impl BootInfo {
         self.tags().filter(|tag| tag.tag_type() == tag_type).next()
     }
 
+    /// Modules loaded by the bootloader, like the ones given with `module2` in GRUB
+    pub fn modules(&self) -> impl Iterator<Item = Module> {
+        self.tags().filter_map(|tag| tag.as_module())
+    }
+
+    /// Whether the EFI boot services are still usable, the bootloader didn't exit them
+    pub fn efi_boot_services_running(&self) -> bool {
+        self.get_tag(TagType::EFIBootServicesNotTerminated)
+            .is_some()
+    }
+
     fn get_ptr(&self) -> *const u8 {
         self.header.as_ptr() as *const u8
     }
@@ -141,12 +160,137 @@ impl<'t> Tag<'t> {
         unsafe { core::slice::from_raw_parts(self.data.as_ptr(), data_size) }
     }
 
-    pub fn as_memmap(&self) -> Option<&'t MemoryMap> {
+    pub fn as_memmap(&self) -> Option<MemoryMap<'t>> {
         match self.tag_type() {
             TagType::MemMap => MemoryMap::from_bytes(self.data()),
             _ => None,
         }
     }
+
+    pub fn as_module(&self) -> Option<Module<'t>> {
+        match self.tag_type() {
+            TagType::Modules => Module::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    /// Command line of the kernel
+    pub fn as_cmdline(&self) -> Option<&'t str> {
+        match self.tag_type() {
+            TagType::CmdLine => c_str(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_bootloader_name(&self) -> Option<&'t str> {
+        match self.tag_type() {
+            TagType::BootLoaderName => c_str(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_basic_mem_info(&self) -> Option<BasicMemInfo> {
+        match self.tag_type() {
+            TagType::BasicMemInfo => BasicMemInfo::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_bios_boot_device(&self) -> Option<BiosBootDevice> {
+        match self.tag_type() {
+            TagType::BIOSBootDevice => BiosBootDevice::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_elf_symbols(&self) -> Option<ElfSymbols<'t>> {
+        match self.tag_type() {
+            TagType::ELFSymbols => ElfSymbols::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_vbe_info(&self) -> Option<VbeInfo<'t>> {
+        match self.tag_type() {
+            TagType::VbeInfo => VbeInfo::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_framebuffer_info(&self) -> Option<FramebufferInfo<'t>> {
+        match self.tag_type() {
+            TagType::FramebufferInfo => FramebufferInfo::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_apm_table(&self) -> Option<ApmTable> {
+        match self.tag_type() {
+            TagType::ApmTable => ApmTable::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_smbios_tables(&self) -> Option<SmbiosTables<'t>> {
+        match self.tag_type() {
+            TagType::SmbiosTables => SmbiosTables::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    /// Copy of the RSDP, the old tag must hold a revision 0 RSDP & the new one a later revision
+    pub fn as_rsdp(&self) -> Option<Rsdp> {
+        let rsdp = match self.tag_type() {
+            TagType::ACPIOldRsdp | TagType::ACPINewRsdp => Rsdp::parse(self.data()).ok()?,
+            _ => return None,
+        };
+
+        match (self.tag_type(), rsdp.revision) {
+            (TagType::ACPIOldRsdp, 0) => Some(rsdp),
+            (TagType::ACPINewRsdp, revision) if revision >= 2 => Some(rsdp),
+            _ => None,
+        }
+    }
+
+    pub fn as_net_info(&self) -> Option<NetInfo<'t>> {
+        match self.tag_type() {
+            TagType::NetInfo => NetInfo::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    pub fn as_efi_memmap(&self) -> Option<EfiMemoryMap<'t>> {
+        match self.tag_type() {
+            TagType::EFIMemMap => EfiMemoryMap::from_bytes(self.data()),
+            _ => None,
+        }
+    }
+
+    /// Physical address of the EFI system table
+    pub fn as_efi_system_table(&self) -> Option<PhyAddr> {
+        match self.tag_type() {
+            TagType::EFI32TablePointer => pointer(self.data(), false).map(PhyAddr::new),
+            TagType::EFI64TablePointer => pointer(self.data(), true).map(PhyAddr::new),
+            _ => None,
+        }
+    }
+
+    /// EFI handle of the kernel image, given when the boot services weren't terminated
+    pub fn as_efi_image_handle(&self) -> Option<PhyAddr> {
+        match self.tag_type() {
+            TagType::EFI32ImagePointer => pointer(self.data(), false).map(PhyAddr::new),
+            TagType::EFI64ImagePointer => pointer(self.data(), true).map(PhyAddr::new),
+            _ => None,
+        }
+    }
+
+    /// Physical address the kernel image was loaded at, only given for relocatable images
+    pub fn as_image_base(&self) -> Option<PhyAddr> {
+        match self.tag_type() {
+            TagType::ImageBasePhyAddr => pointer(self.data(), false).map(PhyAddr::new),
+            _ => None,
+        }
+    }
 }
 
 impl<'t> fmt::Debug for Tag<'t> {
@@ -222,5 +366,6 @@ pub fn init() {
         }
     }
 
-    early_kprintln!("boot info initialized");
+    kinfo!("multiboot: boot info initialized");
+    commands::register();
 }
@@ -0,0 +1,66 @@
+//! Shell command to look at the boot information given by the bootloader
+
+use super::{get_boot_info, TagType};
+use crate::kernel::shell::{self, Command, CommandError};
+
+use core::fmt::Write;
+
+pub(super) fn register() {
+    shell::register(&ListTags);
+}
+
+struct ListTags;
+
+impl Command for ListTags {
+    fn name(&self) -> &'static str {
+        "tags"
+    }
+
+    fn description(&self) -> &'static str {
+        "list the multiboot2 tags given by the bootloader"
+    }
+
+    fn run(&self, _args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        for tag in get_boot_info().tags() {
+            writeln!(out, "{:?}, {} bytes", tag.tag_type(), tag.size())?;
+
+            match tag.tag_type() {
+                TagType::CmdLine => {
+                    if let Some(cmdline) = tag.as_cmdline() {
+                        writeln!(out, "    {}", cmdline)?;
+                    }
+                }
+                TagType::BootLoaderName => {
+                    if let Some(name) = tag.as_bootloader_name() {
+                        writeln!(out, "    {}", name)?;
+                    }
+                }
+                TagType::Modules => {
+                    if let Some(module) = tag.as_module() {
+                        writeln!(
+                            out,
+                            "    {:#x}-{:#x} {}",
+                            module.start, module.end, module.cmdline
+                        )?;
+                    }
+                }
+                TagType::MemMap => {
+                    if let Some(memmap) = tag.as_memmap() {
+                        for memory in memmap.entries() {
+                            let base = memory.base_addr;
+                            writeln!(
+                                out,
+                                "    {:#014x}-{:#014x} {:?}",
+                                base,
+                                base as u64 + memory.length,
+                                memory.mem_type
+                            )?;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+//! Kernel parameters, set from the command line given by the bootloader.
+//!
+//! Parameters are declared anywhere in the kernel with `kernel_param!`, which puts them in the
+//! `.kernel_params` section, & are set by `init` early during the boot. With
+//! `multiboot2 /boot/lambix panic_reboot=10 mem=0x40000000` in `grub.cfg`, the command line is a
+//! list of `key=value` or `key` tokens separated by spaces, values can be quoted to hold spaces.
+
+use crate::boot::multiboot;
+use crate::{kinfo, kwarn};
+
+use ::alloc::boxed::Box;
+use ::alloc::string::String;
+use core::convert::TryFrom;
+use lib::sync::StaticSpinlock;
+use lib::without_interrupts;
+
+/// Declare a parameter set from the kernel command line, read with `get()`
+///
+/// ```ignore
+/// kernel_param! {
+///     /// Seconds to wait before rebooting after a panic, 0 means never reboot
+///     static PANIC_REBOOT_DELAY: u64 = 0, "panic_reboot";
+/// }
+/// ```
+#[macro_export]
+macro_rules! kernel_param {
+    ($(#[$attr:meta])* $vis:vis static $ident:ident: $ty:ty = $default:expr, $name:literal;) => {
+        $(#[$attr])*
+        $vis static $ident: $crate::boot::params::Param<$ty> =
+            $crate::boot::params::Param::new($default);
+
+        const _: () = {
+            fn set(
+                value: ::core::option::Option<&'static str>,
+            ) -> ::core::result::Result<(), $crate::boot::params::ParamError> {
+                $ident.set_from(value)
+            }
+
+            #[used]
+            #[link_section = ".kernel_params"]
+            static PARAM: $crate::boot::params::KernelParam = $crate::boot::params::KernelParam {
+                name: $name,
+                set,
+            };
+        };
+    };
+}
+
+extern "C" {
+    static __kernel_params_start: u8;
+    static __kernel_params_end: u8;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParamError {
+    /// The parameter needs a value, only flags can be given without one
+    MissingValue,
+    /// The parameter is a `Flag`, it can't be given a value
+    UnexpectedValue,
+    InvalidValue,
+}
+
+/// Entry of the `.kernel_params` section
+#[repr(C)]
+pub struct KernelParam {
+    pub name: &'static str,
+    pub set: fn(Option<&'static str>) -> Result<(), ParamError>,
+}
+
+/// Value of a parameter, the default one until the command line is parsed
+pub struct Param<T> {
+    /// Must only be locked with interrupts disabled, the panic handler reads some
+    value: StaticSpinlock<T>,
+}
+
+impl<T: ParamValue> Param<T> {
+    pub const fn new(default: T) -> Param<T> {
+        Param {
+            value: StaticSpinlock::new(default),
+        }
+    }
+
+    pub fn get(&self) -> T {
+        without_interrupts(|| *self.value.lock())
+    }
+
+    pub fn set(&self, value: T) {
+        without_interrupts(|| *self.value.lock() = value);
+    }
+
+    pub fn set_from(&self, value: Option<&'static str>) -> Result<(), ParamError> {
+        self.set(T::parse(value)?);
+        Ok(())
+    }
+}
+
+/// Type a parameter can have, enums implement it with `parse_choice`
+pub trait ParamValue: Copy + Send + Sized {
+    /// Parse the value given on the command line, `None` when only the key was given
+    fn parse(value: Option<&'static str>) -> Result<Self, ParamError>;
+}
+
+impl ParamValue for bool {
+    fn parse(value: Option<&'static str>) -> Result<bool, ParamError> {
+        match value {
+            None | Some("1") | Some("y") | Some("yes") | Some("on") | Some("true") => Ok(true),
+            Some("0") | Some("n") | Some("no") | Some("off") | Some("false") => Ok(false),
+            Some(_) => Err(ParamError::InvalidValue),
+        }
+    }
+}
+
+/// Parameter that is set by giving its key alone, for the ones the boot stub also looks for & that
+/// it couldn't parse a value of
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Flag(pub bool);
+
+impl ParamValue for Flag {
+    fn parse(value: Option<&'static str>) -> Result<Flag, ParamError> {
+        match value {
+            None => Ok(Flag(true)),
+            Some(_) => Err(ParamError::UnexpectedValue),
+        }
+    }
+}
+
+impl ParamValue for &'static str {
+    fn parse(value: Option<&'static str>) -> Result<&'static str, ParamError> {
+        value.ok_or(ParamError::MissingValue)
+    }
+}
+
+macro_rules! impl_integer_param {
+    ($($ty:ty),*) => {
+        $(
+            impl ParamValue for $ty {
+                fn parse(value: Option<&'static str>) -> Result<$ty, ParamError> {
+                    let value = parse_integer(value.ok_or(ParamError::MissingValue)?)?;
+                    <$ty>::try_from(value).map_err(|_| ParamError::InvalidValue)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer_param!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// Parse an enum value, `choices` gives the value of each accepted name
+pub fn parse_choice<T: Copy>(
+    value: Option<&'static str>,
+    choices: &[(&str, T)],
+) -> Result<T, ParamError> {
+    let value = value.ok_or(ParamError::MissingValue)?;
+    choices
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|&(_, choice)| choice)
+        .ok_or(ParamError::InvalidValue)
+}
+
+/// Every parameter declared with `kernel_param!`
+pub fn params() -> &'static [KernelParam] {
+    unsafe {
+        let start = &__kernel_params_start as *const u8;
+        let end = &__kernel_params_end as *const u8;
+        let count = (end as usize - start as usize) / core::mem::size_of::<KernelParam>();
+        core::slice::from_raw_parts(start as *const KernelParam, count)
+    }
+}
+
+/// Set the parameters given on the command line, the heap has to be set up
+pub fn init() {
+    let boot_info = multiboot::get_boot_info();
+    let cmdline = match boot_info.tags().find_map(|tag| tag.as_cmdline()) {
+        Some(cmdline) => cmdline,
+        None => return,
+    };
+
+    // string parameters point into it
+    let cmdline: &'static str = Box::leak(String::from(cmdline).into_boxed_str());
+    kinfo!("cmdline: {}", cmdline);
+
+    for token in tokens(cmdline) {
+        let (key, value) = match token.split_once('=') {
+            Some((key, value)) => (key, Some(unquote(value))),
+            None => (token, None),
+        };
+
+        match params().iter().find(|param| param.name == key) {
+            Some(param) => {
+                if let Err(error) = (param.set)(value) {
+                    kwarn!("params: can't set {} to {:?}, {:?}", key, value, error);
+                }
+            }
+            None => kwarn!("params: unknown parameter {:?}, ignored", key),
+        }
+    }
+}
+
+/// Split the command line on spaces that aren't quoted, a token that is entirely quoted is
+/// unquoted
+fn tokens(cmdline: &'static str) -> impl Iterator<Item = &'static str> {
+    let mut rest = cmdline;
+    core::iter::from_fn(move || {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            return None;
+        }
+
+        let mut quoted = false;
+        let end = rest
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                c.is_whitespace() && !quoted
+            })
+            .map_or(rest.len(), |(end, _)| end);
+
+        let (token, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(unquote(token))
+    })
+}
+
+fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+/// Decimal or `0x` hexadecimal integer, with an optional `K`, `M` or `G` binary suffix
+fn parse_integer(value: &str) -> Result<i128, ParamError> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+
+    let (value, multiplier) = match value.char_indices().last() {
+        Some((index, 'k')) | Some((index, 'K')) => (&value[..index], 1 << 10),
+        Some((index, 'm')) | Some((index, 'M')) => (&value[..index], 1 << 20),
+        Some((index, 'g')) | Some((index, 'G')) => (&value[..index], 1 << 30),
+        _ => (value, 1),
+    };
+
+    let number = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => i128::from_str_radix(hex, 16),
+        None => value.parse::<i128>(),
+    }
+    .map_err(|_| ParamError::InvalidValue)?;
+
+    let number = number
+        .checked_mul(multiplier)
+        .ok_or(ParamError::InvalidValue)?;
+    Ok(if negative { -number } else { number })
+}
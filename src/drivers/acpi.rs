@@ -1,10 +1,80 @@
+mod commands;
 mod lacpica;
+pub mod namespace;
+pub mod notify;
+pub mod overrides;
+pub mod processor;
+pub mod prt;
 pub mod tables;
+pub mod thermal;
 
 use acpica::*;
 
+/// Give ACPICA access to the ACPI tables, before anything else is set up
 pub fn setup_acpi() {
+    overrides::init();
+
     unsafe {
         AcpiInitializeTables(core::ptr::null_mut(), 0, 0);
     };
+
+    overrides::install_extra_tables();
+}
+
+/// Start the ACPICA interpreter: load the DSDT & SSDTs, enable ACPI mode, install the SCI &
+/// enable the runtime GPEs.
+/// Needs interrupts routing & timers to be set up.
+pub fn enable_acpi() {
+    let steps: [(&str, unsafe extern "C" fn() -> ACPI_STATUS); 2] = [
+        ("AcpiInitializeSubsystem", AcpiInitializeSubsystem),
+        ("AcpiLoadTables", AcpiLoadTables),
+    ];
+
+    for (name, step) in steps.iter() {
+        let status = unsafe { step() };
+        if status != 0 {
+            kerr!("acpi: {} failed with status {:#x}", name, status);
+            return;
+        }
+    }
+
+    let status = unsafe { AcpiEnableSubsystem(ACPI_FULL_INITIALIZATION) };
+    if status != 0 {
+        kerr!("acpi: AcpiEnableSubsystem failed with status {:#x}", status);
+        return;
+    }
+
+    let status = unsafe { AcpiInitializeObjects(ACPI_FULL_INITIALIZATION) };
+    if status != 0 {
+        kerr!(
+            "acpi: AcpiInitializeObjects failed with status {:#x}",
+            status
+        );
+        return;
+    }
+
+    // enable the GPEs that have a _Lxx or _Exx method, the other ones are left to the drivers
+    let status = unsafe { AcpiUpdateAllGpes() };
+    if status != 0 {
+        kerr!("acpi: AcpiUpdateAllGpes failed with status {:#x}", status);
+    }
+
+    if acpica::DEBUGGER {
+        let status = unsafe { acpica::initialize_debugger() };
+        if status != 0 {
+            kerr!(
+                "acpi: can't initialize the AML debugger, status {:#x}",
+                status
+            );
+        }
+    }
+
+    commands::register();
+    kinfo!("acpi: subsystem enabled");
+}
+
+/// Run the AML debugger on ttyS0 until its `quit` command, false when the kernel was built without
+/// it
+pub fn run_debugger() -> bool {
+    unsafe { acpica::run_debugger() }
 }
@@ -0,0 +1,29 @@
+//! Shell command to run the AML debugger
+
+use crate::kernel::shell::{self, Command, CommandError};
+
+use core::fmt::Write;
+
+pub(super) fn register() {
+    shell::register(&AcpiDebugger);
+}
+
+struct AcpiDebugger;
+
+impl Command for AcpiDebugger {
+    fn name(&self) -> &'static str {
+        "acpidb"
+    }
+
+    fn description(&self) -> &'static str {
+        "run the ACPICA AML debugger on ttyS0, until quit"
+    }
+
+    fn run(&self, _args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        if !super::run_debugger() {
+            writeln!(out, "the kernel was built without the AML debugger")?;
+            return Err(CommandError::Failed);
+        }
+        Ok(())
+    }
+}
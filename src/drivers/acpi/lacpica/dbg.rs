@@ -1,24 +1,100 @@
+//! OS side of the ACPICA AML debugger, it runs single-threaded on the CPU that enters it & reads
+//! its commands from the serial port
+
+use crate::boot::early_kprintln::{read_from_serial, write_to_serial};
+
+use acpica::*;
+use core::ffi::*;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+const AE_OK: ACPI_STATUS = 0;
+const AE_BUFFER_OVERFLOW: ACPI_STATUS = 0xb;
+
+const COMMAND_PROMPT: char = '-';
+const EXECUTE_PROMPT: char = '%';
+
+const BACKSPACE: u8 = 0x08;
+const DELETE: u8 = 0x7f;
+
+/// Terminals send CR, CR LF or LF, an LF right after a CR doesn't end another line. Starts as a
+/// CR for the LF left over by the shell command line starting the debugger.
+static LAST_BYTE: AtomicU8 = AtomicU8::new(b'\r');
+
 #[no_mangle]
-pub extern "C" fn AcpiDbDisplayResultObject() {
-    unimplemented!("ACPI debugger isn't implemented yet");
+extern "C" fn AcpiOsInitializeDebugger() -> ACPI_STATUS {
+    AE_OK
 }
 
 #[no_mangle]
-pub extern "C" fn AcpiDbSingleStep() {
-    unimplemented!("ACPI debugger isn't implemented yet");
-}
+extern "C" fn AcpiOsTerminateDebugger() {}
 
+/// Prompt for the next command & read it into the debugger line buffer
 #[no_mangle]
-pub extern "C" fn AcpiDbDumpMethodInfo() {
-    unimplemented!("ACPI debugger isn't implemented yet");
+unsafe extern "C" fn AcpiOsWaitCommandReady() -> ACPI_STATUS {
+    let prompt = if debugger_method_executing() {
+        EXECUTE_PROMPT
+    } else {
+        COMMAND_PROMPT
+    };
+    write_to_serial(format_args!("{} ", prompt));
+
+    let line = debugger_line_buffer();
+    AcpiOsGetLine(
+        line.as_mut_ptr() as *mut c_char,
+        line.len() as UINT32,
+        core::ptr::null_mut(),
+    )
 }
 
 #[no_mangle]
-pub extern "C" fn AcpiDbDisplayArgumentObject() {
-    unimplemented!("ACPI debugger isn't implemented yet");
+extern "C" fn AcpiOsNotifyCommandComplete() -> ACPI_STATUS {
+    AE_OK
 }
 
+/// Read a line from the serial port, echoing it back. The line is nul-terminated & the newline
+/// isn't kept.
 #[no_mangle]
-pub extern "C" fn AcpiDbSignalBreakPoint() {
-    unimplemented!("ACPI debugger isn't implemented yet");
+unsafe extern "C" fn AcpiOsGetLine(
+    buffer: *mut c_char,
+    buffer_length: UINT32,
+    bytes_read: *mut UINT32,
+) -> ACPI_STATUS {
+    if buffer_length == 0 {
+        return AE_BUFFER_OVERFLOW;
+    }
+
+    let line = core::slice::from_raw_parts_mut(buffer as *mut u8, buffer_length as usize);
+    let mut length = 0;
+    let status = loop {
+        let byte = read_from_serial();
+        let last = LAST_BYTE.swap(byte, Ordering::Relaxed);
+        match byte {
+            b'\n' if last == b'\r' => (),
+            b'\r' | b'\n' => break AE_OK,
+            BACKSPACE | DELETE => {
+                if length > 0 {
+                    length -= 1;
+                    write_to_serial(format_args!("\x08 \x08"));
+                }
+            }
+            byte if byte.is_ascii_graphic() || byte == b' ' => {
+                // keep room for the nul terminator
+                if length + 1 == line.len() {
+                    break AE_BUFFER_OVERFLOW;
+                }
+                line[length] = byte;
+                length += 1;
+                write_to_serial(format_args!("{}", byte as char));
+            }
+            _ => (),
+        }
+    };
+
+    write_to_serial(format_args!("\n"));
+    line[length] = 0;
+    if !bytes_read.is_null() {
+        *bytes_read = length as UINT32;
+    }
+
+    status
 }
@@ -1,71 +1,204 @@
 use core::convert::TryFrom;
 use core::ffi::VaList;
-use core::mem::size_of;
+use core::sync::atomic::*;
+use core::time::Duration;
 
 use core::fmt;
 
-use ::alloc::vec;
+use ::alloc::alloc::{alloc_zeroed, dealloc, Layout};
+use ::alloc::boxed::Box;
 use ::alloc::vec::Vec;
 
 use acpica::*;
 use core::ffi::*;
-use lib::{io_read_port, io_write_port};
-
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+use crate::boot::multiboot::{self, memmap::MemoryType};
+use crate::drivers::acpi::overrides;
+use crate::drivers::acpi::tables::SdtHeader;
+use crate::drivers::pci;
+use crate::kernel::apic::{self, ioapic};
+use crate::kernel::deferred::{queue_work, workqueue};
+use crate::kernel::idt::{self, ACPI_SCI_VECTOR};
+use crate::kernel::mem::paging::PAGE_SIZE;
 use crate::kernel::mem::*;
+use crate::kernel::table::idt::InterruptStackFrame;
+use crate::kernel::{cpu, time};
 use printf_compat::{format, output};
 
+const AE_OK: ACPI_STATUS = 0x0000;
+const AE_ERROR: ACPI_STATUS = 0x0001;
+const AE_NOT_EXIST: ACPI_STATUS = 0x0006;
+const AE_ALREADY_EXISTS: ACPI_STATUS = 0x0007;
+const AE_SUPPORT: ACPI_STATUS = 0x000f;
+const AE_TIME: ACPI_STATUS = 0x0011;
+const AE_BAD_PARAMETER: ACPI_STATUS = 0x1001;
+
+/// Alignment of every allocation, the size of the allocation is stored in front of it
+const ALLOCATION_HEADER: usize = 16;
+
+/// SCI handler installed by ACPICA & its context, stored as addresses so the ISR doesn't need a lock
+static SCI_HANDLER: AtomicUsize = AtomicUsize::new(0);
+static SCI_CONTEXT: AtomicUsize = AtomicUsize::new(0);
+
+/// Physical & virtual address of the pages mapped by `register_pointer`, they stay mapped since
+/// ACPICA keeps polling the same few registers. Must only be locked with interrupts disabled, the
+/// SCI handler reads registers.
+static REGISTER_PAGES: StaticSpinlock<Vec<(usize, usize)>> = StaticSpinlock::new(Vec::new());
+
+struct Semaphore {
+    units: AtomicU32,
+    max_units: u32,
+}
+
+/// Object cache, ACPICA is built without its own local cache
+struct Cache {
+    object_layout: Layout,
+    max_depth: usize,
+    objects: StaticSpinlock<Vec<*mut u8>>,
+}
+
+impl Cache {
+    fn purge(&self) {
+        let objects = without_interrupts(|| core::mem::take(&mut *self.objects.lock()));
+        for object in objects {
+            unsafe { dealloc(object, self.object_layout) };
+        }
+    }
+}
+
 #[no_mangle]
-extern "C" fn AcpiOsAcquireLock() -> ACPI_STATUS {
-    0
+extern "C" fn AcpiOsAcquireLock(handle: *mut c_void) -> ACPI_SIZE {
+    let lock = unsafe { &*(handle as *const StaticSpinlock<()>) };
+    let were_enabled = unsafe { interrupts_enabled!() };
+
+    unsafe { disable_interrupts!() };
+    core::mem::forget(lock.lock());
+    ACPI_SIZE::from(were_enabled)
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsAcquireObject() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsAcquireObject(cache: *mut c_void) -> *mut c_void {
+    let cache = unsafe { &*(cache as *const Cache) };
+
+    match without_interrupts(|| cache.objects.lock().pop()) {
+        Some(object) => {
+            unsafe { core::ptr::write_bytes(object, 0, cache.object_layout.size()) };
+            object as _
+        }
+        None => unsafe { alloc_zeroed(cache.object_layout) as _ },
+    }
 }
 
 #[no_mangle]
 extern "C" fn AcpiOsAllocate(size: ACPI_SIZE) -> *mut c_void {
-    let req_size = usize::try_from(size).unwrap();
-
-    let buffer_size = if req_size % size_of::<u128>() != 0 {
-        req_size / size_of::<u128>() + 1
-    } else {
-        req_size / size_of::<u128>()
+    let size = match usize::try_from(size)
+        .ok()
+        .and_then(|size| size.checked_add(ALLOCATION_HEADER))
+    {
+        Some(size) => size,
+        None => return core::ptr::null_mut(),
+    };
+    let layout = match Layout::from_size_align(size, ALLOCATION_HEADER) {
+        Ok(layout) => layout,
+        Err(_) => return core::ptr::null_mut(),
     };
 
-    let buffer = vec![0u128; buffer_size];
-    Vec::leak(buffer).as_ptr() as _
+    unsafe {
+        let base = alloc_zeroed(layout);
+        if base.is_null() {
+            return core::ptr::null_mut();
+        }
+
+        *(base as *mut usize) = size;
+        base.add(ALLOCATION_HEADER) as _
+    }
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsCreateCache() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsCreateCache(
+    _cache_name: *mut c_char,
+    object_size: UINT16,
+    max_depth: UINT16,
+    return_cache: *mut *mut c_void,
+) -> ACPI_STATUS {
+    let object_layout =
+        match Layout::from_size_align(usize::from(object_size).max(1), ALLOCATION_HEADER) {
+            Ok(layout) => layout,
+            Err(_) => return AE_BAD_PARAMETER,
+        };
+
+    if return_cache.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    let cache = Box::new(Cache {
+        object_layout,
+        max_depth: usize::from(max_depth),
+        objects: StaticSpinlock::new(Vec::new()),
+    });
+
+    unsafe { *return_cache = Box::into_raw(cache) as _ };
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsCreateLock() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsCreateLock(out_handle: *mut *mut c_void) -> ACPI_STATUS {
+    if out_handle.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    let lock = Box::new(StaticSpinlock::new(()));
+    unsafe { *out_handle = Box::into_raw(lock) as _ };
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsCreateSemaphore() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsCreateSemaphore(
+    max_units: UINT32,
+    initial_units: UINT32,
+    out_handle: *mut *mut c_void,
+) -> ACPI_STATUS {
+    if out_handle.is_null() || initial_units > max_units {
+        return AE_BAD_PARAMETER;
+    }
+
+    let semaphore = Box::new(Semaphore {
+        units: AtomicU32::new(initial_units),
+        max_units,
+    });
+
+    unsafe { *out_handle = Box::into_raw(semaphore) as _ };
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsDeleteCache() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsDeleteCache(cache: *mut c_void) -> ACPI_STATUS {
+    if cache.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    let cache = unsafe { Box::from_raw(cache as *mut Cache) };
+    cache.purge();
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsDeleteLock() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsDeleteLock(handle: *mut c_void) {
+    if !handle.is_null() {
+        core::mem::drop(unsafe { Box::from_raw(handle as *mut StaticSpinlock<()>) });
+    }
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsDeleteSemaphore() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsDeleteSemaphore(handle: *mut c_void) -> ACPI_STATUS {
+    if handle.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    core::mem::drop(unsafe { Box::from_raw(handle as *mut Semaphore) });
+    AE_OK
 }
 
 #[no_mangle]
@@ -78,13 +211,44 @@ extern "C" fn AcpiOsEnterSleep(
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsExecute() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsExecute(
+    _kind: ACPI_EXECUTE_TYPE,
+    function: ACPI_OSD_EXEC_CALLBACK,
+    context: *mut c_void,
+) -> ACPI_STATUS {
+    struct Callback(unsafe extern "C" fn(*mut c_void), *mut c_void);
+    unsafe impl Send for Callback {}
+
+    impl Callback {
+        fn call(self) {
+            unsafe { (self.0)(self.1) }
+        }
+    }
+
+    match function {
+        Some(function) => {
+            let callback = Callback(function, context);
+            queue_work(move || callback.call());
+            AE_OK
+        }
+        None => AE_BAD_PARAMETER,
+    }
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsFree(_ptr: *mut c_void) -> ACPI_STATUS {
-    0 // TODO: proper allocator is needed..
+extern "C" fn AcpiOsFree(ptr: *mut c_void) {
+    if ptr.is_null() {
+        return;
+    }
+
+    unsafe {
+        let base = (ptr as *mut u8).sub(ALLOCATION_HEADER);
+        let size = *(base as *const usize);
+        dealloc(
+            base,
+            Layout::from_size_align_unchecked(size, ALLOCATION_HEADER),
+        );
+    }
 }
 
 #[no_mangle]
@@ -96,36 +260,70 @@ extern "C" fn AcpiOsGetRootPointer() -> ACPI_PHYSICAL_ADDRESS {
 
 #[no_mangle]
 extern "C" fn AcpiOsGetThreadId() -> u64 {
-    0
+    // there are no threads yet, every CPU is one. ACPICA reserves 0 for "no thread".
+    u64::try_from(cpu::current() + 1).unwrap()
 }
 
+/// Current time in 100 ns units
 #[no_mangle]
-extern "C" fn AcpiOsGetTimer() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsGetTimer() -> UINT64 {
+    u64::try_from(time::monotonic().as_nanos() / 100).unwrap_or(u64::MAX)
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsInitialize() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsInitialize() -> ACPI_STATUS {
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsInstallInterruptHandler() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsInstallInterruptHandler(
+    interrupt_number: UINT32,
+    service_routine: ACPI_OSD_HANDLER,
+    context: *mut c_void,
+) -> ACPI_STATUS {
+    let handler = match service_routine {
+        Some(handler) => handler as usize,
+        None => return AE_BAD_PARAMETER,
+    };
+
+    // ACPICA only ever installs the SCI
+    if SCI_HANDLER
+        .compare_exchange(0, handler, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        return AE_ALREADY_EXISTS;
+    }
+    // the SCI is masked until it's routed below, the handler can't run without its context
+    SCI_CONTEXT.store(context as usize, Ordering::Release);
+
+    unsafe { idt::set_handler(ACPI_SCI_VECTOR, sci_interrupt) };
+
+    // the SCI is a sharable, level-triggered, active low interrupt unless overridden by the MADT
+    let (gsi, flags) = sci_gsi(interrupt_number);
+    if let Err(err) = ioapic::route_irq(gsi, ACPI_SCI_VECTOR, flags) {
+        kerr!("acpi: can't route the SCI, {:?}", err);
+        SCI_HANDLER.store(0, Ordering::Release);
+        return AE_BAD_PARAMETER;
+    }
+
+    AE_OK
 }
 
+/// ACPICA writes through the mappings too: SystemMemory operation regions, the global lock & the
+/// waking vector of the FACS. Only memory is cached, operation regions can be device registers.
 #[no_mangle]
 extern "C" fn AcpiOsMapMemory(
     paddr: acpica::ACPI_PHYSICAL_ADDRESS,
     size: acpica::ACPI_SIZE,
 ) -> *mut u8 {
-    let vbuffer = unsafe {
-        VBuffer::with_flags(
-            PhyAddr::from(usize::try_from(paddr).unwrap()),
-            usize::try_from(size).unwrap(),
-            Flags::NO_EXECUTE,
-        )
-    };
+    let paddr = usize::try_from(paddr).unwrap();
+    let size = usize::try_from(size).unwrap();
+    let mut flags = Flags::READ_WRITE | Flags::NO_EXECUTE;
+    if !is_memory(paddr, size) {
+        flags |= Flags::CACHE_DISABLE | Flags::WRITETHROUGH;
+    }
+
+    let vbuffer = unsafe { VBuffer::with_flags(PhyAddr::from(paddr), size, flags) };
 
     match vbuffer {
         Ok(vbuffer) => VBuffer::leak(vbuffer).0,
@@ -135,14 +333,27 @@ extern "C" fn AcpiOsMapMemory(
 
 #[no_mangle]
 extern "C" fn AcpiOsPhysicalTableOverride(
-    _ExistingTable: *mut ACPI_TABLE_HEADER,
+    ExistingTable: *mut ACPI_TABLE_HEADER,
     NewAddress: *mut ACPI_PHYSICAL_ADDRESS,
     NewTableLength: *mut UINT32,
-) -> () {
+) -> ACPI_STATUS {
+    let header =
+        unsafe { core::slice::from_raw_parts(ExistingTable as *const u8, SdtHeader::SIZE) };
+    let replacement = SdtHeader::parse(header)
+        .ok()
+        .and_then(|header| overrides::find(&header));
+
+    let (address, length) = match replacement {
+        Some((address, length)) => (usize::from(address) as ACPI_PHYSICAL_ADDRESS, length),
+        None => (0, 0),
+    };
+
     unsafe {
-        *NewAddress = 0;
-        *NewTableLength = 0;
+        *NewAddress = address;
+        *NewTableLength = length;
     }
+
+    AE_OK
 }
 
 #[no_mangle]
@@ -176,18 +387,63 @@ unsafe extern "C" fn AcpiOsPrintf(format: *const c_char, args: ...) -> () {
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsPurgeCache() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsPurgeCache(cache: *mut c_void) -> ACPI_STATUS {
+    if cache.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    unsafe { &*(cache as *const Cache) }.purge();
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsReadMemory() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsReadMemory(
+    address: ACPI_PHYSICAL_ADDRESS,
+    value: *mut UINT64,
+    width: UINT32,
+) -> ACPI_STATUS {
+    let register = match register_pointer(address, width) {
+        Some(register) => register,
+        None => return AE_BAD_PARAMETER,
+    };
+
+    unsafe {
+        *value = match width {
+            8 => u64::from(core::ptr::read_volatile(register)),
+            16 => u64::from(core::ptr::read_volatile(register as *const u16)),
+            32 => u64::from(core::ptr::read_volatile(register as *const u32)),
+            _ => core::ptr::read_volatile(register as *const u64),
+        };
+    }
+
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsReadPciConfiguration() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsReadPciConfiguration(
+    pci_id: *mut ACPI_PCI_ID,
+    register: UINT32,
+    value: *mut UINT64,
+    width: UINT32,
+) -> ACPI_STATUS {
+    let (address, offset) = match pci_location(pci_id, register) {
+        Ok(location) => location,
+        Err(status) => return status,
+    };
+
+    let result = match width {
+        8 => u64::from(pci::config_read(address, offset, pci::Width::Byte)),
+        16 => u64::from(pci::config_read(address, offset, pci::Width::Word)),
+        32 => u64::from(pci::config_read(address, offset, pci::Width::Dword)),
+        64 if offset <= 0xf8 => {
+            u64::from(pci::config_read(address, offset, pci::Width::Dword))
+                | u64::from(pci::config_read(address, offset + 4, pci::Width::Dword)) << 32
+        }
+        _ => return AE_BAD_PARAMETER,
+    };
+
+    unsafe { *value = result };
+    AE_OK
 }
 
 #[no_mangle]
@@ -197,70 +453,148 @@ extern "C" fn AcpiOsReadPort(
     Width: UINT32,
 ) -> ACPI_STATUS {
     unsafe {
-        *Value = 0;
         *Value = match Width {
             8 => io_read_port!(u8, Address) as u32,
             16 => io_read_port!(u16, Address) as u32,
             32 => io_read_port!(u32, Address) as u32,
             _ => {
-                return 1;
+                return AE_BAD_PARAMETER;
             }
         };
-
-        early_kprintln!("Read port: 0x{:x} {} => {:?}", Address, Width, *Value);
     }
 
-    0
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsReleaseLock() -> ACPI_STATUS {
-    0
+extern "C" fn AcpiOsReleaseLock(handle: *mut c_void, flags: ACPI_SIZE) {
+    let lock = unsafe { &*(handle as *const StaticSpinlock<()>) };
+    unsafe {
+        lock.force_unlock();
+        if flags != 0 {
+            enable_interrupts!();
+        }
+    }
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsReleaseObject() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsReleaseObject(cache: *mut c_void, object: *mut c_void) -> ACPI_STATUS {
+    if cache.is_null() || object.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    let cache = unsafe { &*(cache as *const Cache) };
+    let object = object as *mut u8;
+    let kept = without_interrupts(|| {
+        let mut objects = cache.objects.lock();
+        if objects.len() < cache.max_depth {
+            objects.push(object);
+            true
+        } else {
+            false
+        }
+    });
+
+    if !kept {
+        unsafe { dealloc(object, cache.object_layout) };
+    }
+
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsRemoveInterruptHandler() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsRemoveInterruptHandler(
+    interrupt_number: UINT32,
+    service_routine: ACPI_OSD_HANDLER,
+) -> ACPI_STATUS {
+    let handler = match service_routine {
+        Some(handler) => handler as usize,
+        None => return AE_BAD_PARAMETER,
+    };
+
+    if SCI_HANDLER
+        .compare_exchange(handler, 0, Ordering::AcqRel, Ordering::Acquire)
+        .is_err()
+    {
+        return AE_NOT_EXIST;
+    }
+
+    if let Err(err) = ioapic::mask_irq(sci_gsi(interrupt_number).0) {
+        kwarn!("acpi: can't mask the SCI, {:?}", err);
+    }
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsSignal() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsSignal(function: UINT32, info: *mut c_void) -> ACPI_STATUS {
+    match function {
+        ACPI_SIGNAL_FATAL => {
+            let info = unsafe { &*(info as *const ACPI_SIGNAL_FATAL_INFO) };
+            kerr!(
+                "acpi: fatal AML error, type={:#x} code={:#x} argument={:#x}",
+                info.Type,
+                info.Code,
+                info.Argument
+            );
+        }
+        ACPI_SIGNAL_BREAKPOINT => {
+            let message = unsafe { CStr::from_ptr(info as *const c_char) };
+            kinfo!("acpi: AML breakpoint, {:?}", message);
+        }
+        _ => return AE_BAD_PARAMETER,
+    }
+
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsSignalSemaphore(_Handle: *const (), _Units: UINT32) -> ACPI_STATUS {
-    1
+extern "C" fn AcpiOsSignalSemaphore(handle: *mut c_void, units: UINT32) -> ACPI_STATUS {
+    if handle.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    let semaphore = unsafe { &*(handle as *const Semaphore) };
+    let result = semaphore
+        .units
+        .fetch_update(Ordering::Release, Ordering::Relaxed, |current| {
+            current
+                .checked_add(units)
+                .filter(|&new| new <= semaphore.max_units)
+        });
+
+    match result {
+        Ok(_) => AE_OK,
+        Err(_) => AE_ERROR,
+    }
 }
 
+/// No scheduler yet, so sleeping is busy-waiting
 #[no_mangle]
-extern "C" fn AcpiOsSleep() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsSleep(milliseconds: UINT64) {
+    busy_wait(Duration::from_millis(milliseconds));
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsStall() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsStall(microseconds: UINT32) {
+    busy_wait(Duration::from_micros(u64::from(microseconds)));
 }
 
 #[no_mangle]
 extern "C" fn AcpiOsTableOverride(
-    ExistingTable: *const ACPI_TABLE_HEADER,
+    _ExistingTable: *const ACPI_TABLE_HEADER,
     NewTable: *mut *const ACPI_TABLE_HEADER,
-) -> () {
+) -> ACPI_STATUS {
+    // replacement tables are given by physical address, see AcpiOsPhysicalTableOverride
     unsafe {
-        *NewTable = ExistingTable;
+        *NewTable = core::ptr::null();
     };
+
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsTerminate() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsTerminate() -> ACPI_STATUS {
+    AE_OK
 }
 
 #[no_mangle]
@@ -275,28 +609,96 @@ unsafe extern "C" fn AcpiOsVprintf(format: *const c_char, args: core::ffi::VaLis
     _vprintf_impl(format, a.as_va_list());
 }
 
+/// Run the work queued with `AcpiOsExecute` until there is none left
 #[no_mangle]
-extern "C" fn AcpiOsWaitEventsComplete() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsWaitEventsComplete() {
+    workqueue::run_pending();
 }
 
 #[no_mangle]
 extern "C" fn AcpiOsWaitSemaphore(
-    _Handle: *mut c_void,
-    _Units: UINT32,
-    _Timeout: UINT16,
+    handle: *mut c_void,
+    units: UINT32,
+    timeout: UINT16,
 ) -> ACPI_STATUS {
-    0
+    if handle.is_null() {
+        return AE_BAD_PARAMETER;
+    }
+
+    let semaphore = unsafe { &*(handle as *const Semaphore) };
+    let deadline = if u32::from(timeout) == ACPI_WAIT_FOREVER {
+        None
+    } else {
+        Some(time::monotonic() + Duration::from_millis(u64::from(timeout)))
+    };
+
+    loop {
+        let acquired = semaphore
+            .units
+            .fetch_update(Ordering::Acquire, Ordering::Relaxed, |current| {
+                current.checked_sub(units)
+            })
+            .is_ok();
+
+        if acquired {
+            return AE_OK;
+        }
+
+        if deadline.map_or(false, |deadline| time::monotonic() >= deadline) {
+            return AE_TIME;
+        }
+
+        core::hint::spin_loop();
+    }
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsWriteMemory() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsWriteMemory(
+    address: ACPI_PHYSICAL_ADDRESS,
+    value: UINT64,
+    width: UINT32,
+) -> ACPI_STATUS {
+    let register = match register_pointer(address, width) {
+        Some(register) => register,
+        None => return AE_BAD_PARAMETER,
+    };
+
+    unsafe {
+        match width {
+            8 => core::ptr::write_volatile(register, value as u8),
+            16 => core::ptr::write_volatile(register as *mut u16, value as u16),
+            32 => core::ptr::write_volatile(register as *mut u32, value as u32),
+            _ => core::ptr::write_volatile(register as *mut u64, value),
+        }
+    }
+
+    AE_OK
 }
 
 #[no_mangle]
-extern "C" fn AcpiOsWritePciConfiguration() -> () {
-    unimplemented!();
+extern "C" fn AcpiOsWritePciConfiguration(
+    pci_id: *mut ACPI_PCI_ID,
+    register: UINT32,
+    value: UINT64,
+    width: UINT32,
+) -> ACPI_STATUS {
+    let (address, offset) = match pci_location(pci_id, register) {
+        Ok(location) => location,
+        Err(status) => return status,
+    };
+
+    match width {
+        8 => pci::config_write(address, offset, pci::Width::Byte, value as u32),
+        16 => pci::config_write(address, offset, pci::Width::Word, value as u32),
+        32 => pci::config_write(address, offset, pci::Width::Dword, value as u32),
+        64 if offset <= 0xf8 => {
+            pci::config_write(address, offset, pci::Width::Dword, value as u32);
+            pci::config_write(address, offset + 4, pci::Width::Dword, (value >> 32) as u32);
+        }
+        _ => return AE_BAD_PARAMETER,
+    }
+
+    AE_OK
 }
 
 #[no_mangle]
@@ -306,16 +708,124 @@ extern "C" fn AcpiOsWritePort(
     Width: UINT32,
 ) -> ACPI_STATUS {
     unsafe {
-        early_kprintln!("Write port: 0x{:x} {} <= {:?}", Address, Width, Value);
         match Width {
             8 => io_write_port!(u8, Address, Value),
             16 => io_write_port!(u16, Address, Value),
-            32 => io_write_port!(u8, Address, Value),
+            32 => io_write_port!(u32, Address, Value),
             _ => {
-                return 1;
+                return AE_BAD_PARAMETER;
             }
         }
     }
 
-    0
+    AE_OK
+}
+
+/// Pointer to the register of `width` bits at `address`, its page is mapped uncached the first
+/// time & stays mapped
+fn register_pointer(address: ACPI_PHYSICAL_ADDRESS, width: UINT32) -> Option<*mut u8> {
+    if !matches!(width, 8 | 16 | 32 | 64) {
+        return None;
+    }
+
+    let address = usize::try_from(address).ok()?;
+    let page = address & !(PAGE_SIZE - 1);
+    let offset = address - page;
+    if offset + usize::try_from(width / 8).unwrap() > PAGE_SIZE {
+        return None;
+    }
+
+    without_interrupts(|| {
+        let mut pages = REGISTER_PAGES.lock();
+        let virtual_page = match pages.iter().find(|&&(physical, _)| physical == page) {
+            Some(&(_, virtual_page)) => virtual_page,
+            None => {
+                let buffer = unsafe {
+                    VBuffer::with_flags(
+                        PhyAddr::from(page),
+                        PAGE_SIZE,
+                        Flags::READ_WRITE
+                            | Flags::NO_EXECUTE
+                            | Flags::CACHE_DISABLE
+                            | Flags::WRITETHROUGH,
+                    )
+                }
+                .ok()?;
+                let virtual_page = VBuffer::leak(buffer).0 as usize;
+                pages.push((page, virtual_page));
+                virtual_page
+            }
+        };
+        Some((virtual_page + offset) as *mut u8)
+    })
+}
+
+/// Whether the memory map has RAM, ACPI tables or ACPI NVS memory from `start` to `start + size`
+fn is_memory(start: usize, size: usize) -> bool {
+    let end = start.saturating_add(size);
+    multiboot::get_boot_info()
+        .tags()
+        .filter_map(|tag| tag.as_memmap())
+        .flat_map(|memmap| memmap.entries())
+        .filter(|entry| {
+            matches!(
+                entry.mem_type,
+                MemoryType::AvailableRAM
+                    | MemoryType::ACPIInformation
+                    | MemoryType::ReservedToPreserve
+            )
+        })
+        .any(|entry| {
+            let length = usize::try_from(entry.length).unwrap_or(usize::MAX);
+            let entry_end = entry.base_addr.saturating_add(length);
+            entry.base_addr <= start && end <= entry_end
+        })
+}
+
+/// Only segment 0 & the legacy configuration space are reachable through the I/O ports
+fn pci_location(
+    pci_id: *const ACPI_PCI_ID,
+    register: UINT32,
+) -> Result<(pci::Address, u8), ACPI_STATUS> {
+    let pci_id = unsafe { pci_id.as_ref() }.ok_or(AE_BAD_PARAMETER)?;
+    if pci_id.Segment != 0 {
+        return Err(AE_SUPPORT);
+    }
+
+    let address = pci::Address::new(
+        u8::try_from(pci_id.Bus).map_err(|_| AE_BAD_PARAMETER)?,
+        u8::try_from(pci_id.Device).map_err(|_| AE_BAD_PARAMETER)?,
+        u8::try_from(pci_id.Function).map_err(|_| AE_BAD_PARAMETER)?,
+    );
+    let offset = u8::try_from(register).map_err(|_| AE_SUPPORT)?;
+    Ok((address, offset))
+}
+
+/// The SCI given by ACPICA is an ISA IRQ when below 16, a GSI otherwise
+fn sci_gsi(interrupt_number: UINT32) -> (u32, ioapic::RedirectionFlags) {
+    let flags = ioapic::RedirectionFlags::LEVEL_TRIGGERED | ioapic::RedirectionFlags::ACTIVE_LOW;
+    match u8::try_from(interrupt_number) {
+        Ok(irq) if irq < 16 => ioapic::isa_irq_to_gsi(irq, flags),
+        _ => (interrupt_number, flags),
+    }
+}
+
+fn busy_wait(duration: Duration) {
+    let deadline = time::monotonic() + duration;
+    while time::monotonic() < deadline {
+        core::hint::spin_loop();
+    }
+}
+
+isr! {
+    fn sci_interrupt(_frame: &InterruptStackFrame) {
+        let handler = SCI_HANDLER.load(Ordering::Acquire);
+        if handler != 0 {
+            let handler: unsafe extern "C" fn(*mut c_void) -> UINT32 =
+                unsafe { core::mem::transmute(handler) };
+            unsafe { handler(SCI_CONTEXT.load(Ordering::Acquire) as *mut c_void) };
+        }
+
+        apic::end_of_interrupt();
+    }
 }
@@ -0,0 +1,327 @@
+//! Walk the ACPI namespace & register the devices it describes
+
+use crate::drivers::device::{self, Device, IrqFlags, Resource};
+use crate::kernel::mem::addr::PhyAddr;
+
+use ::alloc::string::String;
+use ::alloc::vec::Vec;
+use acpica::*;
+
+use core::ffi::c_void;
+use core::fmt::Write;
+
+const AE_OK: ACPI_STATUS = 0;
+const AE_CTRL_DEPTH: ACPI_STATUS = 0x4006;
+
+const MAX_PATH_LENGTH: usize = 256;
+
+// resource descriptor values, ACPICA defines them with casts bindgen can't evaluate
+const ACPI_LEVEL_SENSITIVE: u8 = 0;
+const ACPI_ACTIVE_LOW: u8 = 1;
+const ACPI_READ_WRITE_MEMORY: u8 = 1;
+const ACPI_MEMORY_RANGE: u8 = 0;
+const ACPI_IO_RANGE: u8 = 1;
+
+/// Register every device under `\_SB`, ACPICA has to be enabled
+pub fn enumerate() {
+    let mut system_bus: ACPI_HANDLE = core::ptr::null_mut();
+    let status = unsafe {
+        AcpiGetHandle(
+            core::ptr::null_mut(),
+            b"\\_SB\0".as_ptr() as ACPI_STRING,
+            &mut system_bus,
+        )
+    };
+
+    if status != AE_OK {
+        kwarn!("acpi: no \\_SB in the namespace, status {:#x}", status);
+        return;
+    }
+
+    let status = unsafe {
+        AcpiWalkNamespace(
+            ACPI_TYPE_DEVICE,
+            system_bus,
+            u32::MAX,
+            Some(visit_device),
+            None,
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+        )
+    };
+
+    if status != AE_OK {
+        kerr!("acpi: namespace walk failed with status {:#x}", status);
+    }
+
+    kinfo!("acpi: {} devices found", device::devices().len());
+}
+
+unsafe extern "C" fn visit_device(
+    handle: ACPI_HANDLE,
+    _nesting_level: UINT32,
+    _context: *mut c_void,
+    _return_value: *mut *mut c_void,
+) -> ACPI_STATUS {
+    // devices without _STA are present & functioning
+    let status = evaluate_integer(handle, b"_STA\0").map_or(0xf, |status| status as u32);
+    if status & ACPI_STA_DEVICE_PRESENT == 0 {
+        // children of a functioning device can be present even if their parent isn't
+        return if status & ACPI_STA_DEVICE_FUNCTIONING == 0 {
+            AE_CTRL_DEPTH
+        } else {
+            AE_OK
+        };
+    }
+
+    let mut info: *mut ACPI_DEVICE_INFO = core::ptr::null_mut();
+    if AcpiGetObjectInfo(handle, &mut info) != AE_OK {
+        return AE_OK;
+    }
+
+    let valid = u32::from((*info).Valid);
+    let hid = (valid & ACPI_VALID_HID != 0).then(|| pnp_id(&(*info).HardwareId));
+    let uid = (valid & ACPI_VALID_UID != 0).then(|| pnp_id(&(*info).UniqueId));
+    let cids = if valid & ACPI_VALID_CID != 0 {
+        let list = &(*info).CompatibleIdList;
+        let ids = list.Ids.as_ptr();
+        (0..list.Count as usize)
+            .map(|i| pnp_id(&*ids.add(i)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    AcpiOsFree(info as *mut c_void);
+
+    let mut resources = Vec::new();
+    AcpiWalkResources(
+        handle,
+        b"_CRS\0".as_ptr() as *mut _,
+        Some(visit_resource),
+        &mut resources as *mut Vec<Resource> as *mut c_void,
+    );
+
+    let device = device::register(Device {
+        path: path(handle),
+        hid,
+        cids,
+        uid,
+        status,
+        resources,
+    });
+
+    let mut line = String::new();
+    for resource in device.resources.iter() {
+        let _ = match *resource {
+            Resource::Io { base, length } => write!(line, " io {:#x}+{:#x}", base, length),
+            Resource::Memory { base, length, .. } => write!(line, " mem {:?}+{:#x}", base, length),
+            Resource::Irq { irq, .. } => write!(line, " irq {}", irq),
+        };
+    }
+    kdebug!(
+        "acpi: {} {}{}",
+        device.path,
+        device.hid.as_deref().unwrap_or("-"),
+        line
+    );
+
+    AE_OK
+}
+
+pub(super) unsafe extern "C" fn visit_resource(
+    resource: *mut ACPI_RESOURCE,
+    context: *mut c_void,
+) -> ACPI_STATUS {
+    let resources = &mut *(context as *mut Vec<Resource>);
+    let data = &(*resource).Data;
+
+    match (*resource).Type {
+        ACPI_RESOURCE_TYPE_IRQ => {
+            let irq = data.Irq;
+            let flags = irq_flags(irq.Triggering, irq.Polarity, irq.Shareable);
+            let interrupts = core::ptr::addr_of!(data.Irq.Interrupts) as *const u8;
+            for i in 0..usize::from(irq.InterruptCount) {
+                resources.push(Resource::Irq {
+                    irq: u32::from(*interrupts.add(i)),
+                    flags,
+                });
+            }
+        }
+        ACPI_RESOURCE_TYPE_EXTENDED_IRQ => {
+            let irq = data.ExtendedIrq;
+            let flags = irq_flags(irq.Triggering, irq.Polarity, irq.Shareable);
+            let interrupts = core::ptr::addr_of!(data.ExtendedIrq.Interrupts) as *const u32;
+            for i in 0..usize::from(irq.InterruptCount) {
+                resources.push(Resource::Irq {
+                    irq: interrupts.add(i).read_unaligned(),
+                    flags,
+                });
+            }
+        }
+        ACPI_RESOURCE_TYPE_IO => {
+            let io = data.Io;
+            if io.AddressLength != 0 {
+                resources.push(Resource::Io {
+                    base: io.Minimum,
+                    length: u16::from(io.AddressLength),
+                });
+            }
+        }
+        ACPI_RESOURCE_TYPE_FIXED_IO => {
+            let io = data.FixedIo;
+            if io.AddressLength != 0 {
+                resources.push(Resource::Io {
+                    base: io.Address,
+                    length: u16::from(io.AddressLength),
+                });
+            }
+        }
+        ACPI_RESOURCE_TYPE_MEMORY32 => {
+            let memory = data.Memory32;
+            push_memory(
+                resources,
+                memory.Minimum.into(),
+                memory.AddressLength.into(),
+                memory.WriteProtect,
+            );
+        }
+        ACPI_RESOURCE_TYPE_FIXED_MEMORY32 => {
+            let memory = data.FixedMemory32;
+            push_memory(
+                resources,
+                memory.Address.into(),
+                memory.AddressLength.into(),
+                memory.WriteProtect,
+            );
+        }
+        ACPI_RESOURCE_TYPE_ADDRESS16 => {
+            let address = data.Address16;
+            push_address(
+                resources,
+                address.ResourceType,
+                address.Address.Minimum.into(),
+                address.Address.AddressLength.into(),
+            );
+        }
+        ACPI_RESOURCE_TYPE_ADDRESS32 => {
+            let address = data.Address32;
+            push_address(
+                resources,
+                address.ResourceType,
+                address.Address.Minimum.into(),
+                address.Address.AddressLength.into(),
+            );
+        }
+        ACPI_RESOURCE_TYPE_ADDRESS64 => {
+            let address = data.Address64;
+            push_address(
+                resources,
+                address.ResourceType,
+                address.Address.Minimum,
+                address.Address.AddressLength,
+            );
+        }
+        _ => (),
+    }
+
+    AE_OK
+}
+
+pub(super) fn irq_flags(triggering: u8, polarity: u8, shareable: u8) -> IrqFlags {
+    IrqFlags {
+        level_triggered: triggering == ACPI_LEVEL_SENSITIVE,
+        active_low: polarity == ACPI_ACTIVE_LOW,
+        shareable: shareable != 0,
+    }
+}
+
+fn push_memory(resources: &mut Vec<Resource>, base: u64, length: u64, write_protect: u8) {
+    if length == 0 {
+        return;
+    }
+
+    resources.push(Resource::Memory {
+        base: PhyAddr::new(usize::try_from(base).unwrap()),
+        length: usize::try_from(length).unwrap(),
+        writable: write_protect == ACPI_READ_WRITE_MEMORY,
+    });
+}
+
+/// Address space descriptors, bus numbers ranges are ignored
+fn push_address(resources: &mut Vec<Resource>, resource_type: u8, base: u64, length: u64) {
+    if resource_type == ACPI_MEMORY_RANGE {
+        push_memory(resources, base, length, ACPI_READ_WRITE_MEMORY);
+    } else if resource_type == ACPI_IO_RANGE && length != 0 {
+        if let (Ok(base), Ok(length)) = (u16::try_from(base), u16::try_from(length)) {
+            resources.push(Resource::Io { base, length });
+        }
+    }
+}
+
+/// Evaluate a method or an object returning an integer, `name` must be nul-terminated
+pub(super) unsafe fn evaluate_integer(handle: ACPI_HANDLE, name: &[u8]) -> Option<u64> {
+    let mut object: ACPI_OBJECT = core::mem::zeroed();
+    let mut buffer = ACPI_BUFFER {
+        Length: core::mem::size_of::<ACPI_OBJECT>() as ACPI_SIZE,
+        Pointer: &mut object as *mut ACPI_OBJECT as *mut c_void,
+    };
+
+    let status = AcpiEvaluateObject(
+        handle,
+        name.as_ptr() as ACPI_STRING,
+        core::ptr::null_mut(),
+        &mut buffer,
+    );
+
+    if status == AE_OK && object.Type == ACPI_TYPE_INTEGER {
+        Some(object.Integer.Value)
+    } else {
+        None
+    }
+}
+
+unsafe fn pnp_id(id: &ACPI_PNP_DEVICE_ID) -> String {
+    if id.String.is_null() || id.Length == 0 {
+        return String::new();
+    }
+
+    // the length includes the nul terminator
+    let bytes = core::slice::from_raw_parts(id.String as *const u8, id.Length as usize - 1);
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+pub(super) unsafe fn path(handle: ACPI_HANDLE) -> String {
+    let mut path = [0u8; MAX_PATH_LENGTH];
+    let mut buffer = ACPI_BUFFER {
+        Length: MAX_PATH_LENGTH as ACPI_SIZE,
+        Pointer: path.as_mut_ptr() as *mut c_void,
+    };
+
+    if AcpiGetName(handle, ACPI_FULL_PATHNAME, &mut buffer) != AE_OK {
+        return String::from("?");
+    }
+
+    let length = path.iter().position(|&c| c == 0).unwrap_or(MAX_PATH_LENGTH);
+    String::from_utf8_lossy(&path[..length]).into_owned()
+}
+
+/// Handle of the object at the absolute `path`, like `\_TZ.THRM`
+pub(super) fn handle(path: &str) -> Result<ACPI_HANDLE, ACPI_STATUS> {
+    let mut path = String::from(path);
+    path.push('\0');
+
+    let mut handle: ACPI_HANDLE = core::ptr::null_mut();
+    let status = unsafe {
+        AcpiGetHandle(
+            core::ptr::null_mut(),
+            path.as_ptr() as ACPI_STRING,
+            &mut handle,
+        )
+    };
+
+    if status == AE_OK {
+        Ok(handle)
+    } else {
+        Err(status)
+    }
+}
@@ -0,0 +1,113 @@
+//! Delivery of the `Notify()` of the AML to drivers.
+//!
+//! The SCI handler only queues the notifications, ACPICA dispatches them through `AcpiOsExecute`
+//! so handlers run on the deferred-work thread & are free to evaluate methods or allocate.
+
+use super::namespace;
+
+use ::alloc::boxed::Box;
+use ::alloc::string::String;
+use acpica::*;
+
+use core::ffi::c_void;
+
+const AE_OK: ACPI_STATUS = 0;
+
+// standard notification values, the ones from 0x80 are specific to the type of device
+pub const BUS_CHECK: u32 = 0x00;
+pub const DEVICE_CHECK: u32 = 0x01;
+pub const DEVICE_WAKE: u32 = 0x02;
+pub const EJECT_REQUEST: u32 = 0x03;
+pub const DEVICE_CHECK_LIGHT: u32 = 0x04;
+pub const FREQUENCY_MISMATCH: u32 = 0x05;
+pub const BUS_MODE_MISMATCH: u32 = 0x06;
+pub const POWER_FAULT: u32 = 0x07;
+
+/// Called with the full path of the object & the notification value
+pub type NotifyHandler = fn(path: &str, value: u32);
+
+struct Subscription {
+    path: String,
+    handler: NotifyHandler,
+}
+
+/// Call `handler` for the notifications of every device whose hardware or compatible ID is `id`.
+/// Returns the number of devices subscribed to.
+pub fn subscribe(id: &str, handler: NotifyHandler) -> usize {
+    let mut id = String::from(id);
+    id.push('\0');
+
+    let mut count = 0usize;
+    let mut context = (handler, &mut count);
+    let status = unsafe {
+        AcpiGetDevices(
+            id.as_ptr() as *mut _,
+            Some(subscribe_device),
+            &mut context as *mut (NotifyHandler, &mut usize) as *mut c_void,
+            core::ptr::null_mut(),
+        )
+    };
+
+    if status != AE_OK {
+        kwarn!(
+            "acpi: can't look for {} devices, status {:#x}",
+            id.trim_end_matches('\0'),
+            status
+        );
+    }
+
+    count
+}
+
+/// Call `handler` for the notifications of the object at `path`, like `\_TZ.THRM` for thermal
+/// zones that aren't devices
+pub fn subscribe_path(path: &str, handler: NotifyHandler) -> Result<(), ACPI_STATUS> {
+    let handle = namespace::handle(path)?;
+    unsafe { install(handle, handler) }
+}
+
+unsafe extern "C" fn subscribe_device(
+    handle: ACPI_HANDLE,
+    _nesting_level: UINT32,
+    context: *mut c_void,
+    _return_value: *mut *mut c_void,
+) -> ACPI_STATUS {
+    let (handler, count) = &mut *(context as *mut (NotifyHandler, &mut usize));
+    if install(handle, *handler).is_ok() {
+        **count += 1;
+    }
+
+    AE_OK
+}
+
+/// Subscriptions are never removed, their context is leaked
+unsafe fn install(handle: ACPI_HANDLE, handler: NotifyHandler) -> Result<(), ACPI_STATUS> {
+    let subscription = Box::into_raw(Box::new(Subscription {
+        path: namespace::path(handle),
+        handler,
+    }));
+
+    let status = AcpiInstallNotifyHandler(
+        handle,
+        ACPI_ALL_NOTIFY,
+        Some(dispatch),
+        subscription as *mut c_void,
+    );
+
+    if status != AE_OK {
+        let subscription = Box::from_raw(subscription);
+        kwarn!(
+            "acpi: can't subscribe to notifications of {}, status {:#x}",
+            subscription.path,
+            status
+        );
+        return Err(status);
+    }
+
+    Ok(())
+}
+
+unsafe extern "C" fn dispatch(_handle: ACPI_HANDLE, value: UINT32, context: *mut c_void) {
+    let subscription = &*(context as *const Subscription);
+    (subscription.handler)(&subscription.path, value);
+}
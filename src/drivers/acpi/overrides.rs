@@ -0,0 +1,122 @@
+//! Replacement & extra ACPI tables, loaded as a multiboot module to work around firmware bugs.
+//!
+//! Modules whose command line is `acpi` hold one or more tables, back to back, like
+//! `cat dsdt.aml ssdt.aml > tables.aml` with `module2 /boot/tables.aml acpi` in GRUB. A table
+//! with the signature, OEM id & OEM table id of a firmware table replaces it, the other ones are
+//! installed as extra tables.
+
+use super::tables::{Sdt, SdtHeader};
+use crate::boot::multiboot;
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::vbuffer::VBuffer;
+use crate::kernel::mem::Flags;
+
+use ::alloc::vec::Vec;
+use acpica::*;
+use lib::sync::StaticSpinlock;
+
+const MODULE_CMDLINE: &str = "acpi";
+
+const AE_OK: ACPI_STATUS = 0;
+
+static OVERRIDES: StaticSpinlock<Vec<Table>> = StaticSpinlock::new(Vec::new());
+
+struct Table {
+    header: SdtHeader,
+    address: PhyAddr,
+    /// Whether it replaced a firmware table
+    used: bool,
+}
+
+/// Find the tables given as modules, must be called before ACPICA reads the tables
+pub fn init() {
+    let boot_info = multiboot::get_boot_info();
+    let mut overrides = OVERRIDES.lock();
+
+    for module in boot_info.modules() {
+        if module.cmdline.trim() != MODULE_CMDLINE || module.is_empty() {
+            continue;
+        }
+
+        let start = PhyAddr::new(module.start);
+        let buffer = match unsafe { VBuffer::with_flags(start, module.len(), Flags::NO_EXECUTE) } {
+            Ok(buffer) => buffer,
+            Err(_) => {
+                kwarn!("acpi: can't map the tables module at {:?}", start);
+                continue;
+            }
+        };
+
+        let mut bytes = unsafe { core::slice::from_raw_parts(buffer.as_ptr::<u8>(), module.len()) };
+        let mut offset = 0;
+        while !bytes.is_empty() {
+            let sdt = match Sdt::parse(bytes) {
+                Ok(sdt) => sdt,
+                Err(error) => {
+                    kwarn!(
+                        "acpi: invalid table at offset {:#x} of the tables module: {:?}",
+                        offset,
+                        error
+                    );
+                    break;
+                }
+            };
+
+            overrides.push(Table {
+                header: sdt.header,
+                address: start.wrapping_add(offset),
+                used: false,
+            });
+
+            let length = sdt.bytes().len();
+            bytes = &bytes[length..];
+            offset += length;
+        }
+    }
+
+    if !overrides.is_empty() {
+        kinfo!("acpi: {} tables given as a module", overrides.len());
+    }
+}
+
+/// Physical address & length of the table replacing the one with this header, if any
+pub fn find(existing: &SdtHeader) -> Option<(PhyAddr, u32)> {
+    let mut overrides = OVERRIDES.lock();
+    let table = overrides.iter_mut().find(|table| {
+        table.header.signature == existing.signature
+            && table.header.oem_id == existing.oem_id
+            && table.header.oem_table_id == existing.oem_table_id
+    })?;
+
+    kinfo!(
+        "acpi: overriding {}, revision {:#x} replaced by {:#x}",
+        core::str::from_utf8(&existing.signature).unwrap_or("????"),
+        existing.oem_revision,
+        table.header.oem_revision
+    );
+
+    table.used = true;
+    Some((table.address, table.header.length))
+}
+
+/// Install the tables that didn't replace a firmware table, must be called after ACPICA has
+/// read the firmware tables & before they are loaded
+pub fn install_extra_tables() {
+    let extra_tables = OVERRIDES
+        .lock()
+        .iter()
+        .filter(|table| !table.used)
+        .map(|table| (table.header.signature, table.address))
+        .collect::<Vec<_>>();
+
+    for (signature, address) in extra_tables {
+        let status = unsafe { AcpiInstallTable(usize::from(address) as ACPI_PHYSICAL_ADDRESS, 1) };
+        if status != AE_OK {
+            kwarn!(
+                "acpi: can't install the extra {} table, status {:#x}",
+                core::str::from_utf8(&signature).unwrap_or("????"),
+                status
+            );
+        }
+    }
+}
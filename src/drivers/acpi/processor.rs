@@ -0,0 +1,223 @@
+//! Processor idle states, from the `_CST` of the ACPI processor objects
+
+use super::namespace;
+use crate::kernel::idle::{self, IdleState};
+
+use acpica::*;
+
+use core::ffi::c_void;
+
+const AE_OK: ACPI_STATUS = 0;
+const AE_CTRL_TERMINATE: ACPI_STATUS = 0x4003;
+
+/// Let ACPICA allocate the returned buffer, it has to be freed with `AcpiOsFree`
+const ACPI_ALLOCATE_BUFFER: ACPI_SIZE = ACPI_SIZE::MAX;
+
+const PROCESSOR_DEVICE_HID: &[u8] = b"ACPI0007\0";
+
+// address spaces of the register of a C-state
+const SYSTEM_IO: u8 = 0x01;
+const FIXED_HARDWARE: u8 = 0x7f;
+/// Vendor & class of the fixed hardware registers meaning `mwait`, the address is the hint
+const FIXED_HARDWARE_INTEL: u8 = 0x01;
+const FIXED_HARDWARE_MWAIT: u8 = 0x02;
+
+// _PDC capabilities, what the firmware describes in _CST depends on them
+const PDC_REVISION: u32 = 1;
+const PDC_C1_HALT: u32 = 1 << 1;
+const PDC_C1_FFH: u32 = 1 << 8;
+const PDC_C2C3_FFH: u32 = 1 << 9;
+
+/// C-state as described by one entry of `_CST`
+struct CState {
+    /// 1 for C1 to 3 for C3
+    kind: u64,
+    latency: u64,
+    register: GenericRegister,
+}
+
+struct GenericRegister {
+    space_id: u8,
+    bit_width: u8,
+    bit_offset: u8,
+    address: u64,
+}
+
+/// Use the deepest C-state of the first processor the kernel can enter & `idle::allowed` accepts,
+/// ACPICA has to be enabled
+pub fn init() {
+    let mut chosen: Option<(u64, IdleState)> = None;
+    let context = &mut chosen as *mut Option<(u64, IdleState)> as *mut c_void;
+
+    let root = match namespace::handle("\\") {
+        Ok(root) => root,
+        Err(_) => return,
+    };
+
+    unsafe {
+        AcpiWalkNamespace(
+            ACPI_TYPE_PROCESSOR,
+            root,
+            u32::MAX,
+            Some(visit_processor),
+            None,
+            context,
+            core::ptr::null_mut(),
+        );
+
+        if chosen.is_none() {
+            AcpiGetDevices(
+                PROCESSOR_DEVICE_HID.as_ptr() as *mut _,
+                Some(visit_processor),
+                context,
+                core::ptr::null_mut(),
+            );
+        }
+    }
+
+    match chosen {
+        Some((latency, state)) => {
+            kinfo!("idle: using {:?} from _CST, {} us to exit", state, latency);
+            idle::set_state(state);
+        }
+        None => kinfo!("idle: no usable _CST, keeping {:?}", idle::state()),
+    }
+}
+
+unsafe extern "C" fn visit_processor(
+    handle: ACPI_HANDLE,
+    _nesting_level: UINT32,
+    context: *mut c_void,
+    _return_value: *mut *mut c_void,
+) -> ACPI_STATUS {
+    let chosen = &mut *(context as *mut Option<(u64, IdleState)>);
+    declare_capabilities(handle);
+
+    let mut buffer = ACPI_BUFFER {
+        Length: ACPI_ALLOCATE_BUFFER,
+        Pointer: core::ptr::null_mut(),
+    };
+
+    let status = AcpiEvaluateObject(
+        handle,
+        b"_CST\0".as_ptr() as ACPI_STRING,
+        core::ptr::null_mut(),
+        &mut buffer,
+    );
+    if status != AE_OK {
+        return AE_OK;
+    }
+
+    let package = &*(buffer.Pointer as *const ACPI_OBJECT);
+    if package.Type == ACPI_TYPE_PACKAGE {
+        // the first element is the number of C-states
+        let elements =
+            core::slice::from_raw_parts(package.Package.Elements, package.Package.Count as usize);
+        for state in elements
+            .iter()
+            .skip(1)
+            .filter_map(|element| parse_cstate(element))
+        {
+            let usable = match idle_state(&state) {
+                Some(usable) if idle::allowed(usable) => usable,
+                _ => continue,
+            };
+
+            // states are listed from the shallowest to the deepest
+            *chosen = Some((state.latency, usable));
+        }
+    }
+
+    AcpiOsFree(buffer.Pointer);
+
+    if chosen.is_some() {
+        AE_CTRL_TERMINATE
+    } else {
+        AE_OK
+    }
+}
+
+/// Tell the firmware the kernel handles C1 with `hlt` & the deeper states with `mwait`
+unsafe fn declare_capabilities(handle: ACPI_HANDLE) {
+    let mut capabilities = PDC_C1_HALT;
+    if idle::supports_mwait() {
+        capabilities |= PDC_C1_FFH | PDC_C2C3_FFH;
+    }
+
+    let mut pdc = [PDC_REVISION, 1, capabilities];
+    let mut argument: ACPI_OBJECT = core::mem::zeroed();
+    argument.Buffer.Type = ACPI_TYPE_BUFFER;
+    argument.Buffer.Length = core::mem::size_of_val(&pdc) as u32;
+    argument.Buffer.Pointer = pdc.as_mut_ptr() as *mut u8;
+    let mut arguments = ACPI_OBJECT_LIST {
+        Count: 1,
+        Pointer: &mut argument,
+    };
+
+    // _PDC is optional
+    AcpiEvaluateObject(
+        handle,
+        b"_PDC\0".as_ptr() as ACPI_STRING,
+        &mut arguments,
+        core::ptr::null_mut(),
+    );
+}
+
+/// Read a `Package { Register, Type, Latency, Power }` entry of `_CST`
+unsafe fn parse_cstate(element: &ACPI_OBJECT) -> Option<CState> {
+    if element.Type != ACPI_TYPE_PACKAGE || element.Package.Count < 4 {
+        return None;
+    }
+
+    let fields = core::slice::from_raw_parts(element.Package.Elements, 4);
+    if fields[0].Type != ACPI_TYPE_BUFFER
+        || fields[1].Type != ACPI_TYPE_INTEGER
+        || fields[2].Type != ACPI_TYPE_INTEGER
+    {
+        return None;
+    }
+
+    // a generic register descriptor: tag, length, space id, bit width, bit offset, access size &
+    // the address
+    let descriptor =
+        core::slice::from_raw_parts(fields[0].Buffer.Pointer, fields[0].Buffer.Length as usize);
+    if descriptor.len() < 15 {
+        return None;
+    }
+
+    let mut address = [0u8; 8];
+    address.copy_from_slice(&descriptor[7..15]);
+
+    Some(CState {
+        kind: fields[1].Integer.Value,
+        latency: fields[2].Integer.Value,
+        register: GenericRegister {
+            space_id: descriptor[3],
+            bit_width: descriptor[4],
+            bit_offset: descriptor[5],
+            address: u64::from_le_bytes(address),
+        },
+    })
+}
+
+/// How to enter a C-state, C3 through I/O ports isn't used since it needs the bus masters to be
+/// stopped
+fn idle_state(state: &CState) -> Option<IdleState> {
+    let register = &state.register;
+    match (register.space_id, state.kind) {
+        (FIXED_HARDWARE, _)
+            if register.bit_width == FIXED_HARDWARE_INTEL
+                && register.bit_offset == FIXED_HARDWARE_MWAIT
+                && idle::supports_mwait() =>
+        {
+            Some(IdleState::Mwait {
+                hint: register.address as u32,
+            })
+        }
+        (_, 1) => Some(IdleState::Halt),
+        (SYSTEM_IO, 2) => Some(IdleState::Io {
+            port: u16::try_from(register.address).ok()?,
+        }),
+        _ => None,
+    }
+}
@@ -0,0 +1,374 @@
+//! PCI interrupt routing, from the `_PRT` of the PCI bridges described in the ACPI namespace
+
+use super::namespace::{evaluate_integer, irq_flags, visit_resource};
+use crate::drivers::device::{IrqFlags, Resource};
+use crate::drivers::pci;
+use crate::kernel::apic::ioapic::{self, RedirectionFlags};
+
+use ::alloc::vec::Vec;
+use acpica::*;
+use lib::sync::StaticSpinlock;
+
+use core::ffi::c_void;
+
+const AE_OK: ACPI_STATUS = 0;
+const AE_NOT_FOUND: ACPI_STATUS = 5;
+
+/// Let ACPICA allocate the returned buffer, it has to be freed with `AcpiOsFree`
+const ACPI_ALLOCATE_BUFFER: ACPI_SIZE = ACPI_SIZE::MAX;
+
+const APIC_MODE: u64 = 1;
+
+/// Hard-wired PCI interrupts are shareable, level-triggered & active low
+const PCI_IRQ_FLAGS: IrqFlags = IrqFlags {
+    level_triggered: true,
+    active_low: true,
+    shareable: true,
+};
+
+const PCI_HEADER_TYPE: u8 = 0x0e;
+const PCI_HEADER_TYPE_BRIDGE: u32 = 0x1;
+const PCI_SECONDARY_BUS: u8 = 0x19;
+
+static ROUTING: StaticSpinlock<Routing> = StaticSpinlock::new(Routing::new());
+
+/// Global system interrupt an INTx pin is connected to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PciIrq {
+    pub gsi: u32,
+    pub flags: IrqFlags,
+}
+
+impl PciIrq {
+    /// Flags to use when routing this interrupt with the I/O APIC
+    pub fn redirection_flags(&self) -> RedirectionFlags {
+        let mut flags = RedirectionFlags::empty();
+        flags.set(
+            RedirectionFlags::LEVEL_TRIGGERED,
+            self.flags.level_triggered,
+        );
+        flags.set(RedirectionFlags::ACTIVE_LOW, self.flags.active_low);
+        flags
+    }
+}
+
+struct Route {
+    bus: u8,
+    device: u8,
+    /// 0 for INTA to 3 for INTD
+    pin: u8,
+    irq: PciIrq,
+}
+
+/// PCI to PCI bridge found in the namespace
+struct Bridge {
+    parent_bus: u8,
+    device: u8,
+    secondary_bus: u8,
+}
+
+struct Routing {
+    routes: Vec<Route>,
+    bridges: Vec<Bridge>,
+}
+
+impl Routing {
+    const fn new() -> Routing {
+        Routing {
+            routes: Vec::new(),
+            bridges: Vec::new(),
+        }
+    }
+}
+
+/// Switch the firmware to APIC mode & read the routing tables of every PCI bridge, ACPICA has
+/// to be enabled
+pub fn init() {
+    set_apic_mode();
+
+    // PCI Express root bridges have the PCI bus ID as a compatible ID, they are found too
+    let status = unsafe {
+        AcpiGetDevices(
+            b"PNP0A03\0".as_ptr() as *mut _,
+            Some(visit_root_bridge),
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+        )
+    };
+
+    if status != AE_OK {
+        kwarn!(
+            "acpi: can't find the PCI root bridges, status {:#x}",
+            status
+        );
+    }
+
+    kinfo!("acpi: {} PCI interrupt routes", ROUTING.lock().routes.len());
+}
+
+/// Global system interrupt of the INTx `pin` of a PCI function. `pin` is the value of its
+/// interrupt pin register, 1 for INTA to 4 for INTD.
+///
+/// Devices behind a bridge the firmware doesn't describe are routed using the standard swizzling
+/// of the pins across the bridge.
+pub fn pci_irq_for(bus: u8, device: u8, _function: u8, pin: u8) -> Option<PciIrq> {
+    if !(1..=4).contains(&pin) {
+        return None;
+    }
+
+    let routing = ROUTING.lock();
+    let (mut bus, mut device, mut pin) = (bus, device, pin - 1);
+    loop {
+        let route = routing
+            .routes
+            .iter()
+            .find(|route| route.bus == bus && route.device == device && route.pin == pin);
+        if let Some(route) = route {
+            return Some(route.irq);
+        }
+
+        let bridge = routing
+            .bridges
+            .iter()
+            .find(|bridge| bridge.secondary_bus == bus)?;
+        pin = (pin + device) % 4;
+        device = bridge.device;
+        bus = bridge.parent_bus;
+    }
+}
+
+/// Tell the firmware interrupts are delivered through I/O APICs, `_PIC` is optional
+fn set_apic_mode() {
+    let mut argument: ACPI_OBJECT = unsafe { core::mem::zeroed() };
+    argument.Integer.Type = ACPI_TYPE_INTEGER;
+    argument.Integer.Value = APIC_MODE;
+    let mut arguments = ACPI_OBJECT_LIST {
+        Count: 1,
+        Pointer: &mut argument,
+    };
+
+    let status = unsafe {
+        AcpiEvaluateObject(
+            core::ptr::null_mut(),
+            b"\\_PIC\0".as_ptr() as ACPI_STRING,
+            &mut arguments,
+            core::ptr::null_mut(),
+        )
+    };
+
+    if status != AE_OK && status != AE_NOT_FOUND {
+        kerr!("acpi: can't switch to APIC mode, status {:#x}", status);
+    }
+}
+
+unsafe extern "C" fn visit_root_bridge(
+    handle: ACPI_HANDLE,
+    _nesting_level: UINT32,
+    _context: *mut c_void,
+    _return_value: *mut *mut c_void,
+) -> ACPI_STATUS {
+    let bus = evaluate_integer(handle, b"_BBN\0").unwrap_or(0);
+    if let Ok(bus) = u8::try_from(bus) {
+        read_bridge(handle, bus);
+    }
+
+    AE_OK
+}
+
+/// Read the routing table of the bridge `handle` whose secondary bus is `bus`, then the ones of
+/// the bridges below it
+unsafe fn read_bridge(handle: ACPI_HANDLE, bus: u8) {
+    read_routing_table(handle, bus);
+
+    let mut child: ACPI_HANDLE = core::ptr::null_mut();
+    while AcpiGetNextObject(ACPI_TYPE_DEVICE, handle, child, &mut child) == AE_OK {
+        let address = match evaluate_integer(child, b"_ADR\0") {
+            Some(address) => address,
+            None => continue,
+        };
+
+        let function = pci::Address::new(bus, (address >> 16) as u8, address as u8);
+        let header_type = pci::config_read(function, PCI_HEADER_TYPE, pci::Width::Byte);
+        if header_type == 0xff || header_type & 0x7f != PCI_HEADER_TYPE_BRIDGE {
+            continue;
+        }
+
+        let secondary_bus = pci::config_read(function, PCI_SECONDARY_BUS, pci::Width::Byte) as u8;
+        if secondary_bus <= bus {
+            // not configured by the firmware
+            continue;
+        }
+
+        ROUTING.lock().bridges.push(Bridge {
+            parent_bus: bus,
+            device: function.device,
+            secondary_bus,
+        });
+        read_bridge(child, secondary_bus);
+    }
+}
+
+unsafe fn read_routing_table(handle: ACPI_HANDLE, bus: u8) {
+    let mut buffer = ACPI_BUFFER {
+        Length: ACPI_ALLOCATE_BUFFER,
+        Pointer: core::ptr::null_mut(),
+    };
+
+    if AcpiGetIrqRoutingTable(handle, &mut buffer) != AE_OK {
+        return;
+    }
+
+    let mut entry = buffer.Pointer as *const ACPI_PCI_ROUTING_TABLE;
+    while (*entry).Length != 0 {
+        let source = (*entry).Source.as_ptr();
+        let irq = if *source == 0 {
+            // hard-wired to a global system interrupt
+            Some(PciIrq {
+                gsi: (*entry).SourceIndex,
+                flags: PCI_IRQ_FLAGS,
+            })
+        } else {
+            let mut link: ACPI_HANDLE = core::ptr::null_mut();
+            if AcpiGetHandle(handle, source as ACPI_STRING, &mut link) == AE_OK {
+                link_irq(link)
+            } else {
+                None
+            }
+        };
+
+        if let (Some(irq), Ok(pin)) = (irq, u8::try_from((*entry).Pin)) {
+            ROUTING.lock().routes.push(Route {
+                bus,
+                device: ((*entry).Address >> 16) as u8,
+                pin,
+                irq,
+            });
+        }
+
+        entry = (entry as *const u8).add((*entry).Length as usize) as *const _;
+    }
+
+    AcpiOsFree(buffer.Pointer);
+}
+
+/// Interrupt of a PCI interrupt link device, if the firmware didn't set it one is picked from its
+/// possible resources
+unsafe fn link_irq(link: ACPI_HANDLE) -> Option<PciIrq> {
+    let mut current = Vec::<Resource>::new();
+    AcpiWalkResources(
+        link,
+        b"_CRS\0".as_ptr() as *mut _,
+        Some(visit_resource),
+        &mut current as *mut Vec<Resource> as *mut c_void,
+    );
+
+    let configured = current.iter().find_map(|resource| match *resource {
+        Resource::Irq { irq, flags } if irq != 0 => Some(PciIrq { gsi: irq, flags }),
+        _ => None,
+    });
+    if let Some(irq) = configured {
+        return Some(apply_source_override(irq));
+    }
+
+    let mut possible: Option<ACPI_RESOURCE> = None;
+    AcpiWalkResources(
+        link,
+        b"_PRS\0".as_ptr() as *mut _,
+        Some(first_irq_descriptor),
+        &mut possible as *mut Option<ACPI_RESOURCE> as *mut c_void,
+    );
+    let mut resource = possible?;
+
+    let irq = match resource.Type {
+        ACPI_RESOURCE_TYPE_IRQ => {
+            let descriptor = core::ptr::addr_of_mut!(resource.Data.Irq);
+            if (*descriptor).InterruptCount == 0 {
+                return None;
+            }
+            (*descriptor).InterruptCount = 1;
+            PciIrq {
+                gsi: u32::from((*descriptor).Interrupts[0]),
+                flags: irq_flags(
+                    (*descriptor).Triggering,
+                    (*descriptor).Polarity,
+                    (*descriptor).Shareable,
+                ),
+            }
+        }
+        _ => {
+            let descriptor = core::ptr::addr_of_mut!(resource.Data.ExtendedIrq);
+            if (*descriptor).InterruptCount == 0 {
+                return None;
+            }
+            (*descriptor).InterruptCount = 1;
+            PciIrq {
+                gsi: core::ptr::addr_of!((*descriptor).Interrupts)
+                    .cast::<u32>()
+                    .read_unaligned(),
+                flags: irq_flags(
+                    (*descriptor).Triggering,
+                    (*descriptor).Polarity,
+                    (*descriptor).Shareable,
+                ),
+            }
+        }
+    };
+
+    // the resource list given to _SRS ends with an end tag
+    let mut resources: [ACPI_RESOURCE; 2] = core::mem::zeroed();
+    resources[0] = resource;
+    resources[0].Length = core::mem::size_of::<ACPI_RESOURCE>() as u32;
+    resources[1].Type = ACPI_RESOURCE_TYPE_END_TAG;
+    resources[1].Length = core::mem::size_of::<ACPI_RESOURCE>() as u32;
+
+    let mut buffer = ACPI_BUFFER {
+        Length: core::mem::size_of_val(&resources) as ACPI_SIZE,
+        Pointer: resources.as_mut_ptr() as *mut c_void,
+    };
+
+    let status = AcpiSetCurrentResources(link, &mut buffer);
+    if status != AE_OK {
+        kwarn!(
+            "acpi: can't set the IRQ of a PCI link, status {:#x}",
+            status
+        );
+        return None;
+    }
+
+    Some(apply_source_override(irq))
+}
+
+/// Link devices give ISA IRQs below 16, the interrupt source overrides of the MADT may connect
+/// them to another GSI & change their polarity or trigger mode
+fn apply_source_override(irq: PciIrq) -> PciIrq {
+    let isa_irq = match u8::try_from(irq.gsi) {
+        Ok(isa_irq) if isa_irq < 16 => isa_irq,
+        _ => return irq,
+    };
+
+    let (gsi, flags) = ioapic::isa_irq_to_gsi(isa_irq, irq.redirection_flags());
+    PciIrq {
+        gsi,
+        flags: IrqFlags {
+            level_triggered: flags.contains(RedirectionFlags::LEVEL_TRIGGERED),
+            active_low: flags.contains(RedirectionFlags::ACTIVE_LOW),
+            shareable: irq.flags.shareable,
+        },
+    }
+}
+
+unsafe extern "C" fn first_irq_descriptor(
+    resource: *mut ACPI_RESOURCE,
+    context: *mut c_void,
+) -> ACPI_STATUS {
+    let found = &mut *(context as *mut Option<ACPI_RESOURCE>);
+    let resource_type = (*resource).Type;
+    if found.is_none()
+        && (resource_type == ACPI_RESOURCE_TYPE_IRQ
+            || resource_type == ACPI_RESOURCE_TYPE_EXTENDED_IRQ)
+    {
+        *found = Some(*resource);
+    }
+
+    AE_OK
+}
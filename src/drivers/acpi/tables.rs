@@ -1,29 +1,142 @@
-#[repr(packed)]
-struct RsdpV1Header {
-    pub signature: [u8; 8],
-    pub checksum: u8,
-    pub oemid: [u8; 6],
-    pub revision: u8,
-    pub rsdt_addr: u32,
-}
-
-#[repr(packed)]
-struct RsdpV2Header {
-    pub rsdpv1: RsdpV1Header,
-    pub length: u32,
-    pub xsdt_addr: u64,
-    pub extended_checksum: u8,
-    reserved: [u8; 3],
-}
-
-#[repr(packed)]
-pub struct RsdpV1 {
-    header: RsdpV1Header,
-    tables_addr: [u32],
-}
-
-#[repr(packed)]
-pub struct RsdpV2 {
-    header: RsdpV2Header,
-    tables_addr: [u64],
+//! ACPI tables found through the RSDP given by the bootloader. The parsers only work on byte
+//! slices & are in `lib::acpi` so they can be tested on the host, the tables are mapped from
+//! physical memory by `init` & `find`.
+
+mod commands;
+
+pub use lib::acpi::*;
+
+use crate::boot::multiboot::{self, TagType};
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::vbuffer::VBuffer;
+use crate::kernel::mem::Flags;
+
+use ::alloc::vec::Vec;
+use lib::sync::StaticSpinlock;
+
+static TABLES: StaticSpinlock<Option<Tables>> = StaticSpinlock::new(None);
+
+struct Tables {
+    rsdp: Rsdp,
+    /// Physical address of every table referenced by the RSDT/XSDT
+    addresses: Vec<PhyAddr>,
+    /// Tables mapped so far, they are never unmapped
+    mapped: Vec<&'static [u8]>,
+}
+
+impl Tables {
+    /// Get the `index`-th table, mapping it & the ones before it if needed
+    fn table(&mut self, index: usize) -> Option<&'static [u8]> {
+        while self.mapped.len() <= index {
+            let address = *self.addresses.get(self.mapped.len())?;
+            self.mapped.push(map_table(address).unwrap_or(&[]));
+        }
+
+        Some(self.mapped[index])
+    }
+}
+
+/// Find the RSDP given by the bootloader & read the list of tables from the RSDT/XSDT
+pub fn init() -> Result<()> {
+    let boot_info = multiboot::get_boot_info();
+    let tag = boot_info
+        .get_tag(TagType::ACPINewRsdp)
+        .or_else(|| boot_info.get_tag(TagType::ACPIOldRsdp))
+        .ok_or(TableError::NoRsdp)?;
+
+    let rsdp = Rsdp::parse(tag.data())?;
+    let (root_address, entry_size) = match rsdp.xsdt_address {
+        Some(xsdt) => (PhyAddr::new(usize::try_from(xsdt).unwrap()), 8),
+        None => (PhyAddr::new(usize::try_from(rsdp.rsdt_address).unwrap()), 4),
+    };
+
+    let root = RootTable::parse(map_table(root_address)?, entry_size)?;
+    let addresses = root
+        .entries()
+        .map(|address| PhyAddr::new(usize::try_from(address).unwrap()))
+        .collect::<Vec<_>>();
+
+    kinfo!(
+        "acpi: RSDP revision {}, {} tables",
+        rsdp.revision,
+        addresses.len()
+    );
+
+    *TABLES.lock() = Some(Tables {
+        rsdp,
+        addresses,
+        mapped: Vec::new(),
+    });
+
+    commands::register();
+    Ok(())
+}
+
+pub fn rsdp() -> Option<Rsdp> {
+    TABLES.lock().as_ref().map(|tables| tables.rsdp)
+}
+
+/// Get the `index`-th table with the given signature, its checksum is verified
+pub fn find(signature: &[u8; 4], index: usize) -> Option<Sdt<'static>> {
+    let mut lock = TABLES.lock();
+    let tables = lock.as_mut()?;
+
+    (0..tables.addresses.len())
+        .filter_map(|i| tables.table(i))
+        .filter(|bytes| bytes.get(..4) == Some(&signature[..]))
+        .nth(index)
+        .and_then(|bytes| Sdt::parse(bytes).ok())
+}
+
+/// Headers of every table referenced by the RSDT/XSDT
+pub fn headers() -> Vec<SdtHeader> {
+    let mut lock = TABLES.lock();
+    match lock.as_mut() {
+        Some(tables) => (0..tables.addresses.len())
+            .filter_map(|i| SdtHeader::parse(tables.table(i)?).ok())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+pub fn madt() -> Option<Madt<'static>> {
+    Madt::parse(find(madt::SIGNATURE, 0)?).ok()
+}
+
+pub fn fadt() -> Option<Fadt> {
+    Fadt::parse(find(fadt::SIGNATURE, 0)?).ok()
+}
+
+pub fn hpet() -> Option<Hpet> {
+    Hpet::parse(find(hpet::SIGNATURE, 0)?).ok()
+}
+
+pub fn mcfg() -> Option<Mcfg<'static>> {
+    Mcfg::parse(find(mcfg::SIGNATURE, 0)?).ok()
+}
+
+pub fn srat() -> Option<Srat<'static>> {
+    Srat::parse(find(srat::SIGNATURE, 0)?).ok()
+}
+
+pub fn slit() -> Option<Slit<'static>> {
+    Slit::parse(find(slit::SIGNATURE, 0)?).ok()
+}
+
+/// Map a whole table, the mapping is leaked
+fn map_table(address: PhyAddr) -> Result<&'static [u8]> {
+    let map = |size| unsafe {
+        VBuffer::with_flags(address, size, Flags::NO_EXECUTE).map_err(|_| TableError::MapFailed)
+    };
+
+    let header = map(SdtHeader::SIZE)?;
+    let length = {
+        let bytes = unsafe { core::slice::from_raw_parts(header.as_ptr::<u8>(), SdtHeader::SIZE) };
+        SdtHeader::parse(bytes)?.length
+    };
+    core::mem::drop(header);
+
+    let table = map(usize::try_from(length).unwrap())?;
+    let (ptr, size) = VBuffer::leak(table);
+    Ok(unsafe { core::slice::from_raw_parts(ptr, size) })
 }
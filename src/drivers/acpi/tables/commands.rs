@@ -0,0 +1,91 @@
+//! Shell command to look at the ACPI tables
+
+use crate::kernel::shell::{self, Command, CommandError};
+
+use core::convert::TryFrom;
+use core::fmt::Write;
+
+const DUMP_LINE: usize = 16;
+
+pub(super) fn register() {
+    shell::register(&AcpiTables);
+}
+
+struct AcpiTables;
+
+impl Command for AcpiTables {
+    fn name(&self) -> &'static str {
+        "acpi"
+    }
+
+    fn usage(&self) -> &'static str {
+        "[signature]"
+    }
+
+    fn description(&self) -> &'static str {
+        "list the ACPI tables, or dump the one with the signature"
+    }
+
+    fn run(&self, args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        match args {
+            [] => list(out),
+            [signature] => dump(signature, out),
+            _ => Err(CommandError::Usage),
+        }
+    }
+}
+
+fn list(out: &mut dyn Write) -> Result<(), CommandError> {
+    for header in super::headers() {
+        writeln!(
+            out,
+            "{} {:6} bytes, revision {}, {} {}",
+            ascii(&header.signature),
+            header.length,
+            header.revision,
+            ascii(&header.oem_id).trim_end(),
+            ascii(&header.oem_table_id).trim_end()
+        )?;
+    }
+    Ok(())
+}
+
+fn dump(signature: &str, out: &mut dyn Write) -> Result<(), CommandError> {
+    let signature = <[u8; 4]>::try_from(signature.as_bytes()).map_err(|_| CommandError::Usage)?;
+    let table = match super::find(&signature, 0) {
+        Some(table) => table,
+        None => {
+            writeln!(out, "no valid {} table", ascii(&signature))?;
+            return Err(CommandError::Failed);
+        }
+    };
+
+    for (i, line) in table.bytes().chunks(DUMP_LINE).enumerate() {
+        write!(out, "{:04x}: ", i * DUMP_LINE)?;
+        for byte in line {
+            write!(out, "{:02x} ", byte)?;
+        }
+        for _ in line.len()..DUMP_LINE {
+            out.write_str("   ")?;
+        }
+
+        out.write_str(" |")?;
+        for &byte in line {
+            out.write_char(printable(byte))?;
+        }
+        writeln!(out, "|")?;
+    }
+    Ok(())
+}
+
+/// Signatures & OEM IDs are ASCII, without a nul terminator
+fn ascii(bytes: &[u8]) -> ::alloc::string::String {
+    bytes.iter().map(|&byte| printable(byte)).collect()
+}
+
+fn printable(byte: u8) -> char {
+    match byte {
+        b' '..=b'~' => char::from(byte),
+        _ => '.',
+    }
+}
@@ -0,0 +1,221 @@
+//! ACPI thermal zones: read their temperature & trip points, power off past the critical one.
+//!
+//! Zones are checked when the firmware notifies a temperature change, and by `poll` for the ones
+//! asking to be polled with `_TZP`. The local APIC timer wakes the idle loop up at the shortest
+//! polling period so `poll` runs even when nothing else interrupts the CPU, the idle loop is kept
+//! in C1 for the timer to run.
+
+use super::{namespace, notify};
+use crate::kernel::apic::timer;
+use crate::kernel::{idle, power, time};
+
+use ::alloc::string::String;
+use ::alloc::vec::Vec;
+use acpica::*;
+use lib::sync::StaticSpinlock;
+
+use core::ffi::c_void;
+use core::fmt;
+use core::time::Duration;
+
+const AE_OK: ACPI_STATUS = 0;
+
+const TEMPERATURE_CHANGED: u32 = 0x80;
+const TRIP_POINTS_CHANGED: u32 = 0x81;
+
+static ZONES: StaticSpinlock<Vec<Zone>> = StaticSpinlock::new(Vec::new());
+
+/// Temperature in tenths of a Kelvin, like ACPI gives them
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Temperature(pub u64);
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let celsius = self.0 as i64 - 2732;
+        let sign = if celsius < 0 { "-" } else { "" };
+        write!(f, "{}{}.{}°C", sign, celsius.abs() / 10, celsius.abs() % 10)
+    }
+}
+
+struct Zone {
+    path: String,
+    /// Powering off at or above this temperature
+    critical: Option<Temperature>,
+    /// The firmware expects the CPUs to be slowed down at or above this temperature
+    passive: Option<Temperature>,
+    polling: Option<Duration>,
+    last_check: Duration,
+    last_temperature: Option<Temperature>,
+}
+
+/// Find the thermal zones & check them once, ACPICA has to be enabled
+pub fn init() {
+    let root = match namespace::handle("\\") {
+        Ok(root) => root,
+        Err(_) => return,
+    };
+
+    let status = unsafe {
+        AcpiWalkNamespace(
+            ACPI_TYPE_THERMAL,
+            root,
+            u32::MAX,
+            Some(visit_zone),
+            None,
+            core::ptr::null_mut(),
+            core::ptr::null_mut(),
+        )
+    };
+
+    if status != AE_OK {
+        kerr!("thermal: namespace walk failed with status {:#x}", status);
+    }
+
+    let paths = ZONES
+        .lock()
+        .iter()
+        .map(|zone| zone.path.clone())
+        .collect::<Vec<_>>();
+
+    kinfo!("thermal: {} zones found", paths.len());
+    for path in paths {
+        if notify::subscribe_path(&path, zone_notified).is_err() {
+            kinfo!("thermal: {} will only be polled", path);
+        }
+        check(&path);
+    }
+
+    let period = ZONES.lock().iter().filter_map(|zone| zone.polling).min();
+    if let Some(period) = period {
+        kinfo!("thermal: polling every {} ms", period.as_millis());
+        idle::limit_to_c1();
+    }
+    timer::set_periodic(period);
+}
+
+/// Check the zones whose polling period elapsed, called from the idle loop
+pub fn poll() {
+    let now = time::monotonic();
+    let due = ZONES
+        .lock()
+        .iter()
+        .filter(|zone| matches!(zone.polling, Some(period) if now >= zone.last_check + period))
+        .map(|zone| zone.path.clone())
+        .collect::<Vec<_>>();
+
+    for path in due {
+        check(&path);
+    }
+}
+
+/// Current temperature of every zone, with the path of the zone
+pub fn temperatures() -> Vec<(String, Option<Temperature>)> {
+    ZONES
+        .lock()
+        .iter()
+        .map(|zone| (zone.path.clone(), zone.last_temperature))
+        .collect()
+}
+
+unsafe extern "C" fn visit_zone(
+    handle: ACPI_HANDLE,
+    _nesting_level: UINT32,
+    _context: *mut c_void,
+    _return_value: *mut *mut c_void,
+) -> ACPI_STATUS {
+    let path = namespace::path(handle);
+    let (critical, passive) = trip_points(handle);
+    let polling = namespace::evaluate_integer(handle, b"_TZP\0")
+        .filter(|&period| period != 0)
+        .map(|period| Duration::from_millis(period * 100));
+
+    kinfo!(
+        "thermal: {}, critical at {}, passive at {}",
+        path,
+        critical.map_or(String::from("none"), |t| ::alloc::format!("{}", t)),
+        passive.map_or(String::from("none"), |t| ::alloc::format!("{}", t)),
+    );
+
+    ZONES.lock().push(Zone {
+        path,
+        critical,
+        passive,
+        polling,
+        last_check: Duration::ZERO,
+        last_temperature: None,
+    });
+
+    AE_OK
+}
+
+unsafe fn trip_points(handle: ACPI_HANDLE) -> (Option<Temperature>, Option<Temperature>) {
+    let critical = namespace::evaluate_integer(handle, b"_CRT\0").map(Temperature);
+    let passive = namespace::evaluate_integer(handle, b"_PSV\0").map(Temperature);
+    (critical, passive)
+}
+
+fn zone_notified(path: &str, value: u32) {
+    match value {
+        TEMPERATURE_CHANGED => check(path),
+        TRIP_POINTS_CHANGED => {
+            if let Ok(handle) = namespace::handle(path) {
+                let (critical, passive) = unsafe { trip_points(handle) };
+                if let Some(zone) = ZONES.lock().iter_mut().find(|zone| zone.path == path) {
+                    zone.critical = critical;
+                    zone.passive = passive;
+                }
+            }
+            check(path);
+        }
+        _ => (),
+    }
+}
+
+/// Read the temperature of a zone & react to the trip points it crossed
+fn check(path: &str) {
+    let temperature = match namespace::handle(path) {
+        Ok(handle) => unsafe { namespace::evaluate_integer(handle, b"_TMP\0") }.map(Temperature),
+        Err(_) => None,
+    };
+
+    let mut zones = ZONES.lock();
+    let zone = match zones.iter_mut().find(|zone| zone.path == path) {
+        Some(zone) => zone,
+        None => return,
+    };
+
+    let previous = core::mem::replace(&mut zone.last_temperature, temperature);
+    zone.last_check = time::monotonic();
+
+    let temperature = match temperature {
+        Some(temperature) => temperature,
+        None => return,
+    };
+
+    if matches!(zone.critical, Some(critical) if temperature >= critical) {
+        kerr!(
+            "thermal: {} reached its critical temperature, {}",
+            path,
+            temperature
+        );
+        drop(zones);
+        power::poweroff();
+    }
+
+    if let Some(passive) = zone.passive {
+        let was_above = matches!(previous, Some(previous) if previous >= passive);
+        if temperature >= passive && !was_above {
+            kwarn!(
+                "thermal: {} is above its passive trip point, {}",
+                path,
+                temperature
+            );
+        } else if temperature < passive && was_above {
+            kinfo!(
+                "thermal: {} is back below its passive trip point, {}",
+                path,
+                temperature
+            );
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Registry of the character devices, byte streams like the serial ports, looked up by name
+
+use ::alloc::vec::Vec;
+use lib::sync::StaticSpinlock;
+
+static DEVICES: StaticSpinlock<Vec<&'static dyn CharDevice>> = StaticSpinlock::new(Vec::new());
+
+pub trait CharDevice: Sync {
+    /// Name of the device, like `ttyS0`
+    fn name(&self) -> &'static str;
+
+    /// Take the bytes already received without waiting, returns how many were read
+    fn read(&self, buffer: &mut [u8]) -> usize;
+
+    /// Queue the bytes to send, only waits when the device can't buffer them. Returns how many
+    /// were written.
+    fn write(&self, buffer: &[u8]) -> usize;
+}
+
+pub fn register(device: &'static dyn CharDevice) {
+    DEVICES.lock().push(device);
+}
+
+pub fn find(name: &str) -> Option<&'static dyn CharDevice> {
+    DEVICES
+        .lock()
+        .iter()
+        .find(|device| device.name() == name)
+        .copied()
+}
+
+pub fn devices() -> Vec<&'static dyn CharDevice> {
+    DEVICES.lock().clone()
+}
@@ -0,0 +1,116 @@
+//! Registry of the devices found by the firmware, drivers look them up by hardware ID
+
+use crate::kernel::mem::addr::PhyAddr;
+
+use ::alloc::string::String;
+use ::alloc::sync::Arc;
+use ::alloc::vec::Vec;
+use lib::sync::StaticSpinlock;
+
+/// Hardware IDs of common devices
+pub mod hid {
+    pub const PCI_BUS: &str = "PNP0A03";
+    pub const PCIE_BUS: &str = "PNP0A08";
+    pub const HPET: &str = "PNP0103";
+    pub const RTC: &str = "PNP0B00";
+    pub const SERIAL_16550: &str = "PNP0501";
+    pub const PS2_KEYBOARD: &str = "PNP0303";
+    pub const PS2_MOUSE: &str = "PNP0F13";
+    pub const POWER_BUTTON: &str = "PNP0C0C";
+}
+
+static DEVICES: StaticSpinlock<Vec<Arc<Device>>> = StaticSpinlock::new(Vec::new());
+
+#[derive(Clone, Debug)]
+pub struct Device {
+    /// Full path of the device in the ACPI namespace, like `\_SB_.PCI0.SF8_`
+    pub path: String,
+    /// `_HID`
+    pub hid: Option<String>,
+    /// `_CID`, IDs of devices this one is compatible with
+    pub cids: Vec<String>,
+    /// `_UID`, tells apart devices with the same hardware ID
+    pub uid: Option<String>,
+    /// `_STA`
+    pub status: u32,
+    /// Resources currently assigned to the device, from `_CRS`
+    pub resources: Vec<Resource>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Resource {
+    Io {
+        base: u16,
+        length: u16,
+    },
+    Memory {
+        base: PhyAddr,
+        length: usize,
+        writable: bool,
+    },
+    Irq {
+        irq: u32,
+        flags: IrqFlags,
+    },
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IrqFlags {
+    pub level_triggered: bool,
+    pub active_low: bool,
+    pub shareable: bool,
+}
+
+impl Device {
+    /// Whether the device has `id` as its hardware ID or as one of its compatible IDs
+    pub fn matches(&self, id: &str) -> bool {
+        self.hid.as_deref() == Some(id) || self.cids.iter().any(|cid| cid == id)
+    }
+
+    pub fn io_ports(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        self.resources
+            .iter()
+            .filter_map(|resource| match *resource {
+                Resource::Io { base, length } => Some((base, length)),
+                _ => None,
+            })
+    }
+
+    pub fn memory(&self) -> impl Iterator<Item = (PhyAddr, usize)> + '_ {
+        self.resources
+            .iter()
+            .filter_map(|resource| match *resource {
+                Resource::Memory { base, length, .. } => Some((base, length)),
+                _ => None,
+            })
+    }
+
+    pub fn irqs(&self) -> impl Iterator<Item = (u32, IrqFlags)> + '_ {
+        self.resources
+            .iter()
+            .filter_map(|resource| match *resource {
+                Resource::Irq { irq, flags } => Some((irq, flags)),
+                _ => None,
+            })
+    }
+}
+
+pub fn register(device: Device) -> Arc<Device> {
+    let device = Arc::new(device);
+    DEVICES.lock().push(device.clone());
+    device
+}
+
+/// Every registered device matching `id`, see `Device::matches`
+pub fn find(id: &str) -> Vec<Arc<Device>> {
+    DEVICES
+        .lock()
+        .iter()
+        .filter(|device| device.matches(id))
+        .cloned()
+        .collect()
+}
+
+pub fn devices() -> Vec<Arc<Device>> {
+    DEVICES.lock().clone()
+}
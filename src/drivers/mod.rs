@@ -1,2 +1,7 @@
 pub mod acpi;
+pub mod chardev;
+pub mod device;
+pub mod pci;
+pub mod rtc;
+pub mod serial;
 pub mod vga_buffer;
@@ -0,0 +1,79 @@
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+const CONFIG_ADDRESS_PORT: u16 = 0xcf8;
+const CONFIG_DATA_PORT: u16 = 0xcfc;
+const CONFIG_ENABLE_BIT: u32 = 1 << 31;
+
+/// Serialize accesses to the configuration address & data ports, must only be taken with
+/// interrupts disabled
+static CONFIG: StaticSpinlock<()> = StaticSpinlock::new(());
+
+/// Location of a PCI function on segment 0
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Address {
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+}
+
+impl Address {
+    pub const fn new(bus: u8, device: u8, function: u8) -> Address {
+        Address {
+            bus,
+            device,
+            function,
+        }
+    }
+
+    fn config_address(&self, offset: u8) -> u32 {
+        CONFIG_ENABLE_BIT
+            | u32::from(self.bus) << 16
+            | u32::from(self.device & 0x1f) << 11
+            | u32::from(self.function & 0x7) << 8
+            | u32::from(offset & 0xfc)
+    }
+}
+
+/// Width of a configuration space access, in bits
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Width {
+    Byte = 8,
+    Word = 16,
+    Dword = 32,
+}
+
+/// Read from the configuration space of `address` through the legacy 0xcf8/0xcfc mechanism.
+/// `offset` must be aligned on `width`.
+pub fn config_read(address: Address, offset: u8, width: Width) -> u32 {
+    let data_port = CONFIG_DATA_PORT + u16::from(offset & 0x3);
+
+    without_interrupts(|| {
+        let _lock = CONFIG.lock();
+        unsafe {
+            io_write_port!(u32, CONFIG_ADDRESS_PORT, address.config_address(offset));
+            match width {
+                Width::Byte => u32::from(io_read_port!(u8, data_port)),
+                Width::Word => u32::from(io_read_port!(u16, data_port)),
+                Width::Dword => io_read_port!(u32, data_port),
+            }
+        }
+    })
+}
+
+/// Write to the configuration space of `address`, `offset` must be aligned on `width`
+pub fn config_write(address: Address, offset: u8, width: Width, value: u32) {
+    let data_port = CONFIG_DATA_PORT + u16::from(offset & 0x3);
+
+    without_interrupts(|| {
+        let _lock = CONFIG.lock();
+        unsafe {
+            io_write_port!(u32, CONFIG_ADDRESS_PORT, address.config_address(offset));
+            match width {
+                Width::Byte => io_write_port!(u8, data_port, value),
+                Width::Word => io_write_port!(u16, data_port, value),
+                Width::Dword => io_write_port!(u32, data_port, value),
+            }
+        }
+    });
+}
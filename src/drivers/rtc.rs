@@ -0,0 +1,250 @@
+use crate::drivers::acpi::tables;
+use crate::kernel::apic::{self, ioapic};
+use crate::kernel::idt::{self, ISA_IRQ_BASE};
+use crate::kernel::table::idt::InterruptStackFrame;
+
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+use core::convert::TryFrom;
+use core::sync::atomic::*;
+
+const CMOS_INDEX_PORT: u16 = 0x70;
+const CMOS_DATA_PORT: u16 = 0x71;
+
+const RTC_IRQ: u8 = 8;
+
+const STATUS_A_UPDATE_IN_PROGRESS: u8 = 1 << 7;
+const STATUS_A_RATE_MASK: u8 = 0x0f;
+const STATUS_B_PERIODIC_INTERRUPT: u8 = 1 << 6;
+const STATUS_B_BINARY: u8 = 1 << 2;
+const STATUS_B_24_HOURS: u8 = 1 << 1;
+const STATUS_C_PERIODIC_INTERRUPT: u8 = 1 << 6;
+const HOURS_PM_BIT: u8 = 1 << 7;
+
+/// Serialize accesses to the CMOS index & data ports, must only be taken with interrupts disabled
+static CMOS: StaticSpinlock<()> = StaticSpinlock::new(());
+
+/// Index of the CMOS century register given by the FADT, 0 if there is none
+static CENTURY_REGISTER: AtomicU8 = AtomicU8::new(0);
+
+/// Handler called on each periodic interrupt, stored as an address so the ISR doesn't need a lock
+static PERIODIC_HANDLER: AtomicUsize = AtomicUsize::new(0);
+
+#[repr(u8)]
+#[derive(Copy, Clone)]
+enum Register {
+    Seconds = 0x00,
+    Minutes = 0x02,
+    Hours = 0x04,
+    DayOfMonth = 0x07,
+    Month = 0x08,
+    Year = 0x09,
+    StatusA = 0x0a,
+    StatusB = 0x0b,
+    StatusC = 0x0c,
+}
+
+/// Date & time as kept by the RTC, assumed to be UTC
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Number of seconds elapsed since the UNIX epoch (1970-01-01 00:00:00 UTC)
+    pub fn to_unix(&self) -> u64 {
+        // days from civil, cf. http://howardhinnant.github.io/date_algorithms.html
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - if month <= 2 { 1 } else { 0 };
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146097 + day_of_era - 719468;
+
+        let seconds = days * 86400
+            + i64::from(self.hour) * 3600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second);
+
+        u64::try_from(seconds).unwrap_or(0)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+struct RawDateTime {
+    second: u8,
+    minute: u8,
+    hour: u8,
+    day: u8,
+    month: u8,
+    year: u8,
+    century: Option<u8>,
+}
+
+/// Find the century register from the FADT
+pub fn init() {
+    if let Some(fadt) = tables::fadt() {
+        CENTURY_REGISTER.store(fadt.century, Ordering::Relaxed);
+    }
+}
+
+/// Read the current date & time from the RTC
+pub fn read() -> DateTime {
+    without_interrupts(|| {
+        let _lock = CMOS.lock();
+
+        // the RTC can update between two reads, read until two consecutive values are the same
+        let mut last = unsafe { read_raw() };
+        loop {
+            let current = unsafe { read_raw() };
+            if current == last {
+                break;
+            }
+            last = current;
+        }
+
+        decode(last, unsafe { read_register(Register::StatusB) })
+    })
+}
+
+/// Enable the periodic interrupt of the RTC, calling `handler` from interrupt context.
+///
+/// The interrupt frequency is `32768 >> (rate - 1)` Hz, `rate` must be between 3 (8192 Hz) and
+/// 15 (2 Hz).
+pub fn enable_periodic_interrupt(rate: u8, handler: fn()) {
+    assert!(
+        (3..=15).contains(&rate),
+        "invalid RTC periodic interrupt rate"
+    );
+
+    PERIODIC_HANDLER.store(handler as usize, Ordering::Release);
+    unsafe { idt::set_handler(ISA_IRQ_BASE + RTC_IRQ, rtc_interrupt) };
+
+    without_interrupts(|| {
+        let _lock = CMOS.lock();
+        unsafe {
+            let status_a = read_register(Register::StatusA);
+            write_register(Register::StatusA, (status_a & !STATUS_A_RATE_MASK) | rate);
+
+            let status_b = read_register(Register::StatusB);
+            write_register(Register::StatusB, status_b | STATUS_B_PERIODIC_INTERRUPT);
+
+            // discard any interrupt that was already pending
+            read_register(Register::StatusC);
+        }
+    });
+
+    if let Err(err) = ioapic::route_isa_irq(RTC_IRQ, ISA_IRQ_BASE + RTC_IRQ) {
+        kwarn!("rtc: can't route the periodic interrupt, {:?}", err);
+    }
+}
+
+pub fn disable_periodic_interrupt() {
+    let (gsi, _) = ioapic::isa_irq_to_gsi(RTC_IRQ, ioapic::RedirectionFlags::empty());
+    if let Err(err) = ioapic::mask_irq(gsi) {
+        kwarn!("rtc: can't mask the periodic interrupt, {:?}", err);
+    }
+
+    without_interrupts(|| {
+        let _lock = CMOS.lock();
+        unsafe {
+            let status_b = read_register(Register::StatusB);
+            write_register(Register::StatusB, status_b & !STATUS_B_PERIODIC_INTERRUPT);
+        }
+    });
+
+    PERIODIC_HANDLER.store(0, Ordering::Release);
+}
+
+isr! {
+    fn rtc_interrupt(_frame: &InterruptStackFrame) {
+        // status C has to be read, otherwise the RTC won't raise any other interrupt
+        let status_c = {
+            let _lock = CMOS.lock();
+            unsafe { read_register(Register::StatusC) }
+        };
+
+        let handler = PERIODIC_HANDLER.load(Ordering::Acquire);
+        if status_c & STATUS_C_PERIODIC_INTERRUPT != 0 && handler != 0 {
+            let handler: fn() = unsafe { core::mem::transmute(handler) };
+            handler();
+        }
+
+        apic::end_of_interrupt();
+    }
+}
+
+fn decode(raw: RawDateTime, status_b: u8) -> DateTime {
+    let convert = |value: u8| {
+        if status_b & STATUS_B_BINARY != 0 {
+            value
+        } else {
+            (value >> 4) * 10 + (value & 0x0f)
+        }
+    };
+
+    let mut hour = convert(raw.hour & !HOURS_PM_BIT);
+    if status_b & STATUS_B_24_HOURS == 0 {
+        // 12 AM is midnight & 12 PM is noon
+        hour %= 12;
+        if raw.hour & HOURS_PM_BIT != 0 {
+            hour += 12;
+        }
+    }
+
+    // without a century register, assume we are in the 21st century
+    let century = raw.century.map(convert).unwrap_or(20);
+
+    DateTime {
+        year: u16::from(century) * 100 + u16::from(convert(raw.year)),
+        month: convert(raw.month),
+        day: convert(raw.day),
+        hour,
+        minute: convert(raw.minute),
+        second: convert(raw.second),
+    }
+}
+
+/// # Safety
+/// The CMOS lock has to be held
+unsafe fn read_raw() -> RawDateTime {
+    while read_register(Register::StatusA) & STATUS_A_UPDATE_IN_PROGRESS != 0 {
+        core::hint::spin_loop();
+    }
+
+    let century_register = CENTURY_REGISTER.load(Ordering::Relaxed);
+    RawDateTime {
+        second: read_register(Register::Seconds),
+        minute: read_register(Register::Minutes),
+        hour: read_register(Register::Hours),
+        day: read_register(Register::DayOfMonth),
+        month: read_register(Register::Month),
+        year: read_register(Register::Year),
+        century: if century_register != 0 {
+            Some(read_cmos(century_register))
+        } else {
+            None
+        },
+    }
+}
+
+unsafe fn read_register(register: Register) -> u8 {
+    read_cmos(register as u8)
+}
+
+unsafe fn write_register(register: Register, value: u8) {
+    io_write_port!(u8, CMOS_INDEX_PORT, register as u8);
+    io_write_port!(u8, CMOS_DATA_PORT, value);
+}
+
+unsafe fn read_cmos(index: u8) -> u8 {
+    io_write_port!(u8, CMOS_INDEX_PORT, index);
+    io_read_port!(u8, CMOS_DATA_PORT)
+}
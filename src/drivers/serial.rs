@@ -0,0 +1,434 @@
+//! 16550 UARTs, the `PNP0501` devices of the ACPI namespace or the legacy COM ports when the
+//! firmware describes none. Once `init` has run, the ports are character devices named `ttyS0` to
+//! `ttyS3` served by their interrupts, and the one given by the `console` parameter takes over the
+//! kernel log from the early serial console.
+//!
+//! `write_polled` writes synchronously on `ttyS0`, COM1 until `init` has run, for
+//! `early_kprintln!`. It works before `init` & after a panic.
+
+mod buffer;
+mod uart;
+
+use crate::drivers::chardev::{self, CharDevice};
+use crate::drivers::device::{self, hid};
+use crate::kernel::apic::{self, ioapic};
+use crate::kernel::idt::{self, ISA_IRQ_BASE};
+use crate::kernel::log::{self, Console};
+use crate::kernel::table::idt::InterruptStackFrame;
+use buffer::ByteRing;
+use uart::*;
+
+use core::convert::TryFrom;
+use core::fmt::{self, Write};
+use core::sync::atomic::*;
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+const BUFFER_SIZE: usize = 4096;
+
+/// Bounds the work of an interrupt, in case the UART keeps one pending
+const MAX_INTERRUPT_ROUNDS: usize = 16;
+
+kernel_param! {
+    /// Speed of the serial ports in bauds, 115200 divided by an integer
+    static SERIAL_BAUD: u32 = 115_200, "serial_baud";
+}
+
+kernel_param! {
+    /// Serial port the kernel log is written to, `none` to keep the early serial console
+    static CONSOLE: &'static str = "ttyS0", "console";
+}
+
+/// I/O base & ISA IRQ of the COM ports, COM1 & COM3 share an IRQ, like COM2 & COM4
+const LEGACY_PORTS: [(u16, u8); 4] = [(0x3f8, 4), (0x2f8, 3), (0x3e8, 4), (0x2e8, 3)];
+
+/// `irq` of the ports served by polling
+const NO_IRQ: u8 = u8::MAX;
+
+pub static PORTS: [SerialPort; 4] = [
+    SerialPort::new("ttyS0", LEGACY_PORTS[0].0, LEGACY_PORTS[0].1),
+    SerialPort::new("ttyS1", 0, NO_IRQ),
+    SerialPort::new("ttyS2", 0, NO_IRQ),
+    SerialPort::new("ttyS3", 0, NO_IRQ),
+];
+
+pub struct SerialPort {
+    name: &'static str,
+    /// I/O base of the UART, 0 when the port has none. Only changed by `init`.
+    base: AtomicU16,
+    /// ISA IRQ or `NO_IRQ`
+    irq: AtomicU8,
+    /// Must only be locked with interrupts disabled, the interrupt handler takes it
+    state: StaticSpinlock<State>,
+}
+
+struct State {
+    probed: bool,
+    /// None when there is no UART
+    model: Option<Model>,
+    /// The receiver & the transmitter are served by the interrupt handler, they are polled
+    /// otherwise
+    interrupts: bool,
+    received: ByteRing<BUFFER_SIZE>,
+    to_send: ByteRing<BUFFER_SIZE>,
+    /// Received bytes lost because the buffer or the FIFO of the UART were full
+    overruns: usize,
+}
+
+impl State {
+    fn probe(&mut self, uart: Uart, baud: u32) {
+        self.send_all(uart);
+        self.model = uart.init(baud);
+        self.probed = true;
+    }
+
+    /// Move the received bytes to the buffer
+    fn receive(&mut self, uart: Uart) {
+        if uart.line_status() & LSR_OVERRUN != 0 {
+            self.overruns += 1;
+        }
+
+        while let Some(byte) = uart.receive() {
+            if !self.received.push(byte) {
+                self.overruns += 1;
+            }
+        }
+    }
+
+    /// Fill the transmitter if it's empty, & only ask for its interrupt when bytes are left
+    fn transmit(&mut self, uart: Uart) {
+        if uart.line_status() & LSR_TRANSMITTER_EMPTY != 0 {
+            let burst = match self.model {
+                Some(Model::Uart16550A) => FIFO_SIZE,
+                _ => 1,
+            };
+
+            for _ in 0..burst {
+                match self.to_send.pop() {
+                    Some(byte) => uart.transmit(byte),
+                    None => break,
+                }
+            }
+        }
+
+        if self.interrupts {
+            uart.set_interrupts(self.enabled_interrupts());
+        }
+    }
+
+    fn send_all(&mut self, uart: Uart) {
+        while let Some(byte) = self.to_send.pop() {
+            uart.transmit_polled(byte);
+        }
+    }
+
+    fn enabled_interrupts(&self) -> u8 {
+        let mut interrupts = IER_RECEIVED_DATA | IER_LINE_STATUS;
+        if !self.to_send.is_empty() {
+            interrupts |= IER_TRANSMITTER_EMPTY;
+        }
+        interrupts
+    }
+}
+
+impl SerialPort {
+    const fn new(name: &'static str, base: u16, irq: u8) -> SerialPort {
+        SerialPort {
+            name,
+            base: AtomicU16::new(base),
+            irq: AtomicU8::new(irq),
+            state: StaticSpinlock::new(State {
+                probed: false,
+                model: None,
+                interrupts: false,
+                received: ByteRing::new(),
+                to_send: ByteRing::new(),
+                overruns: 0,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn base(&self) -> u16 {
+        self.base.load(Ordering::Relaxed)
+    }
+
+    /// None when the port is polled
+    pub fn irq(&self) -> Option<u8> {
+        Some(self.irq.load(Ordering::Relaxed)).filter(|&irq| irq != NO_IRQ)
+    }
+
+    fn uart(&self) -> Uart {
+        Uart::new(self.base())
+    }
+
+    fn configure(&self, base: u16, irq: Option<u8>) {
+        self.base.store(base, Ordering::Relaxed);
+        self.irq.store(irq.unwrap_or(NO_IRQ), Ordering::Relaxed);
+    }
+
+    /// The UART found, None when the port isn't probed yet or has none
+    pub fn model(&self) -> Option<Model> {
+        without_interrupts(|| self.state.lock().model)
+    }
+
+    pub fn overruns(&self) -> usize {
+        without_interrupts(|| self.state.lock().overruns)
+    }
+
+    fn probe(&self, baud: u32) -> Option<Model> {
+        without_interrupts(|| {
+            let mut state = self.state.lock();
+            state.probe(self.uart(), baud);
+            state.model
+        })
+    }
+
+    fn enable_interrupts(&self) {
+        without_interrupts(|| {
+            let mut state = self.state.lock();
+            state.interrupts = true;
+            self.uart().set_interrupts(state.enabled_interrupts());
+        });
+    }
+
+    fn handle_interrupt(&self) {
+        let mut state = self.state.lock();
+        if !state.interrupts {
+            return;
+        }
+
+        let uart = self.uart();
+        for _ in 0..MAX_INTERRUPT_ROUNDS {
+            if !uart.interrupt_pending() {
+                break;
+            }
+
+            uart.modem_status();
+            state.receive(uart);
+            state.transmit(uart);
+        }
+    }
+
+    /// Wait for a byte to be received
+    pub fn read_byte(&self) -> u8 {
+        let mut byte = 0;
+        while self.read(core::slice::from_mut(&mut byte)) == 0 {
+            core::hint::spin_loop();
+        }
+        byte
+    }
+}
+
+impl CharDevice for SerialPort {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn read(&self, buffer: &mut [u8]) -> usize {
+        without_interrupts(|| {
+            let mut state = self.state.lock();
+            if !state.interrupts && state.model.is_some() {
+                state.receive(self.uart());
+            }
+
+            let mut count = 0;
+            while count < buffer.len() {
+                match state.received.pop() {
+                    Some(byte) => buffer[count] = byte,
+                    None => break,
+                }
+                count += 1;
+            }
+            count
+        })
+    }
+
+    fn write(&self, buffer: &[u8]) -> usize {
+        without_interrupts(|| {
+            let mut state = self.state.lock();
+            if state.model.is_none() {
+                return buffer.len();
+            }
+
+            let uart = self.uart();
+            for &byte in buffer {
+                // the interrupt handler can't run while the lock is held, send some bytes here
+                while !state.to_send.push(byte) {
+                    state.transmit(uart);
+                    core::hint::spin_loop();
+                }
+            }
+
+            if state.interrupts {
+                state.transmit(uart);
+            } else {
+                state.send_all(uart);
+            }
+            buffer.len()
+        })
+    }
+}
+
+impl Console for SerialPort {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn write_record(&self, args: fmt::Arguments) {
+        let _ = DeviceWriter(self).write_fmt(format_args!("{}\n", args));
+    }
+}
+
+struct DeviceWriter<'a>(&'a dyn CharDevice);
+
+impl Write for DeviceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+    }
+}
+
+/// Polls the transmitter of a UART, bypassing its buffer
+struct PolledWriter(Uart);
+
+impl Write for PolledWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            self.0.transmit_polled(byte);
+        }
+        Ok(())
+    }
+}
+
+/// Give the ports the I/O base & the IRQ of the `PNP0501` devices, in the order of the namespace,
+/// or those of the COM ports when there are none
+fn configure_ports() {
+    let devices = device::find(hid::SERIAL_16550);
+    let mut ports = PORTS.iter();
+    for device in devices.iter() {
+        let base = match device.io_ports().next() {
+            Some((base, _)) => base,
+            None => {
+                kwarn!("serial: {} has no I/O ports", device.path);
+                continue;
+            }
+        };
+
+        let port = match ports.next() {
+            Some(port) => port,
+            None => {
+                kwarn!("serial: no port left for {} at {:#x}", device.path, base);
+                continue;
+            }
+        };
+
+        // the IRQs of an ISA device are ISA IRQs
+        let irq = device.irqs().next().and_then(|(irq, _)| {
+            let isa_irq = u8::try_from(irq).ok().filter(|&irq| irq < 16);
+            if isa_irq.is_none() {
+                kwarn!("serial: {} has irq {}, it will be polled", device.path, irq);
+            }
+            isa_irq
+        });
+        port.configure(base, irq);
+    }
+
+    if PORTS.iter().all(|port| port.base() == 0) {
+        kdebug!("serial: no 16550 in the ACPI namespace, using the COM ports");
+        for (port, &(base, irq)) in PORTS.iter().zip(LEGACY_PORTS.iter()) {
+            port.configure(base, Some(irq));
+        }
+    }
+}
+
+/// Probe the serial ports & serve them with interrupts, the I/O APIC has to be set up & the ACPI
+/// namespace enumerated
+pub fn init() {
+    configure_ports();
+
+    let baud = SERIAL_BAUD.get();
+    for port in PORTS.iter().filter(|port| port.base() != 0) {
+        match port.probe(baud) {
+            Some(model) => {
+                match port.irq() {
+                    Some(irq) => kinfo!(
+                        "serial: {} at {:#x}, irq {}, {:?}",
+                        port.name,
+                        port.base(),
+                        irq,
+                        model
+                    ),
+                    None => kinfo!(
+                        "serial: {} at {:#x}, polled, {:?}",
+                        port.name,
+                        port.base(),
+                        model
+                    ),
+                }
+                chardev::register(port);
+            }
+            None => kdebug!("serial: no UART at {:#x}", port.base()),
+        }
+    }
+
+    for port in PORTS.iter().filter(|port| port.model().is_some()) {
+        let irq = match port.irq() {
+            Some(irq) => irq,
+            None => continue,
+        };
+
+        // ports sharing an IRQ share the handler, it serves all of them
+        unsafe { idt::set_handler(ISA_IRQ_BASE + irq, serial_interrupt) };
+        port.enable_interrupts();
+        if let Err(err) = ioapic::route_isa_irq(irq, ISA_IRQ_BASE + irq) {
+            kwarn!("serial: can't route the irq of {}, {:?}", port.name, err);
+        }
+    }
+
+    let console = CONSOLE.get();
+    if console == "none" {
+        return;
+    }
+
+    match PORTS
+        .iter()
+        .find(|port| port.name == console && port.model().is_some())
+    {
+        Some(port) => log::replace_console(log::EARLY_SERIAL_CONSOLE, port),
+        None => kwarn!(
+            "serial: no serial port {}, keeping the early console",
+            console
+        ),
+    }
+}
+
+/// Write on `ttyS0` without interrupts, setting it up first if needed. It doesn't wait for the port
+/// when it's locked, the kernel may have panicked while holding it.
+pub fn write_polled(args: fmt::Arguments) {
+    let port = &PORTS[0];
+    without_interrupts(|| {
+        let mut state = port.state.try_lock();
+        if let Some(state) = state.as_mut() {
+            if !state.probed {
+                state.probe(port.uart(), SERIAL_BAUD.get());
+            }
+            // the bytes already queued come first
+            state.send_all(port.uart());
+        }
+
+        let _ = PolledWriter(port.uart()).write_fmt(args);
+    });
+}
+
+isr! {
+    fn serial_interrupt(_frame: &InterruptStackFrame) {
+        for port in PORTS.iter() {
+            port.handle_interrupt();
+        }
+        apic::end_of_interrupt();
+    }
+}
@@ -0,0 +1,46 @@
+/// Fixed-size FIFO of bytes, the oldest byte comes out first
+pub struct ByteRing<const N: usize> {
+    bytes: [u8; N],
+    start: usize,
+    length: usize,
+}
+
+impl<const N: usize> ByteRing<N> {
+    pub const fn new() -> ByteRing<N> {
+        ByteRing {
+            bytes: [0; N],
+            start: 0,
+            length: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.length == N
+    }
+
+    /// Add a byte at the end, false when the ring is full
+    pub fn push(&mut self, byte: u8) -> bool {
+        if self.is_full() {
+            return false;
+        }
+
+        self.bytes[(self.start + self.length) % N] = byte;
+        self.length += 1;
+        true
+    }
+
+    pub fn pop(&mut self) -> Option<u8> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let byte = self.bytes[self.start];
+        self.start = (self.start + 1) % N;
+        self.length -= 1;
+        Some(byte)
+    }
+}
@@ -0,0 +1,182 @@
+//! Registers of the 8250 family of UARTs, up to the 16550A and its 16 bytes FIFOs
+
+use lib::*;
+
+/// Frequency of the clock of the baud rate generator divided by 16
+const BASE_BAUD: u32 = 115_200;
+
+/// Bytes the transmitter FIFO of a 16550A holds
+pub const FIFO_SIZE: usize = 16;
+
+const DATA: u16 = 0;
+const INTERRUPT_ENABLE: u16 = 1;
+const INTERRUPT_ID: u16 = 2;
+const FIFO_CONTROL: u16 = 2;
+const LINE_CONTROL: u16 = 3;
+const MODEM_CONTROL: u16 = 4;
+const LINE_STATUS: u16 = 5;
+const MODEM_STATUS: u16 = 6;
+const SCRATCH: u16 = 7;
+/// With DLAB set in the line control register
+const DIVISOR_LOW: u16 = 0;
+const DIVISOR_HIGH: u16 = 1;
+
+pub const IER_RECEIVED_DATA: u8 = 1 << 0;
+pub const IER_TRANSMITTER_EMPTY: u8 = 1 << 1;
+pub const IER_LINE_STATUS: u8 = 1 << 2;
+
+const IIR_NO_INTERRUPT: u8 = 1 << 0;
+const IIR_FIFO_MASK: u8 = 0b11 << 6;
+
+const FCR_ENABLE: u8 = 1 << 0;
+const FCR_CLEAR_RECEIVER: u8 = 1 << 1;
+const FCR_CLEAR_TRANSMITTER: u8 = 1 << 2;
+/// Raise the received data interrupt once 14 bytes are waiting
+const FCR_TRIGGER_14: u8 = 0b11 << 6;
+
+const LCR_8_BITS: u8 = 0b11;
+const LCR_DLAB: u8 = 1 << 7;
+
+const MCR_DTR: u8 = 1 << 0;
+const MCR_RTS: u8 = 1 << 1;
+const MCR_OUT1: u8 = 1 << 2;
+/// Connects the interrupt line of the UART to the interrupt controller on PCs
+const MCR_OUT2: u8 = 1 << 3;
+const MCR_LOOPBACK: u8 = 1 << 4;
+
+pub const LSR_DATA_READY: u8 = 1 << 0;
+pub const LSR_OVERRUN: u8 = 1 << 1;
+pub const LSR_TRANSMITTER_EMPTY: u8 = 1 << 5;
+
+/// Spins waiting for the transmitter before giving up, in case the UART doesn't answer
+const TRANSMIT_TIMEOUT: usize = 100_000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// 8250 or 16450, without FIFOs
+    Uart8250,
+    /// The FIFOs of the 16550 are broken, they aren't used
+    Uart16550,
+    Uart16550A,
+}
+
+#[derive(Copy, Clone)]
+pub struct Uart {
+    base: u16,
+}
+
+impl Uart {
+    pub const fn new(base: u16) -> Uart {
+        Uart { base }
+    }
+
+    pub fn base(&self) -> u16 {
+        self.base
+    }
+
+    unsafe fn read(&self, register: u16) -> u8 {
+        io_read_port!(u8, self.base + register)
+    }
+
+    unsafe fn write(&self, register: u16, value: u8) {
+        io_write_port!(u8, self.base + register, value);
+    }
+
+    /// Check the UART is there with the scratch register & the loopback mode, then set it up for
+    /// `baud` bauds, 8 data bits, no parity & 1 stop bit. Interrupts are left disabled.
+    pub fn init(&self, baud: u32) -> Option<Model> {
+        unsafe {
+            self.write(SCRATCH, 0x5a);
+            if self.read(SCRATCH) != 0x5a {
+                return None;
+            }
+
+            self.write(INTERRUPT_ENABLE, 0);
+            self.set_baud_rate(baud);
+            self.write(LINE_CONTROL, LCR_8_BITS);
+
+            // a byte sent in loopback mode is received right away
+            self.write(MODEM_CONTROL, MCR_LOOPBACK | MCR_OUT2 | MCR_OUT1 | MCR_RTS);
+            self.write(DATA, 0xae);
+            if self.read(DATA) != 0xae {
+                return None;
+            }
+
+            self.write(MODEM_CONTROL, MCR_OUT2 | MCR_OUT1 | MCR_RTS | MCR_DTR);
+
+            self.write(
+                FIFO_CONTROL,
+                FCR_ENABLE | FCR_CLEAR_RECEIVER | FCR_CLEAR_TRANSMITTER | FCR_TRIGGER_14,
+            );
+            let model = match self.read(INTERRUPT_ID) & IIR_FIFO_MASK {
+                IIR_FIFO_MASK => Model::Uart16550A,
+                0 => Model::Uart8250,
+                _ => Model::Uart16550,
+            };
+            if model != Model::Uart16550A {
+                self.write(FIFO_CONTROL, 0);
+            }
+
+            // discard what was received before
+            while self.read(LINE_STATUS) & LSR_DATA_READY != 0 {
+                self.read(DATA);
+            }
+
+            Some(model)
+        }
+    }
+
+    /// The closest rate the UART can do is used when it can't do `baud` exactly
+    unsafe fn set_baud_rate(&self, baud: u32) {
+        let divisor = (BASE_BAUD / baud.clamp(1, BASE_BAUD)).min(u32::from(u16::MAX));
+        let line_control = self.read(LINE_CONTROL);
+        self.write(LINE_CONTROL, line_control | LCR_DLAB);
+        self.write(DIVISOR_LOW, (divisor & 0xff) as u8);
+        self.write(DIVISOR_HIGH, (divisor >> 8) as u8);
+        self.write(LINE_CONTROL, line_control & !LCR_DLAB);
+    }
+
+    pub fn set_interrupts(&self, interrupts: u8) {
+        unsafe { self.write(INTERRUPT_ENABLE, interrupts) };
+    }
+
+    /// Whether an interrupt is pending, reading it acknowledges a transmitter empty interrupt
+    pub fn interrupt_pending(&self) -> bool {
+        unsafe { self.read(INTERRUPT_ID) & IIR_NO_INTERRUPT == 0 }
+    }
+
+    pub fn line_status(&self) -> u8 {
+        unsafe { self.read(LINE_STATUS) }
+    }
+
+    /// Acknowledge a modem status interrupt
+    pub fn modem_status(&self) -> u8 {
+        unsafe { self.read(MODEM_STATUS) }
+    }
+
+    /// A received byte, if any
+    pub fn receive(&self) -> Option<u8> {
+        if self.line_status() & LSR_DATA_READY == 0 {
+            return None;
+        }
+
+        Some(unsafe { self.read(DATA) })
+    }
+
+    /// Write a byte to the transmitter, which must be empty
+    pub fn transmit(&self, byte: u8) {
+        unsafe { self.write(DATA, byte) };
+    }
+
+    /// Send a byte once the transmitter is empty
+    pub fn transmit_polled(&self, byte: u8) {
+        for _ in 0..TRANSMIT_TIMEOUT {
+            if self.line_status() & LSR_TRANSMITTER_EMPTY != 0 {
+                break;
+            }
+            core::hint::spin_loop();
+        }
+
+        self.transmit(byte);
+    }
+}
@@ -1,33 +1,64 @@
+#[macro_use]
+pub mod log;
 pub mod apic;
 pub mod config;
+pub mod cpu;
+pub mod deferred;
+pub mod idle;
 pub mod idt;
+pub mod initrd;
 pub mod mem;
+pub mod power;
+pub mod shell;
 pub mod table;
+pub mod time;
+pub mod unwind;
 
+use crate::boot::kaslr;
 use core::ops::Range;
-use mem::addr::PhyAddr;
+use mem::addr::{PhyAddr, VirtAddr};
 
 extern "C" {
     static kernel_start_addr: usize;
     static kernel_end_addr: usize;
 
-    static __eh_frame_hdr: usize;
-    static __eh_frame: usize;
+    static __eh_frame_hdr: u8;
+    static __eh_frame_hdr_end: u8;
+    static __eh_frame: u8;
+    static __eh_frame_end: u8;
 }
 
-pub fn get_eh_frame_hdr_ptr() -> *const () {
-    return unsafe { __eh_frame_hdr as *const () };
+/// Search table of the call frame information
+pub fn get_eh_frame_hdr() -> &'static [u8] {
+    unsafe { section(&__eh_frame_hdr, &__eh_frame_hdr_end) }
 }
 
-pub fn get_eh_frame_ptr() -> *const () {
-    return unsafe { __eh_frame as *const () };
+/// Call frame information of the kernel functions
+pub fn get_eh_frame() -> &'static [u8] {
+    unsafe { section(&__eh_frame, &__eh_frame_end) }
 }
 
+unsafe fn section(start: &'static u8, end: &'static u8) -> &'static [u8] {
+    let start = start as *const u8;
+    let length = (end as *const u8 as usize) - (start as usize);
+    core::slice::from_raw_parts(start, length)
+}
+
+/// Physical memory holding the kernel image
 pub fn kernel_range() -> Range<PhyAddr> {
+    let range = kernel_virtual_range();
+    Range {
+        start: PhyAddr::new(usize::from(range.start) - kaslr::kernel_base()),
+        end: PhyAddr::new(usize::from(range.end) - kaslr::kernel_base()),
+    }
+}
+
+/// Addresses of the kernel image, it runs at `kaslr::kernel_base()` plus its physical address
+pub fn kernel_virtual_range() -> Range<VirtAddr> {
     unsafe {
         Range {
-            start: PhyAddr::from(&kernel_start_addr),
-            end: PhyAddr::from(&kernel_end_addr),
+            start: VirtAddr::from(&kernel_start_addr),
+            end: VirtAddr::from(&kernel_end_addr),
         }
     }
 }
@@ -1,5 +1,10 @@
+mod commands;
+pub mod ioapic;
+pub mod ipi;
 pub mod registers;
+pub mod timer;
 
+use crate::kernel::idt::SPURIOUS_VECTOR;
 use crate::kernel::mem::addr::*;
 use crate::kernel::mem::vbox::*;
 
@@ -12,6 +17,9 @@ use core::sync::atomic::*;
 
 static APIC_REGS: StaticSpinlock<Vec<APIC>> = StaticSpinlock::new(Vec::new());
 
+/// Mapping of the local APIC registers, every CPU sees its own local APIC at that address
+static LOCAL_APIC: AtomicPtr<APICRegisters> = AtomicPtr::new(core::ptr::null_mut());
+
 pub struct APIC {
     handle: VBox<APICRegisters>,
     cpu_hw_id: u8,
@@ -23,12 +31,13 @@ impl APIC {
         usize::try_from(self.get32(APICRegister::ApicID).load(Ordering::SeqCst) >> 24).unwrap()
     }
 
-    pub fn set_spurious_int_handler(&mut self, _vector: u8) {
-        self.get32(APICRegister::SpuriousInterruptVector);
+    pub fn set_spurious_int_handler(&mut self, vector: u8) {
+        self.get32(APICRegister::SpuriousInterruptVector).store(
+            u32::from(vector) | APICRegisters::SOFTWARE_ENABLE_BIT,
+            Ordering::SeqCst,
+        );
     }
 
-    pub fn set_timer(&mut self, _count: usize, _periodic: bool) {}
-
     pub fn end_of_interrupt(&mut self) {
         self.get32(APICRegister::EndOfInterrupt)
             .store(0, Ordering::SeqCst);
@@ -37,7 +46,7 @@ impl APIC {
 
 impl APIC {
     fn get32(&self, register: APICRegister) -> &AtomicU32 {
-        &self.handle.registers[(register as usize) / core::mem::size_of::<APICRegister>()]
+        self.handle.get32(register)
     }
 }
 
@@ -78,8 +87,15 @@ struct APICRegisters {
     registers: [AtomicU32; 1024],
 }
 
+impl APICRegisters {
+    fn get32(&self, register: APICRegister) -> &AtomicU32 {
+        &self.registers[(register as usize) / core::mem::size_of::<u32>()]
+    }
+}
+
 impl APICRegisters {
     const MSR_APIC_BASE_ADDR: usize = 0x1b;
+    const SOFTWARE_ENABLE_BIT: u32 = 1 << 8;
     const BSC_BIT: u32 = 8;
     const APIC_ENABLE_BIT: u32 = 1 << 11;
     const ADDR_MASK_HIGH: u32 = (1 << 20) - 1;
@@ -100,7 +116,7 @@ pub fn setup_apic() {
             | usize::try_from(register[1] & APICRegisters::ADDR_MASK_LOW).unwrap(),
     );
 
-    let apic = APIC {
+    let mut apic = APIC {
         handle: unsafe {
             VBox::with_flags(
                 phy_addr,
@@ -108,18 +124,34 @@ pub fn setup_apic() {
             )
             .unwrap()
         },
-        cpu_hw_id: u8::try_from(cpuid!(0x1)[1] >> 24).unwrap(),
+        cpu_hw_id: current_apic_id(),
         is_bsc: (register[1] & APICRegisters::BSC_BIT) != 0,
     };
 
+    apic.set_spurious_int_handler(SPURIOUS_VECTOR);
+    LOCAL_APIC.store(&**apic.handle as *const _ as *mut _, Ordering::Release);
     APIC_REGS.lock().push(apic);
 
-    let boot_info = crate::boot::multiboot::get_boot_info();
-    let tag = boot_info
-        .get_tag(crate::boot::multiboot::TagType::ACPIOldRsdp)
-        .unwrap();
+    ioapic::setup_ioapic();
+    ipi::setup_ipi();
+    timer::setup_timer();
+    commands::register();
+}
+
+/// Hardware id of the local APIC of the current CPU
+pub fn current_apic_id() -> u8 {
+    u8::try_from(cpuid!(0x1)[1] >> 24).unwrap()
+}
+
+/// Signal the end of the interrupt being serviced to the local APIC of the current CPU
+pub fn end_of_interrupt() {
+    local_register(APICRegister::EndOfInterrupt).store(0, Ordering::SeqCst);
+}
 
-    let _rdsp_ptr = tag.data();
+fn local_register(register: APICRegister) -> &'static AtomicU32 {
+    let registers = LOCAL_APIC.load(Ordering::Acquire);
+    assert!(!registers.is_null(), "local APIC isn't mapped yet");
+    unsafe { (*registers).get32(register) }
 }
 
 #[inline]
@@ -0,0 +1,92 @@
+//! Shell command to look at the local APIC & the I/O APIC
+
+use super::ioapic::{self, RedirectionFlags};
+use super::{local_register, APICRegister};
+use crate::kernel::shell::{self, Command, CommandError};
+
+use core::fmt::Write;
+use core::sync::atomic::Ordering;
+
+/// Bit of the local vector table entries masking the interrupt
+const LVT_MASKED: u32 = 1 << 16;
+
+pub(super) fn register() {
+    shell::register(&Apic);
+}
+
+struct Apic;
+
+impl Command for Apic {
+    fn name(&self) -> &'static str {
+        "apic"
+    }
+
+    fn description(&self) -> &'static str {
+        "print the local APIC registers & the I/O APIC redirections"
+    }
+
+    fn run(&self, _args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        // the error status register is only updated by a write
+        local_register(APICRegister::ErrorStatus).store(0, Ordering::SeqCst);
+
+        let registers = [
+            ("id", APICRegister::ApicID),
+            ("version", APICRegister::ApicVersion),
+            ("task priority", APICRegister::TaskPriority),
+            ("spurious", APICRegister::SpuriousInterruptVector),
+            ("error status", APICRegister::ErrorStatus),
+        ];
+        for (name, register) in registers {
+            writeln!(out, "{:<14} {:08x}", name, read(register))?;
+        }
+
+        let vectors = [
+            ("timer", APICRegister::TimerLocalVectorTable),
+            ("thermal", APICRegister::ThermalLocalVector),
+            (
+                "performance",
+                APICRegister::PerformanceCounterLocalVectorTable,
+            ),
+            ("lint0", APICRegister::LocalInterrupt0VectorTable),
+            ("lint1", APICRegister::LocalInterrupt1VectorTable),
+            ("error", APICRegister::ErrorVectorTable),
+        ];
+        for (name, register) in vectors {
+            let value = read(register);
+            write!(
+                out,
+                "lvt {:<10} {:08x} vector {:3}",
+                name,
+                value,
+                value & 0xff
+            )?;
+            if value & LVT_MASKED != 0 {
+                out.write_str(", masked")?;
+            }
+            writeln!(out)?;
+        }
+
+        writeln!(out, "I/O APIC:")?;
+        for (gsi, entry) in ioapic::redirections() {
+            let flags = RedirectionFlags::from_bits_truncate(entry as u32);
+            // masked inputs were never routed
+            if flags.contains(RedirectionFlags::MASKED) {
+                continue;
+            }
+
+            writeln!(
+                out,
+                "  gsi {:3} vector {:3} apic {:3} {:?}",
+                gsi,
+                entry & 0xff,
+                entry >> 56,
+                flags
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn read(register: APICRegister) -> u32 {
+    local_register(register).load(Ordering::SeqCst)
+}
@@ -0,0 +1,208 @@
+use crate::drivers::acpi::tables;
+use crate::kernel::apic::current_apic_id;
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::vbox::*;
+
+use ::alloc::vec::Vec;
+use bitflags::*;
+use lib::sync::StaticSpinlock;
+
+use core::sync::atomic::*;
+
+/// Physical address of the I/O APIC on PC compatible systems, used when there is no MADT
+const DEFAULT_BASE_ADDR: PhyAddr = PhyAddr::new(0xfec0_0000);
+
+static IOAPICS: StaticSpinlock<Vec<IOAPIC>> = StaticSpinlock::new(Vec::new());
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IOAPICError {
+    /// No I/O APIC handles this global system interrupt
+    NoSuchGsi(u32),
+}
+
+pub type Result<T> = core::result::Result<T, IOAPICError>;
+
+pub struct IOAPIC {
+    handle: VBox<IOAPICRegisters>,
+    /// First global system interrupt handled by this I/O APIC
+    gsi_base: u32,
+    inputs: u32,
+}
+
+impl IOAPIC {
+    const IOREGSEL: usize = 0x00;
+    const IOWIN: usize = 0x10;
+
+    const IOAPICVER: u32 = 0x01;
+    const IOREDTBL: u32 = 0x10;
+
+    fn read(&self, register: u32) -> u32 {
+        self.get32(Self::IOREGSEL).store(register, Ordering::SeqCst);
+        self.get32(Self::IOWIN).load(Ordering::SeqCst)
+    }
+
+    fn write(&self, register: u32, value: u32) {
+        self.get32(Self::IOREGSEL).store(register, Ordering::SeqCst);
+        self.get32(Self::IOWIN).store(value, Ordering::SeqCst);
+    }
+
+    fn get32(&self, offset: usize) -> &AtomicU32 {
+        &self.handle.registers[offset / core::mem::size_of::<u32>()]
+    }
+
+    /// Number of interrupt inputs handled by this I/O APIC
+    pub fn input_count(&self) -> u32 {
+        self.inputs
+    }
+
+    /// Input of this I/O APIC connected to `gsi`
+    fn input(&self, gsi: u32) -> Option<u32> {
+        gsi.checked_sub(self.gsi_base)
+            .filter(|&input| input < self.inputs)
+    }
+
+    /// Program the redirection entry of `input`
+    pub fn set_redirection(&self, input: u32, vector: u8, flags: RedirectionFlags, dest: u8) {
+        let register = Self::IOREDTBL + input * 2;
+        self.write(register, RedirectionFlags::MASKED.bits());
+        self.write(register + 1, u32::from(dest) << 24);
+        self.write(register, u32::from(vector) | flags.bits());
+    }
+
+    /// Redirection entry of `input`, the destination is in the high half
+    pub fn redirection(&self, input: u32) -> u64 {
+        let register = Self::IOREDTBL + input * 2;
+        let low = self.read(register);
+        let high = self.read(register + 1);
+        u64::from(high) << 32 | u64::from(low)
+    }
+
+    pub fn mask(&self, input: u32) {
+        let register = Self::IOREDTBL + input * 2;
+        let value = self.read(register);
+        self.write(register, value | RedirectionFlags::MASKED.bits());
+    }
+}
+
+#[repr(align(4096))]
+struct IOAPICRegisters {
+    registers: [AtomicU32; 1024],
+}
+
+bitflags! {
+    /// Flags of an I/O APIC redirection entry, delivery mode is always fixed
+    #[derive(Default)]
+    pub struct RedirectionFlags : u32 {
+        const MASKED = 1 << 16;
+        const LEVEL_TRIGGERED = 1 << 15;
+        const ACTIVE_LOW = 1 << 13;
+        const LOGICAL_DESTINATION = 1 << 11;
+    }
+}
+
+/// Map the I/O APICs described by the MADT & mask all of their inputs
+pub fn setup_ioapic() {
+    let mut ioapics = tables::madt()
+        .map(|madt| {
+            madt.io_apics()
+                .map(|(_, address, gsi_base)| {
+                    (PhyAddr::new(usize::try_from(address).unwrap()), gsi_base)
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if ioapics.is_empty() {
+        ioapics.push((DEFAULT_BASE_ADDR, 0));
+    }
+
+    for (base_addr, gsi_base) in ioapics {
+        let mut ioapic = IOAPIC {
+            handle: unsafe {
+                VBox::with_flags(
+                    base_addr,
+                    Flags::NO_EXECUTE
+                        | Flags::CACHE_DISABLE
+                        | Flags::WRITETHROUGH
+                        | Flags::READ_WRITE,
+                )
+                .expect("failed to map the I/O APIC")
+            },
+            gsi_base,
+            inputs: 0,
+        };
+        ioapic.inputs = ((ioapic.read(IOAPIC::IOAPICVER) >> 16) & 0xff) + 1;
+
+        for input in 0..ioapic.input_count() {
+            ioapic.mask(input);
+        }
+
+        kinfo!(
+            "ioapic: {} inputs from gsi {}",
+            ioapic.input_count(),
+            ioapic.gsi_base
+        );
+        IOAPICS.lock().push(ioapic);
+    }
+}
+
+/// Route an ISA IRQ to `vector` on the current CPU. ISA IRQs are edge-triggered & active high
+/// unless the MADT says otherwise.
+pub fn route_isa_irq(irq: u8, vector: u8) -> Result<()> {
+    let (gsi, flags) = isa_irq_to_gsi(irq, RedirectionFlags::empty());
+    route_irq(gsi, vector, flags)
+}
+
+/// Global system interrupt an ISA IRQ is connected to & its flags, using the interrupt source
+/// overrides of the MADT. `default` is used when the MADT doesn't specify the polarity or the
+/// trigger mode.
+pub fn isa_irq_to_gsi(irq: u8, default: RedirectionFlags) -> (u32, RedirectionFlags) {
+    let (gsi, madt_flags) = match tables::madt() {
+        Some(madt) => madt.isa_irq(irq),
+        None => return (u32::from(irq), default),
+    };
+
+    let mut flags = default;
+    if let Some(active_low) = madt_flags.active_low() {
+        flags.set(RedirectionFlags::ACTIVE_LOW, active_low);
+    }
+    if let Some(level_triggered) = madt_flags.level_triggered() {
+        flags.set(RedirectionFlags::LEVEL_TRIGGERED, level_triggered);
+    }
+
+    (gsi, flags)
+}
+
+/// Route the global system interrupt `gsi` to `vector` on the current CPU
+pub fn route_irq(gsi: u32, vector: u8, flags: RedirectionFlags) -> Result<()> {
+    with_input(gsi, |ioapic, input| {
+        ioapic.set_redirection(input, vector, flags, current_apic_id())
+    })
+}
+
+pub fn mask_irq(gsi: u32) -> Result<()> {
+    with_input(gsi, |ioapic, input| ioapic.mask(input))
+}
+
+/// Run `f` on the I/O APIC handling `gsi` & its input connected to it
+fn with_input(gsi: u32, f: impl FnOnce(&IOAPIC, u32)) -> Result<()> {
+    let ioapics = IOAPICS.lock();
+    let (ioapic, input) = ioapics
+        .iter()
+        .find_map(|ioapic| Some((ioapic, ioapic.input(gsi)?)))
+        .ok_or(IOAPICError::NoSuchGsi(gsi))?;
+
+    f(ioapic, input);
+    Ok(())
+}
+
+/// Global system interrupt & redirection entry of every input of the I/O APICs
+pub fn redirections() -> Vec<(u32, u64)> {
+    IOAPICS
+        .lock()
+        .iter()
+        .flat_map(|ioapic| {
+            (0..ioapic.input_count())
+                .map(move |input| (ioapic.gsi_base + input, ioapic.redirection(input)))
+        })
+        .collect()
+}
@@ -0,0 +1,149 @@
+use super::{local_register, APICRegister};
+use crate::kernel::cpu::{self, MAX_CPUS};
+use crate::kernel::idt::{self, CALL_FUNCTION_VECTOR};
+use crate::kernel::table::idt::InterruptStackFrame;
+
+use ::alloc::sync::Arc;
+use ::alloc::vec::Vec;
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+use core::sync::atomic::*;
+
+const ICR_DELIVERY_PENDING: u32 = 1 << 12;
+const ICR_LEVEL_ASSERT: u32 = 1 << 14;
+const ICR_SHORTHAND_SELF: u32 = 0b01 << 18;
+const ICR_SHORTHAND_ALL: u32 = 0b10 << 18;
+const ICR_SHORTHAND_ALL_BUT_SELF: u32 = 0b11 << 18;
+
+/// Remote function calls waiting to run on each CPU, must only be locked with interrupts disabled
+static CALL_QUEUES: [StaticSpinlock<Vec<Call>>; MAX_CPUS] =
+    [const { StaticSpinlock::new(Vec::new()) }; MAX_CPUS];
+
+/// Target of an inter-processor interrupt
+#[derive(Copy, Clone, Debug)]
+pub enum Destination {
+    /// The CPU with the given local APIC id
+    Apic(u8),
+    /// The current CPU
+    Current,
+    /// Every CPU, including the current one
+    All,
+    /// Every CPU except the current one
+    AllButCurrent,
+}
+
+struct Call {
+    function: Arc<dyn Fn() + Send + Sync>,
+    pending: Arc<AtomicUsize>,
+}
+
+/// Install the handler of the remote function call IPI on the current CPU
+pub fn setup_ipi() {
+    unsafe { idt::set_handler(CALL_FUNCTION_VECTOR, call_function_interrupt) };
+}
+
+/// Send the fixed interrupt `vector` to `destination`
+pub fn send_ipi(destination: Destination, vector: u8) {
+    let (shorthand, apic_id) = match destination {
+        Destination::Apic(apic_id) => (0, apic_id),
+        Destination::Current => (ICR_SHORTHAND_SELF, 0),
+        Destination::All => (ICR_SHORTHAND_ALL, 0),
+        Destination::AllButCurrent => (ICR_SHORTHAND_ALL_BUT_SELF, 0),
+    };
+
+    without_interrupts(|| {
+        wait_for_delivery();
+        local_register(APICRegister::InterruptCommandHigh)
+            .store(u32::from(apic_id) << 24, Ordering::SeqCst);
+        // writing the low half sends the IPI
+        local_register(APICRegister::InterruptCommandLow).store(
+            shorthand | ICR_LEVEL_ASSERT | u32::from(vector),
+            Ordering::SeqCst,
+        );
+        wait_for_delivery();
+    });
+}
+
+/// Run `f` on the CPU `cpu`, from interrupt context.
+///
+/// If `wait` is set, only returns once `f` has completed.
+pub fn call_function(cpu: usize, f: impl Fn() + Send + Sync + 'static, wait: bool) {
+    call_function_many(core::iter::once(cpu), f, wait);
+}
+
+/// Run `f` on every online CPU except the current one, from interrupt context.
+///
+/// If `wait` is set, only returns once `f` has completed everywhere.
+pub fn call_function_others(f: impl Fn() + Send + Sync + 'static, wait: bool) {
+    let current = cpu::current();
+    call_function_many(cpu::online().filter(|&cpu| cpu != current), f, wait);
+}
+
+/// Run `f` on each CPU of `cpus`, from interrupt context.
+///
+/// The current CPU runs `f` directly with interrupts disabled if it is part of `cpus`. If `wait`
+/// is set, only returns once `f` has completed on every CPU. While waiting, calls sent to the
+/// current CPU keep being served so that two CPUs calling each other can't deadlock.
+pub fn call_function_many(
+    cpus: impl IntoIterator<Item = usize>,
+    f: impl Fn() + Send + Sync + 'static,
+    wait: bool,
+) {
+    let function: Arc<dyn Fn() + Send + Sync> = Arc::new(f);
+    let pending = Arc::new(AtomicUsize::new(0));
+    let current = cpu::current();
+    let mut run_locally = false;
+
+    for cpu in cpus {
+        if cpu == current {
+            run_locally = true;
+            continue;
+        }
+
+        pending.fetch_add(1, Ordering::SeqCst);
+        let call = Call {
+            function: function.clone(),
+            pending: pending.clone(),
+        };
+
+        without_interrupts(|| CALL_QUEUES[cpu].lock().push(call));
+        send_ipi(Destination::Apic(cpu::apic_id(cpu)), CALL_FUNCTION_VECTOR);
+    }
+
+    if run_locally {
+        without_interrupts(|| function());
+    }
+
+    if wait {
+        while pending.load(Ordering::Acquire) != 0 {
+            without_interrupts(run_pending_calls);
+            core::hint::spin_loop();
+        }
+    }
+}
+
+/// Run the remote function calls sent to the current CPU, interrupts must be disabled
+fn run_pending_calls() {
+    let calls = core::mem::take(&mut *CALL_QUEUES[cpu::current()].lock());
+    for call in calls {
+        (call.function)();
+        call.pending.fetch_sub(1, Ordering::Release);
+    }
+}
+
+fn wait_for_delivery() {
+    while local_register(APICRegister::InterruptCommandLow).load(Ordering::SeqCst)
+        & ICR_DELIVERY_PENDING
+        != 0
+    {
+        core::hint::spin_loop();
+    }
+}
+
+isr! {
+    fn call_function_interrupt(_frame: &InterruptStackFrame) {
+        run_pending_calls();
+        super::end_of_interrupt();
+    }
+}
@@ -0,0 +1,105 @@
+//! Periodic interrupts from the local APIC timer. The handler only acknowledges them, they are
+//! there to wake the idle loop up so it can poll what needs to be.
+//!
+//! Without the always running APIC timer feature, CPUID 6 EAX bit 2, the timer stops in the ACPI
+//! C-states deeper than C1. The idle loop then stays in C1, see `idle::limit_to_c1`.
+
+use super::{local_register, APICRegister};
+use crate::kernel::idt::{self, TIMER_VECTOR};
+use crate::kernel::table::idt::InterruptStackFrame;
+use crate::kernel::time;
+
+use lib::*;
+
+use core::convert::TryFrom;
+use core::sync::atomic::*;
+use core::time::Duration;
+
+const LVT_MASKED: u32 = 1 << 16;
+const LVT_PERIODIC: u32 = 1 << 17;
+/// Divide configuration dividing the bus clock by 16
+const DIVIDE_BY_16: u32 = 0b0011;
+
+const CPUID_POWER_MANAGEMENT_LEAF: u32 = 0x6;
+const CPUID_ALWAYS_RUNNING_TIMER: u32 = 1 << 2;
+
+const CALIBRATION: Duration = Duration::from_millis(10);
+
+/// Timer ticks per second with the divider, 0 until it's calibrated
+static FREQUENCY: AtomicU64 = AtomicU64::new(0);
+
+/// Whether the timer keeps counting in the C-states deeper than C1
+pub fn always_running() -> bool {
+    cpuid!(0x0)[0] >= CPUID_POWER_MANAGEMENT_LEAF
+        && cpuid!(CPUID_POWER_MANAGEMENT_LEAF)[0] & CPUID_ALWAYS_RUNNING_TIMER != 0
+}
+
+/// Install the timer handler on the current CPU, the timer stays masked until `set_periodic`
+pub fn setup_timer() {
+    unsafe { idt::set_handler(TIMER_VECTOR, timer_interrupt) };
+    local_register(APICRegister::TimerLocalVectorTable)
+        .store(LVT_MASKED | u32::from(TIMER_VECTOR), Ordering::SeqCst);
+}
+
+/// Interrupt the current CPU every `period`, or stop the timer with `None`. The monotonic clock
+/// has to be calibrated.
+pub fn set_periodic(period: Option<Duration>) {
+    let period = match period {
+        Some(period) => period,
+        None => {
+            without_interrupts(|| {
+                local_register(APICRegister::TimerInitialCount).store(0, Ordering::SeqCst);
+            });
+            return;
+        }
+    };
+
+    let ticks = u128::from(frequency()) * period.as_nanos() / 1_000_000_000;
+    let count = u32::try_from(ticks).unwrap_or(u32::MAX).max(1);
+
+    without_interrupts(|| {
+        local_register(APICRegister::TimerDivideConfiguration)
+            .store(DIVIDE_BY_16, Ordering::SeqCst);
+        local_register(APICRegister::TimerLocalVectorTable)
+            .store(LVT_PERIODIC | u32::from(TIMER_VECTOR), Ordering::SeqCst);
+        // writing the initial count starts the timer
+        local_register(APICRegister::TimerInitialCount).store(count, Ordering::SeqCst);
+    });
+}
+
+/// Measure the frequency of the timer against the monotonic clock, the first time it's needed
+fn frequency() -> u64 {
+    let frequency = FREQUENCY.load(Ordering::Relaxed);
+    if frequency != 0 {
+        return frequency;
+    }
+
+    let counted = without_interrupts(|| {
+        local_register(APICRegister::TimerDivideConfiguration)
+            .store(DIVIDE_BY_16, Ordering::SeqCst);
+        local_register(APICRegister::TimerLocalVectorTable)
+            .store(LVT_MASKED | u32::from(TIMER_VECTOR), Ordering::SeqCst);
+
+        let start = time::monotonic();
+        local_register(APICRegister::TimerInitialCount).store(u32::MAX, Ordering::SeqCst);
+        while time::monotonic() < start + CALIBRATION {
+            core::hint::spin_loop();
+        }
+        let remaining = local_register(APICRegister::TimerCurrentCount).load(Ordering::SeqCst);
+        local_register(APICRegister::TimerInitialCount).store(0, Ordering::SeqCst);
+        u32::MAX - remaining
+    });
+
+    let frequency = u64::try_from(u128::from(counted) * 1_000_000_000 / CALIBRATION.as_nanos())
+        .unwrap_or(u64::MAX)
+        .max(1);
+    FREQUENCY.store(frequency, Ordering::Relaxed);
+    kinfo!("apic: timer at {} kHz", frequency / 1000);
+    frequency
+}
+
+isr! {
+    fn timer_interrupt(_frame: &InterruptStackFrame) {
+        super::end_of_interrupt();
+    }
+}
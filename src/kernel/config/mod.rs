@@ -20,8 +20,17 @@ export![
     VMALLOC_END = VMALLOC_BASE + 32 * _1TB;
     // 512GB guard hole
     PAGE_MAP_BASE = VMALLOC_END + _512GB;
-    PAGE_MAP_END = PAGE_MAP_BASE + _1TB;
-    // 512GB guard hole
-    KERNEL_START = PAGE_MAP_END + _512GB;
+    // 8 slots for KASLR, only one is used
+    PAGE_MAP_END = PAGE_MAP_BASE + _1TB * 4;
+    // the kernel image is linked in the last 2GB, as required by the kernel code model, the
+    // boot stub maps the first GB of physical memory there
+    KERNEL_START = TOP - 2 * _1GB + 1;
     KERNEL_END = KERNEL_START + _1GB / 2;
 ];
+
+/* Memory usage */
+export![
+    usize,
+    // the physical memory past that is left to the frame allocator
+    KERNEL_HEAP_MAX_SIZE = _1GB / 4;
+];
@@ -0,0 +1,89 @@
+use crate::kernel::apic;
+
+use lib::*;
+
+use core::convert::TryFrom;
+use core::sync::atomic::*;
+
+/// Maximum number of CPUs the kernel can manage
+pub const MAX_CPUS: usize = 64;
+
+const MSR_GS_BASE: usize = 0xc000_0101;
+
+/// Data only accessed by its own CPU, the GS base of every CPU points to its `CpuLocal`
+#[repr(C)]
+struct CpuLocal {
+    id: AtomicUsize,
+    apic_id: AtomicU8,
+}
+
+impl CpuLocal {
+    const fn new() -> CpuLocal {
+        CpuLocal {
+            id: AtomicUsize::new(0),
+            apic_id: AtomicU8::new(0),
+        }
+    }
+}
+
+static CPUS: [CpuLocal; MAX_CPUS] = [const { CpuLocal::new() }; MAX_CPUS];
+static CPU_COUNT: AtomicUsize = AtomicUsize::new(0);
+static ONLINE_CPUS: AtomicU64 = AtomicU64::new(0);
+
+/// Give a logical id to the current CPU & mark it online
+///
+/// # Safety
+/// Must be called once per CPU, before anything on that CPU calls `current()`.
+pub unsafe fn init_current() {
+    let id = CPU_COUNT.fetch_add(1, Ordering::SeqCst);
+    assert!(
+        id < MAX_CPUS,
+        "too many CPUs, only {} are supported",
+        MAX_CPUS
+    );
+
+    let local = &CPUS[id];
+    local.id.store(id, Ordering::Relaxed);
+    local
+        .apic_id
+        .store(apic::current_apic_id(), Ordering::Relaxed);
+
+    let addr = local as *const CpuLocal as usize;
+    writemsr!(
+        MSR_GS_BASE,
+        [
+            u32::try_from(addr >> 32).unwrap(),
+            u32::try_from(addr & 0xffff_ffff).unwrap()
+        ]
+    );
+
+    ONLINE_CPUS.fetch_or(1 << id, Ordering::SeqCst);
+}
+
+/// Logical id of the current CPU
+pub fn current() -> usize {
+    if CPU_COUNT.load(Ordering::Relaxed) == 0 {
+        return 0;
+    }
+
+    let id: usize;
+    unsafe {
+        core::arch::asm!("mov {}, gs:[0]", out(reg) id, options(nostack, readonly, preserves_flags));
+    }
+    id
+}
+
+/// Local APIC id of the CPU `cpu`
+pub fn apic_id(cpu: usize) -> u8 {
+    CPUS[cpu].apic_id.load(Ordering::Relaxed)
+}
+
+/// Iterate over the logical ids of the online CPUs
+pub fn online() -> impl Iterator<Item = usize> {
+    let mask = ONLINE_CPUS.load(Ordering::Acquire);
+    (0..MAX_CPUS).filter(move |cpu| mask & (1 << cpu) != 0)
+}
+
+pub fn count() -> usize {
+    usize::try_from(ONLINE_CPUS.load(Ordering::Acquire).count_ones()).unwrap()
+}
@@ -0,0 +1,15 @@
+pub mod bottom_half;
+pub mod workqueue;
+
+pub use bottom_half::schedule_bottom_half;
+pub use workqueue::queue_work;
+
+use ::alloc::boxed::Box;
+
+/// A piece of work whose execution has been deferred
+pub type Work = Box<dyn FnOnce() + Send + 'static>;
+
+/// Start running bottom halves when leaving interrupt handlers
+pub fn init() {
+    lib::asm::interrupts::set_interrupt_exit_hook(bottom_half::run_pending);
+}
@@ -0,0 +1,50 @@
+use super::Work;
+use crate::kernel::cpu::{self, MAX_CPUS};
+
+use ::alloc::boxed::Box;
+use ::alloc::vec::Vec;
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+use core::sync::atomic::*;
+
+/// Bottom halves waiting to run on each CPU, must only be locked with interrupts disabled
+static PENDING: [StaticSpinlock<Vec<Work>>; MAX_CPUS] =
+    [const { StaticSpinlock::new(Vec::new()) }; MAX_CPUS];
+static RUNNING: [AtomicBool; MAX_CPUS] = [const { AtomicBool::new(false) }; MAX_CPUS];
+
+/// Defer `f` until the interrupt handler currently running on this CPU returns.
+///
+/// Bottom halves run on the CPU that scheduled them, with interrupts enabled, before returning to
+/// the interrupted code. They must not block.
+pub fn schedule_bottom_half(f: impl FnOnce() + Send + 'static) {
+    let work: Work = Box::new(f);
+    without_interrupts(|| PENDING[cpu::current()].lock().push(work));
+}
+
+/// Run the bottom halves pending on the current CPU, called when leaving an interrupt handler
+/// with interrupts disabled.
+pub fn run_pending() {
+    let cpu = cpu::current();
+
+    // an interrupt raised while bottom halves are running will find the flag set, the pending
+    // work it scheduled is picked up by the loop below
+    if RUNNING[cpu].swap(true, Ordering::Acquire) {
+        return;
+    }
+
+    loop {
+        let pending = core::mem::take(&mut *PENDING[cpu].lock());
+        if pending.is_empty() {
+            break;
+        }
+
+        unsafe { enable_interrupts!() };
+        for work in pending {
+            work();
+        }
+        unsafe { disable_interrupts!() };
+    }
+
+    RUNNING[cpu].store(false, Ordering::Release);
+}
@@ -0,0 +1,28 @@
+use super::Work;
+
+use ::alloc::boxed::Box;
+use ::alloc::collections::VecDeque;
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+/// Work waiting for a worker, must only be locked with interrupts disabled
+static QUEUE: StaticSpinlock<VecDeque<Work>> = StaticSpinlock::new(VecDeque::new());
+
+/// Queue `f` to be run by a worker, outside of any interrupt context.
+///
+/// There is no scheduler yet, the idle loop of every CPU acts as a worker (cf. `run_pending`).
+pub fn queue_work(f: impl FnOnce() + Send + 'static) {
+    let work: Work = Box::new(f);
+    without_interrupts(|| QUEUE.lock().push_back(work));
+}
+
+pub fn has_pending() -> bool {
+    without_interrupts(|| !QUEUE.lock().is_empty())
+}
+
+/// Run queued work until the queue is empty, in the order it was queued
+pub fn run_pending() {
+    while let Some(work) = without_interrupts(|| QUEUE.lock().pop_front()) {
+        work();
+    }
+}
@@ -0,0 +1,133 @@
+//! Putting the CPU to sleep until the next interrupt, with `hlt`, `mwait` or an ACPI C-state.
+
+use crate::kernel::apic::timer;
+
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+use core::sync::atomic::*;
+
+const CPUID_MONITOR_BIT: u32 = 1 << 3;
+const CPUID_MWAIT_LEAF: u32 = 0x5;
+/// `mwait` can be woken up by interrupts even if they are disabled
+const CPUID_MWAIT_INTERRUPT_BREAK: u32 = 1 << 1;
+/// Extension telling `mwait` to be woken up by masked interrupts
+const MWAIT_INTERRUPT_BREAK: u32 = 1 << 0;
+/// Bits of an `mwait` hint selecting the C-state, minus 1
+const MWAIT_CSTATE_MASK: u32 = 0xf0;
+
+/// Must only be locked with interrupts disabled
+static STATE: StaticSpinlock<IdleState> = StaticSpinlock::new(IdleState::Halt);
+
+/// The states deeper than C1 aren't used, the local APIC timer has to keep running
+static C1_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Cache line `monitor` watches, nothing writes to it: only interrupts wake the CPU up
+static MONITORED: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IdleState {
+    /// C1 with `hlt`
+    Halt,
+    /// `mwait` with a hint selecting the C-state
+    Mwait { hint: u32 },
+    /// Reading the I/O port of an ACPI C-state
+    Io { port: u16 },
+}
+
+impl IdleState {
+    /// Whether the state is C1, the deepest one the local APIC timer always runs in
+    pub fn is_c1(&self) -> bool {
+        match *self {
+            IdleState::Halt => true,
+            IdleState::Mwait { hint } => hint & MWAIT_CSTATE_MASK == 0,
+            IdleState::Io { .. } => false,
+        }
+    }
+}
+
+/// Pick the default idle state, `mwait` C1 if it's supported. ACPI can set a deeper one later
+/// with `set_state`, unless the local APIC timer stops in them.
+pub fn init() {
+    set_state(c1_state());
+    if !timer::always_running() {
+        C1_ONLY.store(true, Ordering::Relaxed);
+        kinfo!("idle: the local APIC timer stops below C1, staying in C1");
+    }
+
+    kinfo!("idle: using {:?}", state());
+}
+
+/// Stop using the states deeper than C1, for the users of the local APIC timer
+pub fn limit_to_c1() {
+    C1_ONLY.store(true, Ordering::Relaxed);
+    if !state().is_c1() {
+        set_state(c1_state());
+        kinfo!("idle: back to {:?} for the local APIC timer", state());
+    }
+}
+
+/// Whether `set_state` may use `state`
+pub fn allowed(state: IdleState) -> bool {
+    state.is_c1() || !C1_ONLY.load(Ordering::Relaxed)
+}
+
+fn c1_state() -> IdleState {
+    if supports_mwait() {
+        IdleState::Mwait { hint: 0 }
+    } else {
+        IdleState::Halt
+    }
+}
+
+/// Whether `mwait` exists & can be woken up by masked interrupts
+pub fn supports_mwait() -> bool {
+    cpuid!(0x1)[2] & CPUID_MONITOR_BIT != 0
+        && cpuid!(0x0)[0] >= CPUID_MWAIT_LEAF
+        && cpuid!(CPUID_MWAIT_LEAF)[2] & CPUID_MWAIT_INTERRUPT_BREAK != 0
+}
+
+pub fn state() -> IdleState {
+    without_interrupts(|| *STATE.lock())
+}
+
+/// Use `state` from now on, states `allowed` refuses are replaced by C1
+pub fn set_state(state: IdleState) {
+    let state = if allowed(state) { state } else { c1_state() };
+    without_interrupts(|| *STATE.lock() = state);
+}
+
+/// Sleep until the next interrupt, which is handled before returning
+///
+/// # Safety
+/// Interrupts must be disabled, they are enabled when this returns. Checking for work to do before
+/// calling this doesn't race with interrupts queuing some.
+pub unsafe fn idle() {
+    let state = *STATE.lock();
+    match state {
+        IdleState::Halt => {
+            // sti only takes effect after hlt starts, an interrupt will wake us up
+            core::arch::asm!("sti", "hlt");
+            return;
+        }
+        IdleState::Mwait { hint } => {
+            core::arch::asm!(
+                "monitor",
+                in("rax") MONITORED.as_ptr(),
+                in("ecx") 0,
+                in("edx") 0,
+            );
+            core::arch::asm!(
+                "mwait",
+                in("eax") hint,
+                in("ecx") MWAIT_INTERRUPT_BREAK,
+            );
+        }
+        IdleState::Io { port } => {
+            // the chipset stops the CPU until the next interrupt, whether they are masked or not
+            let _ = io_read_port!(u8, port);
+        }
+    }
+
+    enable_interrupts!();
+}
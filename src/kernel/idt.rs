@@ -1,6 +1,40 @@
 use crate::kernel::table::idt::*;
+use lib::*;
+
+/// First vector used by the ISA IRQs once they are routed through the I/O APIC
+pub const ISA_IRQ_BASE: u8 = 32;
+/// Vector of the ACPI system control interrupt
+pub const ACPI_SCI_VECTOR: u8 = 0x30;
+/// Vector of the local APIC timer
+pub const TIMER_VECTOR: u8 = 0xfc;
+/// Vector of the IPI asking a CPU to run its pending remote function calls
+pub const CALL_FUNCTION_VECTOR: u8 = 0xfd;
+/// Vector the local APIC uses for spurious interrupts
+pub const SPURIOUS_VECTOR: u8 = 0xff;
 
 pub unsafe fn setup_idt() {
     let new_idt = IDT::new();
     IDT::set_for_this_cpu(new_idt);
+    set_handler(SPURIOUS_VECTOR, spurious_interrupt);
+}
+
+/// Install `handler` for `vector` in the IDT of the current CPU
+///
+/// # Safety
+/// `vector` shouldn't be in use by anything else, and must be above 31.
+pub unsafe fn set_handler(vector: u8, handler: Handler) {
+    without_interrupts(|| {
+        IDT::current()[usize::from(vector)].set(
+            handler,
+            GateType::INTERRUPT,
+            DPL::PRIVILEGE0,
+            0x20,
+        );
+    });
+}
+
+isr! {
+    fn spurious_interrupt(_frame: &InterruptStackFrame) {
+        // spurious interrupts must not be acknowledged
+    }
 }
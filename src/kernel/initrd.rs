@@ -0,0 +1,234 @@
+//! Initial ramdisk: cpio (`newc`) or tar archives loaded as multiboot modules, unpacked into an
+//! in-memory filesystem.
+//!
+//! Modules whose command line is `initrd` are used, like `module2 /boot/initrd.cpio initrd` in
+//! GRUB. Files aren't copied, they point into the module which is never freed. Later archives
+//! replace the files of earlier ones.
+
+use crate::boot::multiboot;
+use crate::kernel::mem::addr::PhyAddr;
+use crate::kernel::mem::vbuffer::VBuffer;
+use crate::kernel::mem::Flags;
+
+use ::alloc::collections::BTreeMap;
+use ::alloc::string::String;
+use ::alloc::vec::Vec;
+use lib::initrd::{Entry, EntryKind};
+use lib::sync::StaticSpinlock;
+
+pub use lib::initrd::ArchiveError;
+
+const MODULE_CMDLINE: &str = "initrd";
+
+/// Symbolic links followed before giving up on a path
+const MAX_SYMLINK_DEPTH: usize = 8;
+
+kernel_param! {
+    /// Path of the first program to run, in the initial ramdisk
+    static INIT_PATH: &'static str = "/init", "init";
+}
+
+static FILES: StaticSpinlock<BTreeMap<String, Node>> = StaticSpinlock::new(BTreeMap::new());
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    File(&'static [u8]),
+    Directory,
+    /// Target of the link, relative to the directory holding it or absolute
+    Symlink(&'static str),
+}
+
+/// Unpack the archives given as modules, the heap has to be set up
+pub fn init() {
+    let boot_info = multiboot::get_boot_info();
+    let mut files = FILES.lock();
+    files.insert(String::from("/"), Node::Directory);
+
+    for module in boot_info.modules() {
+        if module.cmdline.trim() != MODULE_CMDLINE || module.is_empty() {
+            continue;
+        }
+
+        let start = PhyAddr::new(module.start);
+        let buffer = match unsafe { VBuffer::with_flags(start, module.len(), Flags::NO_EXECUTE) } {
+            Ok(buffer) => buffer,
+            Err(_) => {
+                kwarn!("initrd: can't map the module at {:?}", start);
+                continue;
+            }
+        };
+
+        // files point into the archive, it stays mapped
+        let (address, _) = VBuffer::leak(buffer);
+        let archive: &'static [u8] = unsafe { core::slice::from_raw_parts(address, module.len()) };
+
+        let mut count = 0;
+        let result = lib::initrd::parse(archive, |entry| {
+            add_entry(&mut files, entry);
+            count += 1;
+        });
+
+        match result {
+            Ok(()) => kinfo!("initrd: {} entries unpacked", count),
+            Err(error) => kwarn!(
+                "initrd: archive at {:?} is invalid after {} entries, {:?}",
+                start,
+                count,
+                error
+            ),
+        }
+    }
+
+    let init_path = INIT_PATH.get();
+    drop(files);
+    if read(init_path).is_none() {
+        kwarn!("initrd: no init program at {}", init_path);
+    }
+}
+
+/// Path of the program to run first, set with `init=` on the command line
+pub fn init_path() -> &'static str {
+    INIT_PATH.get()
+}
+
+/// Node at `path`, without following the last symbolic link
+pub fn lookup(path: &str) -> Option<Node> {
+    let files = FILES.lock();
+    resolve(&files, &normalize(path), 0).map(|(_, node)| node)
+}
+
+/// Content of the file at `path`, following symbolic links
+pub fn read(path: &str) -> Option<&'static [u8]> {
+    let files = FILES.lock();
+    let (mut path, mut node) = resolve(&files, &normalize(path), 0)?;
+    for _ in 0..MAX_SYMLINK_DEPTH {
+        match node {
+            Node::File(data) => return Some(data),
+            Node::Directory => return None,
+            Node::Symlink(target) => {
+                let target = join(parent(&path), target);
+                let resolved = resolve(&files, &target, 0)?;
+                path = resolved.0;
+                node = resolved.1;
+            }
+        }
+    }
+
+    None
+}
+
+/// Names of the entries of the directory at `path`
+pub fn list(path: &str) -> Option<Vec<String>> {
+    let files = FILES.lock();
+    let (directory, node) = resolve(&files, &normalize(path), 0)?;
+    if node != Node::Directory {
+        return None;
+    }
+
+    let prefix = if directory == "/" {
+        directory
+    } else {
+        directory + "/"
+    };
+
+    Some(
+        files
+            .range(prefix.clone()..)
+            .take_while(|(path, _)| path.starts_with(&prefix))
+            .map(|(path, _)| &path[prefix.len()..])
+            .filter(|name| !name.is_empty() && !name.contains('/'))
+            .map(String::from)
+            .collect(),
+    )
+}
+
+fn add_entry(files: &mut BTreeMap<String, Node>, entry: Entry<'_, 'static>) {
+    let path = normalize(entry.path);
+    if path == "/" {
+        return;
+    }
+
+    // archives don't always have entries for the parent directories
+    let mut parent_end = 0;
+    while let Some(next) = path[parent_end + 1..].find('/') {
+        parent_end += next + 1;
+        files
+            .entry(String::from(&path[..parent_end]))
+            .or_insert(Node::Directory);
+    }
+
+    let node = match entry.kind {
+        EntryKind::File => Node::File(entry.data),
+        EntryKind::Directory => Node::Directory,
+        EntryKind::Symlink => match core::str::from_utf8(entry.data) {
+            Ok(target) => Node::Symlink(target),
+            Err(_) => return,
+        },
+    };
+    files.insert(path, node);
+}
+
+/// Find the node at the normalized `path`, following the symbolic links to directories in it.
+/// Returns the path without symbolic links.
+fn resolve(files: &BTreeMap<String, Node>, path: &str, depth: usize) -> Option<(String, Node)> {
+    if depth > MAX_SYMLINK_DEPTH {
+        return None;
+    }
+
+    let mut resolved = String::from("/");
+    let mut components = path.split('/').filter(|c| !c.is_empty()).peekable();
+    while let Some(component) = components.next() {
+        let candidate = join(&resolved, component);
+        let node = *files.get(&candidate)?;
+        resolved = match node {
+            Node::Symlink(target) if components.peek().is_some() => {
+                resolve(files, &join(&resolved, target), depth + 1)?.0
+            }
+            _ => candidate,
+        };
+    }
+
+    let node = *files.get(&resolved)?;
+    Some((resolved, node))
+}
+
+/// Absolute path without `.`, `..`, repeated or trailing slashes
+fn normalize(path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => (),
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+
+    let mut normalized = String::new();
+    for component in components {
+        normalized.push('/');
+        normalized.push_str(component);
+    }
+
+    if normalized.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
+
+fn parent(path: &str) -> &str {
+    match path.rfind('/') {
+        Some(0) | None => "/",
+        Some(index) => &path[..index],
+    }
+}
+
+/// `path` relative to `directory`, or `path` itself if it's absolute
+fn join(directory: &str, path: &str) -> String {
+    if path.starts_with('/') {
+        normalize(path)
+    } else {
+        normalize(&::alloc::format!("{}/{}", directory, path))
+    }
+}
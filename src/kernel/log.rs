@@ -0,0 +1,158 @@
+//! Leveled kernel logging. `kerr!`, `kwarn!`, `kinfo!` & `kdebug!` put records in a lock-free
+//! ring, with the monotonic time & the CPU they come from, which the consoles drain.
+//!
+//! The level is set globally with `loglevel=debug` & per module with
+//! `log=drivers::acpi=debug,kernel::mem=warn`, paths are relative to the crate & the longest
+//! matching one wins.
+//!
+//! Panics & exceptions don't go through it, they are printed synchronously with `early_kprintln!`
+//! followed by a dump of the ring.
+
+#[macro_export]
+macro_rules! klog {
+    ($level:expr, $($arg:tt)+) => (
+        $crate::kernel::log::log($level, module_path!(), format_args!( $($arg)+ ))
+    )
+}
+
+#[macro_export]
+macro_rules! kerr {
+    ($($arg:tt)+) => ( $crate::klog!($crate::kernel::log::Level::Error, $($arg)+) )
+}
+
+#[macro_export]
+macro_rules! kwarn {
+    ($($arg:tt)+) => ( $crate::klog!($crate::kernel::log::Level::Warn, $($arg)+) )
+}
+
+#[macro_export]
+macro_rules! kinfo {
+    ($($arg:tt)+) => ( $crate::klog!($crate::kernel::log::Level::Info, $($arg)+) )
+}
+
+#[macro_export]
+macro_rules! kdebug {
+    ($($arg:tt)+) => ( $crate::klog!($crate::kernel::log::Level::Debug, $($arg)+) )
+}
+
+// declared after the macros, which they use
+mod console;
+mod ring;
+
+pub use console::{flush, register_console, replace_console, Console, EARLY_SERIAL_CONSOLE};
+pub use ring::{Record, RING_SIZE};
+
+use crate::boot::params::{parse_choice, ParamError, ParamValue};
+use crate::kernel::{cpu, time};
+use ring::{Read, Ring};
+
+use core::fmt;
+
+kernel_param! {
+    /// Records less severe than this are dropped, unless `log` gives a level to their module
+    static LOG_LEVEL: Level = Level::Info, "loglevel";
+}
+
+kernel_param! {
+    /// Comma separated `path=level` directives, a directive without a path sets the default level
+    static LOG_FILTER: &'static str = "", "log";
+}
+
+static RING: Ring = Ring::new();
+
+/// Severity of a record, from the most to the least severe
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    const CHOICES: [(&'static str, Level); 4] = [
+        ("error", Level::Error),
+        ("warn", Level::Warn),
+        ("info", Level::Info),
+        ("debug", Level::Debug),
+    ];
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // pad with the width given to the record
+        f.pad(match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+        })
+    }
+}
+
+impl ParamValue for Level {
+    fn parse(value: Option<&'static str>) -> Result<Level, ParamError> {
+        parse_choice(value, &Level::CHOICES)
+    }
+}
+
+/// Add a record, use the macros rather than calling it
+pub fn log(level: Level, module: &'static str, args: fmt::Arguments) {
+    if !enabled(level, module) {
+        return;
+    }
+
+    RING.push(level, cpu::current(), time::monotonic(), args);
+    console::flush();
+}
+
+/// The record would be kept, `module` is given by `module_path!()`
+pub fn enabled(level: Level, module: &str) -> bool {
+    // the paths of the filter don't have the crate name
+    let module = module.split_once("::").map_or("", |(_, path)| path);
+
+    let mut max_level = LOG_LEVEL.get();
+    let mut matched = None;
+    for directive in LOG_FILTER.get().split(',').map(str::trim) {
+        let (path, directive_level) = match directive.split_once('=') {
+            Some((path, directive_level)) => (path, directive_level),
+            None => ("", directive),
+        };
+
+        let directive_level = match Level::parse(Some(directive_level)) {
+            Ok(directive_level) => directive_level,
+            Err(_) => continue,
+        };
+
+        let matches = path.is_empty()
+            || module == path
+            || module
+                .strip_prefix(path)
+                .map_or(false, |rest| rest.starts_with("::"));
+        if matches && matched.map_or(true, |length| path.len() >= length) {
+            matched = Some(path.len());
+            max_level = directive_level;
+        }
+    }
+
+    level <= max_level
+}
+
+/// Records still in the ring, from the oldest
+pub fn records() -> impl Iterator<Item = Record> {
+    (RING.tail()..RING.head()).filter_map(|sequence| match RING.read(sequence) {
+        Read::Record(record) => Some(record),
+        Read::NotReady | Read::Lost => None,
+    })
+}
+
+/// Print the records still in the ring on the serial port, after a panic
+pub fn print_dmesg() {
+    use crate::boot::early_kprintln::write_to_serial;
+
+    write_to_serial(format_args!("----- [DMESG START HERE] -----\n"));
+    for record in records() {
+        write_to_serial(format_args!("{}\n", record));
+    }
+    write_to_serial(format_args!("----- [DMESG  END  HERE] -----\n"));
+}
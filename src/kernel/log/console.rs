@@ -0,0 +1,146 @@
+//! Consoles the records are written to, each one drains the ring from its own position so a
+//! console registered late still gets the records that weren't overwritten.
+
+use super::ring::Read;
+use super::RING;
+use crate::boot::early_kprintln::write_to_serial;
+use crate::drivers::vga_buffer;
+
+use core::fmt;
+use core::sync::atomic::*;
+use lib::sync::StaticSpinlock;
+
+const MAX_CONSOLES: usize = 4;
+
+static CONSOLES: StaticSpinlock<Consoles> = StaticSpinlock::new(Consoles {
+    consoles: [
+        Some((&SerialConsole, 0)),
+        Some((&VgaConsole, 0)),
+        None,
+        None,
+    ],
+});
+
+/// Records were added since the consoles were last drained
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+pub trait Console: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Write a record, a line without its line feed
+    fn write_record(&self, args: fmt::Arguments);
+}
+
+/// Name of the console writing on COM1 until the serial driver takes over
+pub const EARLY_SERIAL_CONSOLE: &str = "serial";
+
+/// The COM1 port, written synchronously
+struct SerialConsole;
+
+impl Console for SerialConsole {
+    fn name(&self) -> &'static str {
+        EARLY_SERIAL_CONSOLE
+    }
+
+    fn write_record(&self, args: fmt::Arguments) {
+        write_to_serial(format_args!("{}\n", args));
+    }
+}
+
+/// The VGA text buffer
+struct VgaConsole;
+
+impl Console for VgaConsole {
+    fn name(&self) -> &'static str {
+        "vga"
+    }
+
+    fn write_record(&self, args: fmt::Arguments) {
+        vga_buffer::_print(format_args!("{}\n", args));
+    }
+}
+
+struct Consoles {
+    /// Consoles & the number of the next record they write
+    consoles: [Option<(&'static dyn Console, u64)>; MAX_CONSOLES],
+}
+
+impl Consoles {
+    fn drain(&mut self) {
+        for (console, next) in self.consoles.iter_mut().flatten() {
+            while *next < RING.head() {
+                match RING.read(*next) {
+                    Read::Record(record) => console.write_record(format_args!("{}", record)),
+                    Read::NotReady => break,
+                    Read::Lost => {
+                        let tail = RING.tail().max(*next + 1);
+                        console.write_record(format_args!("log: {} records lost", tail - *next));
+                        *next = tail;
+                        continue;
+                    }
+                }
+                *next += 1;
+            }
+        }
+    }
+}
+
+/// Add a console, it starts with the oldest record of the ring
+pub fn register_console(console: &'static dyn Console) {
+    let mut consoles = CONSOLES.lock();
+    match consoles
+        .consoles
+        .iter_mut()
+        .find(|console| console.is_none())
+    {
+        Some(free) => *free = Some((console, RING.tail())),
+        None => {
+            drop(consoles);
+            kwarn!(
+                "log: too many consoles, {} isn't registered",
+                console.name()
+            );
+            return;
+        }
+    }
+
+    drop(consoles);
+    flush();
+}
+
+/// Put `console` in place of the console named `name`, it carries on from the same record so
+/// nothing is written twice. It's only added when there's no such console.
+pub fn replace_console(name: &str, console: &'static dyn Console) {
+    let mut consoles = CONSOLES.lock();
+    match consoles
+        .consoles
+        .iter_mut()
+        .flatten()
+        .find(|(existing, _)| existing.name() == name)
+    {
+        Some(existing) => existing.0 = console,
+        None => {
+            drop(consoles);
+            register_console(console);
+            return;
+        }
+    }
+
+    drop(consoles);
+    flush();
+}
+
+/// Write the new records on the consoles. It never waits: when another CPU or an interrupted
+/// context is already draining them, that one writes the new records before it stops.
+pub fn flush() {
+    PENDING.store(true, Ordering::Release);
+    while PENDING.load(Ordering::Acquire) {
+        let mut consoles = match CONSOLES.try_lock() {
+            Some(consoles) => consoles,
+            None => return,
+        };
+
+        PENDING.store(false, Ordering::Release);
+        consoles.drain();
+    }
+}
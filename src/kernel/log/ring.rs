@@ -0,0 +1,161 @@
+//! Lock-free ring of the last log records. Writers reserve a record number with an atomic
+//! increment, the slot of a record holds its number once it is written so readers can tell a
+//! record being written or overwritten from a complete one, like a seqlock.
+
+use super::Level;
+
+use core::cell::UnsafeCell;
+use core::fmt::{self, Write};
+use core::sync::atomic::*;
+use core::time::Duration;
+
+/// Records kept, older ones are overwritten
+pub const RING_SIZE: usize = 512;
+
+/// Longer messages are truncated
+const MESSAGE_SIZE: usize = 160;
+
+/// Sequence of a slot while a record is written to it
+const WRITING: u64 = u64::MAX;
+
+#[derive(Copy, Clone)]
+pub struct Record {
+    pub level: Level,
+    pub cpu: usize,
+    /// Monotonic time of the record, zero before the clock is calibrated
+    pub timestamp: Duration,
+    length: usize,
+    message: [u8; MESSAGE_SIZE],
+}
+
+impl Record {
+    pub fn message(&self) -> &str {
+        core::str::from_utf8(&self.message[..self.length]).unwrap_or("<invalid record>")
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "[{:5}.{:06}] CPU{:<2} {:<5} {}",
+            self.timestamp.as_secs(),
+            self.timestamp.subsec_micros(),
+            self.cpu,
+            self.level,
+            self.message()
+        )
+    }
+}
+
+/// Format into the fixed-size message of a record, truncated on a character boundary
+impl Write for Record {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let available = MESSAGE_SIZE - self.length;
+        let mut length = s.len().min(available);
+        while !s.is_char_boundary(length) {
+            length -= 1;
+        }
+
+        self.message[self.length..self.length + length].copy_from_slice(&s.as_bytes()[..length]);
+        self.length += length;
+        Ok(())
+    }
+}
+
+pub enum Read {
+    Record(Record),
+    /// The record is still being written, or isn't reserved yet
+    NotReady,
+    /// The record was overwritten by a newer one
+    Lost,
+}
+
+struct Slot {
+    /// Number of the record in the slot plus one, 0 when the slot was never written
+    sequence: AtomicU64,
+    record: UnsafeCell<Record>,
+}
+
+impl Slot {
+    const fn new() -> Slot {
+        Slot {
+            sequence: AtomicU64::new(0),
+            record: UnsafeCell::new(Record {
+                level: Level::Info,
+                cpu: 0,
+                timestamp: Duration::ZERO,
+                length: 0,
+                message: [0; MESSAGE_SIZE],
+            }),
+        }
+    }
+}
+
+pub struct Ring {
+    /// Number of the next record
+    head: AtomicU64,
+    slots: [Slot; RING_SIZE],
+}
+
+unsafe impl Sync for Ring {}
+
+impl Ring {
+    pub const fn new() -> Ring {
+        Ring {
+            head: AtomicU64::new(0),
+            slots: [const { Slot::new() }; RING_SIZE],
+        }
+    }
+
+    /// Number of the next record, the ones before were at least reserved
+    pub fn head(&self) -> u64 {
+        self.head.load(Ordering::Acquire)
+    }
+
+    /// Number of the oldest record that can still be read
+    pub fn tail(&self) -> u64 {
+        self.head().saturating_sub(RING_SIZE as u64)
+    }
+
+    pub fn push(&self, level: Level, cpu: usize, timestamp: Duration, args: fmt::Arguments) {
+        // format before reserving the slot, the arguments may log too
+        let mut record = Record {
+            level,
+            cpu,
+            timestamp,
+            length: 0,
+            message: [0; MESSAGE_SIZE],
+        };
+        let _ = record.write_fmt(args);
+
+        let sequence = self.head.fetch_add(1, Ordering::AcqRel);
+        let slot = &self.slots[sequence as usize % RING_SIZE];
+
+        slot.sequence.store(WRITING, Ordering::Relaxed);
+        fence(Ordering::Release);
+        unsafe { core::ptr::write_volatile(slot.record.get(), record) };
+        slot.sequence.store(sequence + 1, Ordering::Release);
+    }
+
+    pub fn read(&self, sequence: u64) -> Read {
+        let slot = &self.slots[sequence as usize % RING_SIZE];
+
+        let before = slot.sequence.load(Ordering::Acquire);
+        if before != sequence + 1 {
+            return if before != WRITING && before > sequence + 1 || sequence < self.tail() {
+                Read::Lost
+            } else {
+                Read::NotReady
+            };
+        }
+
+        let record = unsafe { core::ptr::read_volatile(slot.record.get()) };
+        fence(Ordering::Acquire);
+        if slot.sequence.load(Ordering::Relaxed) != before {
+            return Read::Lost;
+        }
+
+        Read::Record(record)
+    }
+}
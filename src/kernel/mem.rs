@@ -1,6 +1,12 @@
 pub mod addr;
 pub mod alloc;
+mod commands;
+pub mod frame;
+pub mod numa;
 pub mod paging;
+pub mod pcid;
+pub mod protect;
+pub mod tlb;
 mod valloc;
 pub mod vbox;
 pub mod vbuffer;
@@ -11,13 +17,17 @@ pub use paging::Flags;
 pub use vbox::*;
 pub use vbuffer::*;
 
+use crate::boot::kaslr;
 use crate::boot::multiboot;
 use crate::drivers::vga_buffer::*;
+use crate::kernel::config::{USERSPACE_BASE, USERSPACE_END};
+use crate::kernel::table::paging::{PageTable, PageTableType};
 use paging::*;
 
 use core::ptr::NonNull;
 
 pub unsafe fn setup_memory() {
+    kaslr::init();
     paging::init();
     alloc::init();
     valloc::init();
@@ -27,5 +37,17 @@ pub unsafe fn setup_memory() {
     VGA_BUFFER
         .lock()
         .set_buffer_addr(NonNull::new(vga_buffer_addr).unwrap());
-    unmap2m(VirtAddr::from(0)).expect("failed to unmap, aborting");
+    drop_identity_mapping();
+    protect::init();
+    commands::register();
+}
+
+/// Unmap the first GB of memory mapped by the boot stub, the kernel runs from its higher half
+/// mapping & the user half of the address space is left empty
+unsafe fn drop_identity_mapping() {
+    // every entry of the root table covers 512GB
+    for addr in (USERSPACE_BASE..USERSPACE_END).step_by(512 << 30) {
+        PageTable::get_entry(PageTableType::PML4T, VirtAddr::from(addr)).set_value(0);
+    }
+    purge_tlb();
 }
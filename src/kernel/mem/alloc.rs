@@ -1,3 +1,4 @@
+use crate::boot::kaslr;
 use crate::boot::multiboot::{memmap::*, *};
 use crate::kernel::config::*;
 use crate::kernel::kernel_range;
@@ -10,6 +11,7 @@ use core::convert::TryFrom;
 use core::ops::Range;
 use core::ptr::NonNull;
 use lib::sync::*;
+use lib::without_interrupts;
 
 #[global_allocator]
 pub static LALLOC: LambixAllocator = LambixAllocator::new();
@@ -24,17 +26,22 @@ pub struct LambixAllocator {
 
 unsafe impl GlobalAlloc for LambixAllocator {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        if let Some(ref mut allocator) = *self.inner.lock() {
-            allocator.alloc(layout)
-        } else {
-            core::ptr::null_mut()
-        }
+        // interrupt handlers can allocate too, they must not find the lock taken on their own CPU
+        without_interrupts(|| {
+            if let Some(ref mut allocator) = *self.inner.lock() {
+                allocator.alloc(layout)
+            } else {
+                core::ptr::null_mut()
+            }
+        })
     }
 
     unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        if let Some(ref mut allocator) = *self.inner.lock() {
-            allocator.dealloc(ptr, layout);
-        }
+        without_interrupts(|| {
+            if let Some(ref mut allocator) = *self.inner.lock() {
+                allocator.dealloc(ptr, layout);
+            }
+        })
     }
 }
 
@@ -53,9 +60,11 @@ impl LambixAllocator {
                 )
             };
 
-            let mut available_memory = available_memory_iter(&boot_info)
-                .skip(1)
-                .inspect(|d| early_kprintln!("alloc: {:?}", d));
+            let mut available_memory =
+                available_memory_iter(&boot_info, boot_info_range(&boot_info))
+                    .skip(1)
+                    .take(heap_page_count())
+                    .inspect(|d| kdebug!("alloc: {:?}", d));
             unsafe {
                 self.create_empty_allocator();
                 self.add_first_page(&mut available_memory);
@@ -78,6 +87,20 @@ impl LambixAllocator {
         self.inc_mem_pool(PAGE_SIZE);
     }
 
+    /// Bytes allocated & bytes mapped for the heap, freed memory isn't reused
+    pub fn usage(&self) -> (usize, usize) {
+        without_interrupts(|| match *self.inner.lock() {
+            Some(ref allocator) => {
+                let start = usize::from(allocator.memory.start);
+                (
+                    usize::from(allocator.cursor) - start,
+                    usize::from(allocator.memory.end) - start,
+                )
+            }
+            None => (0, 0),
+        })
+    }
+
     #[inline]
     pub fn page_size(&self) -> usize {
         PageTable::PAGE_SIZE
@@ -85,13 +108,13 @@ impl LambixAllocator {
 
     fn create_empty_allocator(&self) {
         let mut lock = self.inner.lock();
-        let base_addr = VirtAddr::from(PHYSICAL_MEMORY_MAPPING_BASE);
+        let base_addr = VirtAddr::from(kaslr::heap_base());
         *lock = Some(InnerAllocator::new(base_addr..base_addr));
     }
 
     unsafe fn add_first_page(&self, available_memory: &mut impl Iterator<Item = PhyAddr>) {
         let flags = Flags::NO_EXECUTE | Flags::READ_WRITE | Flags::PRESENT;
-        let base_addr = VirtAddr::from(PHYSICAL_MEMORY_MAPPING_BASE);
+        let base_addr = VirtAddr::from(kaslr::heap_base());
 
         let error_message = "not enough memory to bootstrap the kernel memory unit";
 
@@ -155,21 +178,44 @@ unsafe fn zero_page_table(page: PhyAddr) {
     core::ptr::write_bytes(page.as_mut_ptr::<u8>(), 0, PageTable::PAGE_SIZE);
 }
 
-fn available_memory_iter<'a>(boot_info: &'a BootInfo) -> impl Iterator<Item = PhyAddr> + 'a {
-    // here, we still have the 1st GB of memory identity-mapped
+/// Call `f` with every available page the heap didn't take, in the order `init` went through them
+pub(super) fn pages_left_by_heap(f: impl FnMut(PhyAddr)) {
+    let boot_info = get_boot_info();
+    let original_start = get_info_header_addr();
+    let original_range = original_start..original_start.wrapping_add(boot_info.len());
+
+    // init skips the 1st page
+    available_memory_iter(&boot_info, original_range)
+        .skip(1 + heap_page_count())
+        .for_each(f);
+}
+
+fn heap_page_count() -> usize {
+    KERNEL_HEAP_MAX_SIZE / PAGE_SIZE
+}
+
+/// Physical memory of the boot information given by the bootloader
+fn boot_info_range(boot_info: &BootInfo) -> Range<PhyAddr> {
+    let range = boot_info.range();
+    PhyAddr::new(usize::from(range.start))..PhyAddr::new(usize::from(range.end))
+}
+
+fn available_memory_iter<'a>(
+    boot_info: &'a BootInfo,
+    boot_info_range: Range<PhyAddr>,
+) -> impl Iterator<Item = PhyAddr> + 'a {
     let tags = boot_info.tags();
-    let boot_info_range = boot_info.range();
 
     tags.filter_map(|tag| tag.as_memmap())
-        .map(|memmap| memmap.entries().iter())
-        .flatten()
+        .flat_map(|memmap| memmap.entries())
         .filter(|mem| mem.mem_type == MemoryType::AvailableRAM)
-        .inspect(|data| early_kprintln!("{:?}", data))
+        .inspect(|data| kdebug!("{:?}", data))
         .flat_map(|mem| {
+            let base_addr = PhyAddr::new(mem.base_addr);
             let mem_section_size = usize::try_from(mem.length).unwrap();
-            let end_addr = mem.base_addr.wrapping_add(mem_section_size) & !PageTable::PAGE_MASK;
+            let end_addr = base_addr.wrapping_add(mem_section_size) & !PageTable::PAGE_MASK;
             let start_addr = {
-                let addr = mem.base_addr.align::<PageTable>();
+                let addr = base_addr.align::<PageTable>();
                 if addr < end_addr {
                     Some(addr)
                 } else {
@@ -190,12 +236,27 @@ fn available_memory_iter<'a>(boot_info: &'a BootInfo) -> impl Iterator<Item = Ph
             let page = *p..p.wrapping_add(PageTable::PAGE_SIZE);
             let kernel_range = kernel_range();
 
-            (page.start >= PhyAddr::new(usize::from(boot_info_range.end))
-                || (page.end <= PhyAddr::new(usize::from(boot_info_range.start))))
+            (page.start >= boot_info_range.end || page.end <= boot_info_range.start)
                 && ((page.start >= kernel_range.end) || (page.end <= kernel_range.start))
+                && boot_info.modules().all(|module| {
+                    page.start >= PhyAddr::new(module.end) || page.end <= PhyAddr::new(module.start)
+                })
+                && symbol_table_ranges(boot_info)
+                    .all(|range| page.start >= range.end || page.end <= range.start)
         })
 }
 
+/// Physical memory of the symbol table the bootloader loads after the kernel, for the backtraces
+fn symbol_table_ranges(boot_info: &BootInfo) -> impl Iterator<Item = Range<PhyAddr>> {
+    boot_info
+        .get_tag(TagType::ELFSymbols)
+        .and_then(|tag| tag.as_elf_symbols())
+        .and_then(|symbols| symbols.symbol_table())
+        .into_iter()
+        .flat_map(|(symbols, names)| [symbols.range(), names.range()])
+        .map(|range| PhyAddr::new(range.start)..PhyAddr::new(range.end))
+}
+
 #[alloc_error_handler]
 fn alloc_error_handler(layout: core::alloc::Layout) -> ! {
     panic!("OOM: failed to allocate {:?}", layout)
@@ -0,0 +1,234 @@
+//! Shell commands to look at the memory & the page tables
+
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::alloc::LALLOC;
+use crate::kernel::mem::frame::{self, FRAME_SIZE};
+use crate::kernel::mem::paging::{get_physical_address, is_mapped};
+use crate::kernel::mem::pcid;
+use crate::kernel::shell::{self, parse_number, Command, CommandError};
+use crate::kernel::table::paging::{Flags, PageTable, PageTableType};
+
+use core::fmt::Write;
+use lib::without_interrupts;
+
+const DUMP_LINE: usize = 16;
+const DUMP_DEFAULT_LENGTH: usize = 64;
+const DUMP_MAX_LENGTH: usize = 4096;
+const PCID_DEFAULT_ROUNDS: usize = 1000;
+/// The measure runs with interrupts disabled
+const PCID_MAX_ROUNDS: usize = 100_000;
+
+/// Bits of a page table entry holding the physical address
+const ADDRESS_MASK: usize = ((1 << 52) - 1) & !PageTable::PAGE_MASK;
+
+pub(super) fn register() {
+    shell::register(&Dump);
+    shell::register(&Walk);
+    shell::register(&MemInfo);
+    shell::register(&PcidBench);
+}
+
+struct Dump;
+
+impl Command for Dump {
+    fn name(&self) -> &'static str {
+        "dump"
+    }
+
+    fn usage(&self) -> &'static str {
+        "<address> [length]"
+    }
+
+    fn description(&self) -> &'static str {
+        "print memory in hexadecimal & ASCII, unmapped bytes are ??"
+    }
+
+    fn run(&self, args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        let (address, length) = match args {
+            [address] => (parse_number(address)?, DUMP_DEFAULT_LENGTH),
+            [address, length] => (parse_number(address)?, parse_number(length)?),
+            _ => return Err(CommandError::Usage),
+        };
+        let end = address.saturating_add(length.min(DUMP_MAX_LENGTH));
+
+        for line in (address..end).step_by(DUMP_LINE) {
+            let mut bytes = [None; DUMP_LINE];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                if line + i < end {
+                    *byte = read_byte(line + i);
+                }
+            }
+
+            write!(out, "{:016x}: ", line)?;
+            for (i, byte) in bytes.iter().enumerate() {
+                match byte {
+                    Some(byte) => write!(out, "{:02x} ", byte)?,
+                    None if line + i < end => out.write_str("?? ")?,
+                    None => out.write_str("   ")?,
+                }
+            }
+
+            out.write_str(" |")?;
+            for byte in bytes.iter().take(end - line) {
+                let c = match byte {
+                    Some(byte @ b' '..=b'~') => char::from(*byte),
+                    _ => '.',
+                };
+                out.write_char(c)?;
+            }
+            writeln!(out, "|")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_byte(address: usize) -> Option<u8> {
+    unsafe {
+        if is_mapped(VirtAddr::from(address)) {
+            Some(core::ptr::read_volatile(address as *const u8))
+        } else {
+            None
+        }
+    }
+}
+
+struct Walk;
+
+impl Command for Walk {
+    fn name(&self) -> &'static str {
+        "walk"
+    }
+
+    fn usage(&self) -> &'static str {
+        "<address>"
+    }
+
+    fn description(&self) -> &'static str {
+        "print the page table entries translating a virtual address"
+    }
+
+    fn run(&self, args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        let address = match args {
+            [address] => parse_number(address)?,
+            _ => return Err(CommandError::Usage),
+        };
+
+        // the upper bits have to be copies of bit 47
+        let upper = address >> 47;
+        if upper != 0 && upper != (1 << 17) - 1 {
+            writeln!(out, "{:#x} isn't canonical", address)?;
+            return Err(CommandError::Failed);
+        }
+
+        let vaddr = VirtAddr::from(address);
+        let levels = [
+            (PageTableType::PML4T, "PML4", 1 << 39),
+            (PageTableType::PDPT, "PDPT", 1 << 30),
+            (PageTableType::PDT, "PD", 1 << 21),
+            (PageTableType::PT, "PT", PageTable::PAGE_SIZE),
+        ];
+
+        for &(table, name, size) in levels.iter() {
+            let index = PageTable::get_index(table, vaddr);
+            // the tables below are only reachable through present entries
+            let value =
+                without_interrupts(|| unsafe { PageTable::get_entry(table, vaddr).get_value() });
+            let flags = Flags::from_bits_truncate(value);
+            writeln!(out, "{:<4} [{:3}] {:016x} {:?}", name, index, value, flags)?;
+
+            if !flags.contains(Flags::PRESENT) {
+                writeln!(out, "{:#x} isn't mapped", address)?;
+                return Ok(());
+            }
+
+            let large = matches!(table, PageTableType::PDPT | PageTableType::PDT)
+                && flags.contains(Flags::PAGE_SIZE);
+            if large {
+                let physical = (value & ADDRESS_MASK & !(size - 1)) | (address & (size - 1));
+                writeln!(
+                    out,
+                    "{:#x} -> {:#x}, in a {} KiB page",
+                    address,
+                    physical,
+                    size >> 10
+                )?;
+                return Ok(());
+            }
+        }
+
+        match without_interrupts(|| unsafe { get_physical_address(vaddr) }) {
+            Ok(physical) => writeln!(out, "{:#x} -> {:#x}", address, usize::from(physical))?,
+            Err(error) => writeln!(out, "{:#x} can't be translated, {:?}", address, error)?,
+        }
+        Ok(())
+    }
+}
+
+struct MemInfo;
+
+impl Command for MemInfo {
+    fn name(&self) -> &'static str {
+        "meminfo"
+    }
+
+    fn description(&self) -> &'static str {
+        "print the usage of the heap & of the physical memory"
+    }
+
+    fn run(&self, _args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        let (used, size) = LALLOC.usage();
+        writeln!(out, "heap: {} KiB used of {} KiB", used >> 10, size >> 10)?;
+
+        for (node, stats) in frame::node_stats().iter().enumerate() {
+            writeln!(
+                out,
+                "node {}: {} MiB free of {} MiB",
+                node,
+                (stats.free_frames * FRAME_SIZE) >> 20,
+                (stats.total_frames * FRAME_SIZE) >> 20
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct PcidBench;
+
+impl Command for PcidBench {
+    fn name(&self) -> &'static str {
+        "pcid"
+    }
+
+    fn usage(&self) -> &'static str {
+        "[rounds]"
+    }
+
+    fn description(&self) -> &'static str {
+        "time address space switches with & without PCIDs"
+    }
+
+    fn run(&self, args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        let rounds = match args {
+            [] => PCID_DEFAULT_ROUNDS,
+            [rounds] => parse_number(rounds)?.min(PCID_MAX_ROUNDS),
+            _ => return Err(CommandError::Usage),
+        };
+
+        let cost = match pcid::measure_switch_cost(rounds) {
+            Some(cost) => cost,
+            None => {
+                writeln!(out, "can't measure {} rounds", rounds)?;
+                return Err(CommandError::Failed);
+            }
+        };
+
+        writeln!(out, "cycles per round trip, {} rounds:", rounds)?;
+        match cost.with_pcid {
+            Some(cycles) => writeln!(out, "  with PCIDs    {}", cycles)?,
+            None => writeln!(out, "  with PCIDs    not enabled")?,
+        }
+        writeln!(out, "  without PCIDs {}", cost.without_pcid)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+//! Allocator of physical frames, for the memory the kernel heap didn't take.
+//!
+//! Free frames are kept per NUMA node, allocations prefer the node of the current CPU & fall back
+//! to the other nodes by increasing distance.
+
+use crate::kernel::cpu;
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::{alloc, numa};
+
+use ::alloc::vec::Vec;
+use core::ops::Range;
+use lib::sync::StaticSpinlock;
+use lib::without_interrupts;
+
+pub const FRAME_SIZE: usize = 4096;
+
+kernel_param! {
+    /// Frames at or past this physical address aren't used, 0 means no limit. The heap is set up
+    /// before the command line is read, it isn't affected.
+    static MEMORY_LIMIT: usize = 0, "mem";
+}
+
+/// Must only be locked with interrupts disabled
+static NODES: StaticSpinlock<Vec<Node>> = StaticSpinlock::new(Vec::new());
+
+struct Node {
+    free: Vec<Range<PhyAddr>>,
+    total_frames: usize,
+    free_frames: usize,
+}
+
+/// Memory usage of a NUMA node, in frames
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct NodeStats {
+    pub total_frames: usize,
+    pub free_frames: usize,
+}
+
+/// Take the memory left by the heap, the NUMA topology has to be initialized first
+pub fn init() {
+    let mut nodes = (0..numa::node_count())
+        .map(|_| Node {
+            free: Vec::new(),
+            total_frames: 0,
+            free_frames: 0,
+        })
+        .collect::<Vec<_>>();
+
+    let memory_map = numa::memory_map();
+    let limit = MEMORY_LIMIT.get();
+    alloc::pages_left_by_heap(|frame| {
+        if limit != 0 && usize::from(frame) >= limit {
+            return;
+        }
+
+        let node = memory_map
+            .iter()
+            .find(|(range, _)| range.contains(&frame))
+            .map_or(0, |&(_, node)| node);
+
+        let node = &mut nodes[node];
+        match node.free.last_mut() {
+            Some(range) if range.end == frame => range.end = frame.wrapping_add(FRAME_SIZE),
+            _ => node.free.push(frame..frame.wrapping_add(FRAME_SIZE)),
+        }
+        node.total_frames += 1;
+        node.free_frames += 1;
+    });
+
+    for (id, node) in nodes.iter().enumerate() {
+        kinfo!(
+            "frame: node {}, {} MiB available",
+            id,
+            (node.free_frames * FRAME_SIZE) >> 20
+        );
+    }
+
+    without_interrupts(|| *NODES.lock() = nodes);
+}
+
+/// Allocate a frame, preferably on the node of the current CPU
+pub fn alloc_frame() -> Option<PhyAddr> {
+    alloc_frame_on(numa::numa_node_of(cpu::current()))
+}
+
+/// Allocate a frame on `node`, or on the closest node that has some left
+pub fn alloc_frame_on(node: usize) -> Option<PhyAddr> {
+    let candidates = numa::nodes_by_distance(node);
+    without_interrupts(|| {
+        let mut nodes = NODES.lock();
+        candidates.into_iter().find_map(|candidate| {
+            let node = nodes.get_mut(candidate)?;
+            let range = node.free.last_mut()?;
+
+            range.end = range.end.wrapping_sub(FRAME_SIZE);
+            let frame = range.end;
+            if range.start == range.end {
+                node.free.pop();
+            }
+
+            node.free_frames -= 1;
+            Some(frame)
+        })
+    })
+}
+
+/// Give back a frame returned by `alloc_frame`
+pub fn free_frame(frame: PhyAddr) {
+    let node = numa::node_of(frame);
+    without_interrupts(|| {
+        let mut nodes = NODES.lock();
+        let node = nodes
+            .get_mut(node)
+            .expect("freeing a frame that doesn't belong to any node");
+
+        match node.free.last_mut() {
+            Some(range) if range.end == frame => range.end = frame.wrapping_add(FRAME_SIZE),
+            _ => node.free.push(frame..frame.wrapping_add(FRAME_SIZE)),
+        }
+        node.free_frames += 1;
+    });
+}
+
+/// Frames managed by each node, indexed by node
+pub fn node_stats() -> Vec<NodeStats> {
+    without_interrupts(|| {
+        NODES
+            .lock()
+            .iter()
+            .map(|node| NodeStats {
+                total_frames: node.total_frames,
+                free_frames: node.free_frames,
+            })
+            .collect()
+    })
+}
@@ -0,0 +1,219 @@
+//! NUMA topology, read from the SRAT & SLIT.
+//!
+//! Nodes are numbered from 0 in the order their proximity domain first appears in the SRAT.
+//! Without a SRAT, everything belongs to node 0.
+
+use crate::boot::multiboot::{self, memmap::MemoryType};
+use crate::drivers::acpi::tables::{self, srat::SratEntry};
+use crate::kernel::cpu;
+use crate::kernel::mem::addr::*;
+
+use ::alloc::vec::Vec;
+use core::ops::Range;
+use lib::sync::StaticSpinlock;
+
+/// Distance of a node to itself, other distances are relative to it
+pub const LOCAL_DISTANCE: u8 = 10;
+/// Distance between nodes when there's no SLIT
+pub const REMOTE_DISTANCE: u8 = 20;
+
+static TOPOLOGY: StaticSpinlock<Option<Topology>> = StaticSpinlock::new(None);
+
+struct Topology {
+    /// Proximity domain of each node
+    domains: Vec<u32>,
+    /// Physical ranges & the node they belong to
+    memory: Vec<(Range<PhyAddr>, usize)>,
+    /// Local APIC id of the processors & their node
+    processors: Vec<(u32, usize)>,
+    /// `distances[from * node_count + to]`
+    distances: Vec<u8>,
+}
+
+impl Topology {
+    fn node_of_domain(&mut self, domain: u32) -> usize {
+        match self.domains.iter().position(|&d| d == domain) {
+            Some(node) => node,
+            None => {
+                self.domains.push(domain);
+                self.domains.len() - 1
+            }
+        }
+    }
+
+    fn node_count(&self) -> usize {
+        self.domains.len().max(1)
+    }
+}
+
+/// Read the topology from the ACPI tables, they have to be initialized first
+pub fn init() {
+    let mut topology = Topology {
+        domains: Vec::new(),
+        memory: Vec::new(),
+        processors: Vec::new(),
+        distances: Vec::new(),
+    };
+
+    if let Some(srat) = tables::srat() {
+        for entry in srat.entries() {
+            match entry {
+                SratEntry::ProcessorAffinity {
+                    proximity_domain,
+                    apic_id,
+                    ..
+                } => {
+                    let node = topology.node_of_domain(proximity_domain);
+                    topology.processors.push((u32::from(apic_id), node));
+                }
+                SratEntry::X2ApicAffinity {
+                    proximity_domain,
+                    x2apic_id,
+                    ..
+                } => {
+                    let node = topology.node_of_domain(proximity_domain);
+                    topology.processors.push((x2apic_id, node));
+                }
+                SratEntry::MemoryAffinity {
+                    proximity_domain,
+                    base_address,
+                    length,
+                    ..
+                } => {
+                    let node = topology.node_of_domain(proximity_domain);
+                    let start = PhyAddr::new(usize::try_from(base_address).unwrap());
+                    let end = start.wrapping_add(usize::try_from(length).unwrap());
+                    topology.memory.push((start..end, node));
+                }
+                SratEntry::Unknown { .. } => (),
+            }
+        }
+    }
+
+    let node_count = topology.node_count();
+    let slit = tables::slit();
+    for from in 0..node_count {
+        for to in 0..node_count {
+            let slit_distance = slit.as_ref().and_then(|slit| {
+                let from = usize::try_from(*topology.domains.get(from)?).ok()?;
+                let to = usize::try_from(*topology.domains.get(to)?).ok()?;
+                slit.distance(from, to)
+            });
+
+            topology.distances.push(match slit_distance {
+                Some(distance) => distance,
+                None if from == to => LOCAL_DISTANCE,
+                None => REMOTE_DISTANCE,
+            });
+        }
+    }
+
+    kinfo!(
+        "numa: {} nodes, distances from SLIT: {}",
+        node_count,
+        slit.is_some()
+    );
+    *TOPOLOGY.lock() = Some(topology);
+
+    for (range, node) in memory_map() {
+        kinfo!("numa: {:?}-{:?} on node {}", range.start, range.end, node);
+    }
+}
+
+pub fn node_count() -> usize {
+    TOPOLOGY.lock().as_ref().map_or(1, Topology::node_count)
+}
+
+/// Node of the CPU with the logical id `cpu`
+pub fn numa_node_of(cpu: usize) -> usize {
+    let apic_id = u32::from(cpu::apic_id(cpu));
+    TOPOLOGY
+        .lock()
+        .as_ref()
+        .and_then(|topology| {
+            topology
+                .processors
+                .iter()
+                .find(|&&(id, _)| id == apic_id)
+                .map(|&(_, node)| node)
+        })
+        .unwrap_or(0)
+}
+
+/// Node of the physical address `address`, memory the SRAT doesn't describe belongs to node 0
+pub fn node_of(address: PhyAddr) -> usize {
+    TOPOLOGY
+        .lock()
+        .as_ref()
+        .and_then(|topology| {
+            topology
+                .memory
+                .iter()
+                .find(|(range, _)| range.contains(&address))
+                .map(|&(_, node)| node)
+        })
+        .unwrap_or(0)
+}
+
+/// Relative distance between two nodes, `LOCAL_DISTANCE` for a node to itself
+pub fn distance(from: usize, to: usize) -> u8 {
+    let lock = TOPOLOGY.lock();
+    let topology = match lock.as_ref() {
+        Some(topology) if from < topology.node_count() && to < topology.node_count() => topology,
+        _ if from == to => return LOCAL_DISTANCE,
+        _ => return REMOTE_DISTANCE,
+    };
+
+    topology.distances[from * topology.node_count() + to]
+}
+
+/// Every node, starting with `from` & ordered by their distance to it
+pub fn nodes_by_distance(from: usize) -> Vec<usize> {
+    let mut nodes = (0..node_count()).collect::<Vec<_>>();
+    nodes.sort_by_key(|&to| (to != from, distance(from, to)));
+    nodes
+}
+
+/// Available RAM ranges from the multiboot memory map, split so each one belongs to one node
+pub fn memory_map() -> Vec<(Range<PhyAddr>, usize)> {
+    let boot_info = multiboot::get_boot_info();
+    let lock = TOPOLOGY.lock();
+    let affinity = lock
+        .as_ref()
+        .map_or(&[][..], |topology| &topology.memory[..]);
+
+    let mut ranges = Vec::new();
+    let available = boot_info
+        .tags()
+        .filter_map(|tag| tag.as_memmap())
+        .flat_map(|memmap| memmap.entries())
+        .filter(|entry| entry.mem_type == MemoryType::AvailableRAM);
+
+    for entry in available {
+        let start = PhyAddr::new(entry.base_addr);
+        let end = start.wrapping_add(usize::try_from(entry.length).unwrap());
+
+        // cut the entry at the boundaries of the SRAT ranges overlapping it
+        let mut cursor = start;
+        while cursor < end {
+            let (node, next) = match affinity.iter().find(|(range, _)| range.contains(&cursor)) {
+                Some((range, node)) => (*node, range.end.min(end)),
+                None => {
+                    let next = affinity
+                        .iter()
+                        .map(|(range, _)| range.start)
+                        .filter(|&range_start| range_start > cursor)
+                        .min()
+                        .unwrap_or(end)
+                        .min(end);
+                    (0, next)
+                }
+            };
+
+            ranges.push((cursor..next, node));
+            cursor = next;
+        }
+    }
+
+    ranges
+}
@@ -1,5 +1,6 @@
-use crate::kernel::config::*;
+use crate::boot::kaslr;
 use crate::kernel::mem::addr::*;
+use crate::kernel::mem::tlb::TlbBatch;
 pub use crate::kernel::table::paging::Flags;
 use crate::kernel::table::paging::*;
 use ::alloc::boxed::Box;
@@ -36,8 +37,8 @@ pub unsafe fn get_physical_address(vaddr: VirtAddr) -> Result<PhyAddr> {
 /// currently mapped to that virtual address.
 ///
 /// # Safety
-/// You have to disable scheduling & interrupts when using this function as it's going
-/// to change the address space for the current CPU only.
+/// You have to disable scheduling & interrupts when using this function as page tables aren't
+/// locked. No TLB invalidation is done since only missing translations are added.
 ///
 /// You have to ensure that the physical address is 4k-aligned, and doesn't have it's higher
 /// significant bit (the 64th one) set as it's used for NX pages.
@@ -65,21 +66,39 @@ pub unsafe fn map4k(vaddr: VirtAddr, paddr: PhyAddr, flags: Flags) -> Result<()>
     }
 }
 
+/// Unmap a 4k page & invalidate it in the TLB of every online CPU
 pub unsafe fn unmap4k(vaddr: VirtAddr) -> Result<()> {
+    let mut batch = TlbBatch::new();
+    let result = unmap4k_batched(vaddr, &mut batch);
+    batch.flush();
+    result
+}
+
+/// Unmap a 4k page, its invalidation is left to `batch`
+pub unsafe fn unmap4k_batched(vaddr: VirtAddr, batch: &mut TlbBatch) -> Result<()> {
     // TODO we have to free all page tables that are empty here if we can
     // this means implementing an algorithm capable of finding the virtual address linked to the
     // physical address of the box
     let pt_entry = get_pt_entry(vaddr)?;
     if pt_entry.is_present() {
         pt_entry.set_value(0);
-        invalidate_page(vaddr);
+        batch.add(vaddr);
         Ok(())
     } else {
         Err(MapErr::NotMapped)
     }
 }
 
+/// Unmap a 2M page & invalidate it in the TLB of every online CPU
 pub unsafe fn unmap2m(vaddr: VirtAddr) -> Result<()> {
+    let mut batch = TlbBatch::new();
+    let result = unmap2m_batched(vaddr, &mut batch);
+    batch.flush();
+    result
+}
+
+/// Unmap a 2M page, its invalidation is left to `batch`
+pub unsafe fn unmap2m_batched(vaddr: VirtAddr, batch: &mut TlbBatch) -> Result<()> {
     let mut result = Err(MapErr::NotMapped);
 
     if PageTable::get_entry(PageTableType::PML4T, vaddr).is_present()
@@ -89,7 +108,7 @@ pub unsafe fn unmap2m(vaddr: VirtAddr) -> Result<()> {
         if pdt_entry.is_present() {
             if (pdt_entry.get_value() & Flags::PAGE_SIZE.bits()) != 0 {
                 pdt_entry.set_value(0);
-                invalidate_page(vaddr);
+                batch.add(vaddr);
                 result = Ok(());
             } else {
                 result = Err(MapErr::Is4KMapped);
@@ -100,11 +119,14 @@ pub unsafe fn unmap2m(vaddr: VirtAddr) -> Result<()> {
     result
 }
 
+/// Invalidate a page in the TLB of the current CPU only, see `tlb` to invalidate it everywhere
 #[inline]
 pub fn invalidate_page(vaddr: VirtAddr) {
     unsafe { core::arch::asm!("invlpg [{}]", in(reg) vaddr.0) };
 }
 
+/// Flush the TLB entries of the current address space on the current CPU, see `pcid::flush_all`
+/// to flush every address space
 #[inline]
 pub fn purge_tlb() {
     unsafe {
@@ -123,6 +145,39 @@ pub unsafe fn get_pt_entry<'a>(vaddr: VirtAddr) -> Result<&'a Entry> {
     }
 }
 
+/// Whether `vaddr` can be accessed without a page fault, large pages included
+///
+/// # Safety
+/// See `get_pt_entry`.
+pub unsafe fn is_mapped(vaddr: VirtAddr) -> bool {
+    // the upper bits have to be copies of bit 47, or the access raises a #GP
+    let upper = usize::from(vaddr) >> 47;
+    if upper != 0 && upper != (1 << 17) - 1 {
+        return false;
+    }
+
+    let levels = [
+        PageTableType::PML4T,
+        PageTableType::PDPT,
+        PageTableType::PDT,
+        PageTableType::PT,
+    ];
+
+    for level in levels.iter() {
+        let entry = Flags::from_bits_truncate(PageTable::get_entry(*level, vaddr).get_value());
+        if !entry.contains(Flags::PRESENT) {
+            return false;
+        }
+
+        let large_page = matches!(level, PageTableType::PDPT | PageTableType::PDT);
+        if large_page && entry.contains(Flags::PAGE_SIZE) {
+            return true;
+        }
+    }
+
+    true
+}
+
 /// Allocate a page table for this entry if none exist
 unsafe fn allocate_if_not_exist(entry: &Entry) {
     if !entry.is_present() {
@@ -139,7 +194,7 @@ unsafe fn setup_paging_table_address_space() {
     // at that point of the boot process, we have memory mapped the 1st GB of the address space
     // so for every in-kernel address, VirtAddr <==> PhyAddr
     let root_table = VirtAddr::from(get_cr3!()).to_ref::<PageTable>();
-    let base_addr = VirtAddr::from(PAGE_MAP_BASE);
+    let base_addr = VirtAddr::from(kaslr::page_map_base());
 
     let index = PageTable::get_index(PageTableType::PML4T, base_addr);
     let flags = Flags::PRESENT | Flags::READ_WRITE | Flags::NO_EXECUTE;
@@ -0,0 +1,352 @@
+use crate::boot::kaslr;
+use crate::kernel::cpu::{self, MAX_CPUS};
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::paging::{get_physical_address, PAGE_SIZE};
+use crate::kernel::power;
+use crate::kernel::table::paging::{PageTable, PageTableType};
+
+use lib::sync::StaticSpinlock;
+use lib::*;
+
+use ::alloc::vec;
+use core::sync::atomic::*;
+
+const CPUID_PCID_BIT: u32 = 1 << 17;
+const CPUID_INVPCID_BIT: u32 = 1 << 10;
+const CR4_PCIDE_BIT: usize = 1 << 17;
+const CR3_PCID_MASK: usize = 0xfff;
+const CR3_NOFLUSH_BIT: usize = 1 << 63;
+
+const INVPCID_ADDRESS: usize = 0;
+const INVPCID_SINGLE_CONTEXT: usize = 1;
+const INVPCID_ALL_CONTEXTS: usize = 2;
+
+/// Bits of a page table entry holding the physical address
+const ENTRY_ADDRESS_MASK: usize = ((1 << 52) - 1) & !(PAGE_SIZE - 1);
+
+/// Heap pages touched after every address space switch by `measure_switch_cost`, heap mappings
+/// aren't global so a flush drops them
+const WORKING_SET_PAGES: usize = 64;
+
+/// Number of PCIDs each CPU cycles through. PCID 0 is the one the kernel address space starts with
+/// & is never reassigned.
+const PCIDS_PER_CPU: usize = 32;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static HAS_INVPCID: AtomicBool = AtomicBool::new(false);
+static NEXT_ASID: AtomicU64 = AtomicU64::new(1);
+
+/// PCIDs in use on each CPU, must only be locked with interrupts disabled
+static TABLES: [StaticSpinlock<PcidTable>; MAX_CPUS] =
+    [const { StaticSpinlock::new(PcidTable::new()) }; MAX_CPUS];
+
+/// Identifier of an address space, never reused. Each CPU maps the address spaces it recently
+/// ran to a PCID, so switching back to them doesn't flush their TLB entries.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Asid(u64);
+
+impl Asid {
+    /// The address space set up at boot
+    pub const KERNEL: Asid = Asid(0);
+
+    pub fn new() -> Asid {
+        Asid(NEXT_ASID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl Default for Asid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct PcidTable {
+    owners: [Option<Asid>; PCIDS_PER_CPU],
+    next: usize,
+}
+
+impl PcidTable {
+    const fn new() -> PcidTable {
+        PcidTable {
+            owners: [None; PCIDS_PER_CPU],
+            next: 1,
+        }
+    }
+
+    fn find(&self, asid: Asid) -> Option<usize> {
+        self.owners.iter().position(|&owner| owner == Some(asid))
+    }
+
+    /// Return the PCID of `asid` & whether it was just assigned, in which case the TLB entries
+    /// left by its previous owner have to be flushed
+    fn get_or_assign(&mut self, asid: Asid) -> (usize, bool) {
+        if let Some(pcid) = self.find(asid) {
+            return (pcid, false);
+        }
+
+        let pcid = self.next;
+        self.next = self.next % (PCIDS_PER_CPU - 1) + 1;
+        self.owners[pcid] = Some(asid);
+        (pcid, true)
+    }
+
+    /// Forget every address space but the one using `keep`, their PCIDs will be flushed when
+    /// reassigned
+    fn forget_all_but(&mut self, keep: usize) {
+        for (pcid, owner) in self.owners.iter_mut().enumerate() {
+            if pcid != keep {
+                *owner = None;
+            }
+        }
+    }
+}
+
+/// Enable PCIDs on the current CPU if it supports them
+///
+/// # Safety
+/// Must be called once per CPU, after `cpu::init_current`, while running the kernel address space.
+pub unsafe fn init() {
+    if cpuid!(0x1)[2] & CPUID_PCID_BIT == 0 {
+        kinfo!("pcid: not supported, every address space switch flushes the TLB");
+        return;
+    }
+
+    HAS_INVPCID.store(
+        cpuid!(0x7, 0)[1] & CPUID_INVPCID_BIT != 0,
+        Ordering::Relaxed,
+    );
+
+    // CR4.PCIDE can only be set while the current PCID is 0
+    without_interrupts(|| {
+        TABLES[cpu::current()].lock().owners[0] = Some(Asid::KERNEL);
+        set_cr3!(get_cr3!() & !CR3_PCID_MASK);
+        set_cr4!(get_cr4!() | CR4_PCIDE_BIT);
+    });
+
+    ENABLED.store(true, Ordering::Release);
+    kinfo!(
+        "pcid: enabled, invpcid {}",
+        if has_invpcid() {
+            "supported"
+        } else {
+            "not supported"
+        }
+    );
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Acquire)
+}
+
+fn has_invpcid() -> bool {
+    HAS_INVPCID.load(Ordering::Relaxed)
+}
+
+/// Switch the current CPU to the address space `asid` whose root page table is at `root`.
+///
+/// If the address space still owns a PCID on this CPU, its TLB entries are kept.
+///
+/// # Safety
+/// `root` must be a valid PML4 that maps the kernel like the current one.
+pub unsafe fn switch_to(asid: Asid, root: PhyAddr) {
+    without_interrupts(|| {
+        if !is_enabled() {
+            set_cr3!(usize::from(root));
+            return;
+        }
+
+        let (pcid, fresh) = TABLES[cpu::current()].lock().get_or_assign(asid);
+        let mut cr3 = usize::from(root) | pcid;
+        if !fresh {
+            cr3 |= CR3_NOFLUSH_BIT;
+        }
+        set_cr3!(cr3);
+    });
+}
+
+/// Free the PCIDs owned by `asid` on every CPU, must be called when an address space is destroyed
+pub fn release(asid: Asid) {
+    assert!(
+        asid != Asid::KERNEL,
+        "the kernel address space can't be released"
+    );
+
+    without_interrupts(|| {
+        for table in TABLES.iter() {
+            let mut table = table.lock();
+            if let Some(pcid) = table.find(asid) {
+                table.owners[pcid] = None;
+            }
+        }
+    });
+}
+
+/// Drop the TLB entries of the address space `asid` on the current CPU
+pub fn flush_address_space(asid: Asid) {
+    without_interrupts(|| {
+        if !is_enabled() {
+            unsafe { set_cr3!(get_cr3!()) };
+            return;
+        }
+
+        let mut table = TABLES[cpu::current()].lock();
+        if let Some(pcid) = table.find(asid) {
+            if pcid == current_pcid() {
+                // reloading CR3 without the no-flush bit drops the entries of the current PCID
+                unsafe { set_cr3!(get_cr3!()) };
+            } else if has_invpcid() {
+                unsafe { invpcid!(INVPCID_SINGLE_CONTEXT, pcid, 0) };
+            } else {
+                table.owners[pcid] = None;
+            }
+        }
+    });
+}
+
+/// Invalidate a page shared by every address space, like kernel memory, on the current CPU
+pub fn invalidate_page_all_contexts(vaddr: VirtAddr) {
+    without_interrupts(|| {
+        if !is_enabled() {
+            super::paging::invalidate_page(vaddr);
+            return;
+        }
+
+        let mut table = TABLES[cpu::current()].lock();
+        if has_invpcid() {
+            for (pcid, owner) in table.owners.iter().enumerate() {
+                if owner.is_some() {
+                    unsafe { invpcid!(INVPCID_ADDRESS, pcid, usize::from(vaddr)) };
+                }
+            }
+        } else {
+            // invlpg only works on the current PCID, the other ones get flushed when reassigned
+            super::paging::invalidate_page(vaddr);
+            table.forget_all_but(current_pcid());
+        }
+    });
+}
+
+/// Flush the TLB entries of every address space on the current CPU
+pub fn flush_all() {
+    without_interrupts(|| {
+        if is_enabled() && has_invpcid() {
+            unsafe { invpcid!(INVPCID_ALL_CONTEXTS, 0, 0) };
+        } else {
+            if is_enabled() {
+                TABLES[cpu::current()].lock().forget_all_but(current_pcid());
+            }
+            unsafe { set_cr3!(get_cr3!()) };
+        }
+    });
+}
+
+fn current_pcid() -> usize {
+    unsafe { get_cr3!() & CR3_PCID_MASK }
+}
+
+kernel_param! {
+    /// Rounds of `measure_switch_cost` to run at the end of the boot, the results are logged & the
+    /// machine is powered off. 0 doesn't run it.
+    static BENCH_ROUNDS: usize = 0, "pcid_bench";
+}
+
+/// Cycles taken by a round trip between two address spaces, touching a working set after each
+/// switch
+#[derive(Copy, Clone, Debug)]
+pub struct SwitchCost {
+    /// The address spaces keep their TLB entries in their own PCIDs, None when PCIDs aren't enabled
+    pub with_pcid: Option<u64>,
+    /// Every switch flushes the TLB, like without PCIDs
+    pub without_pcid: u64,
+}
+
+/// Switch `rounds` times between the current address space & a copy of it, keeping the TLB
+/// entries when PCIDs are enabled & flushing them on every switch. None when `rounds` is 0.
+///
+/// Nothing may change the kernel mappings meanwhile, the copy wouldn't see it. It runs with
+/// interrupts disabled.
+pub fn measure_switch_cost(rounds: usize) -> Option<SwitchCost> {
+    if rounds == 0 {
+        return None;
+    }
+
+    let working_set = vec![0u8; WORKING_SET_PAGES * PAGE_SIZE];
+    let touch = || {
+        for page in working_set.chunks(PAGE_SIZE) {
+            unsafe { core::ptr::read_volatile(page.as_ptr()) };
+        }
+    };
+
+    let copy = PageTable::new();
+    let asid = Asid::new();
+
+    let cost = without_interrupts(|| unsafe {
+        let root = PhyAddr::from(get_cr3!() & !CR3_PCID_MASK);
+        let copy_root = get_physical_address(VirtAddr::from(&*copy)).ok()?;
+
+        let current = PageTable::get_table(PageTableType::PML4T, VirtAddr::from(0));
+        for i in 0..PageTable::ENTRY_COUNT {
+            copy[i].set_value(current[i].get_value());
+        }
+        // the copy maps its own tables through the recursive entry
+        let recursive =
+            PageTable::get_index(PageTableType::PML4T, VirtAddr::from(kaslr::page_map_base()));
+        let flags = current[recursive].get_value() & !ENTRY_ADDRESS_MASK;
+        copy[recursive].set_value(usize::from(copy_root) | flags);
+
+        touch();
+        let with_pcid = if is_enabled() {
+            let start = rdtsc!();
+            for _ in 0..rounds {
+                switch_to(asid, copy_root);
+                touch();
+                switch_to(Asid::KERNEL, root);
+                touch();
+            }
+            Some((rdtsc!() - start) / rounds as u64)
+        } else {
+            None
+        };
+
+        // both address spaces share the current PCID & every reload flushes it
+        let pcid = current_pcid();
+        let start = rdtsc!();
+        for _ in 0..rounds {
+            set_cr3!(usize::from(copy_root) | pcid);
+            touch();
+            set_cr3!(usize::from(root) | pcid);
+            touch();
+        }
+        let without_pcid = rdtsc!() - start;
+
+        Some(SwitchCost {
+            with_pcid,
+            without_pcid: without_pcid / rounds as u64,
+        })
+    });
+
+    // the PCID the copy used is flushed when reassigned
+    release(asid);
+    cost
+}
+
+/// Run the benchmark asked for with `pcid_bench`, then power off so scripts can boot the kernel
+/// once per CPU configuration
+pub fn run_boot_benchmark() {
+    let rounds = BENCH_ROUNDS.get();
+    let cost = match measure_switch_cost(rounds) {
+        Some(cost) => cost,
+        None => return,
+    };
+
+    match cost.with_pcid {
+        Some(cycles) => kinfo!("pcid: {} cycles per round trip with PCIDs", cycles),
+        None => kinfo!("pcid: not enabled, no round trip with PCIDs"),
+    }
+    kinfo!(
+        "pcid: {} cycles per round trip flushing the TLB, {} rounds",
+        cost.without_pcid,
+        rounds
+    );
+    power::poweroff();
+}
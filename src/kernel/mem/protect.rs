@@ -0,0 +1,157 @@
+//! Per-section protection of the kernel image: code is read-only & executable, read-only data
+//! can't be written nor executed, the rest can't be executed.
+//!
+//! The boot stub maps the first GB of physical memory at the kernel base with writable &
+//! executable 2M pages, `init` replaces the pages holding the kernel with 4k pages & unmaps
+//! everything else.
+
+use super::paging::*;
+use crate::boot::kaslr;
+use crate::kernel::config::KERNEL_START;
+use crate::kernel::mem::addr::*;
+use crate::kernel::table::paging::{PageTable, PageTableType};
+
+use ::alloc::boxed::Box;
+use core::ops::Range;
+use lib::*;
+
+const EFER: u32 = 0xc000_0080;
+const EFER_NXE_BIT: u32 = 1 << 11;
+const CR0_WP_BIT: usize = 1 << 16;
+
+const LARGE_PAGE_SIZE: usize = PAGE_SIZE * PageTable::ENTRY_COUNT;
+/// Memory mapped by the boot stub from `KERNEL_START`, the kernel base is in the first half
+const BOOT_MAPPING_SIZE: usize = LARGE_PAGE_SIZE * PageTable::ENTRY_COUNT;
+
+/// Page fault error code bits
+const FAULT_PRESENT: u64 = 1 << 0;
+const FAULT_WRITE: u64 = 1 << 1;
+const FAULT_INSTRUCTION: u64 = 1 << 4;
+
+extern "C" {
+    static __text_start: u8;
+    static __text_end: u8;
+    static __rodata_start: u8;
+    static __rodata_end: u8;
+    static __data_start: u8;
+    static __data_end: u8;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// `.text`
+    Code,
+    /// `.rodata`, `.kernel_params` & `.eh_frame`
+    ReadOnlyData,
+    /// `.data` & `.bss`
+    Data,
+}
+
+impl Section {
+    const ALL: [Section; 3] = [Section::Code, Section::ReadOnlyData, Section::Data];
+
+    pub fn range(self) -> Range<VirtAddr> {
+        unsafe {
+            match self {
+                Section::Code => VirtAddr::from(&__text_start)..VirtAddr::from(&__text_end),
+                Section::ReadOnlyData => {
+                    VirtAddr::from(&__rodata_start)..VirtAddr::from(&__rodata_end)
+                }
+                Section::Data => VirtAddr::from(&__data_start)..VirtAddr::from(&__data_end),
+            }
+        }
+    }
+
+    pub fn flags(self) -> Flags {
+        match self {
+            Section::Code => Flags::PRESENT | Flags::GLOBAL,
+            Section::ReadOnlyData => Flags::PRESENT | Flags::GLOBAL | Flags::NO_EXECUTE,
+            Section::Data => Flags::PRESENT | Flags::GLOBAL | Flags::READ_WRITE | Flags::NO_EXECUTE,
+        }
+    }
+
+    /// Section of the kernel image holding `addr`
+    pub fn of(addr: VirtAddr) -> Option<Section> {
+        Section::ALL
+            .iter()
+            .copied()
+            .find(|section| section.range().contains(&addr))
+    }
+}
+
+/// Remap the kernel image with the permissions of its sections, the heap has to be set up
+pub unsafe fn init() {
+    enable_protection();
+
+    for large_page in (KERNEL_START..KERNEL_START + BOOT_MAPPING_SIZE).step_by(LARGE_PAGE_SIZE) {
+        let pdt_entry = PageTable::get_entry(PageTableType::PDT, VirtAddr::from(large_page));
+        let pages = (large_page..large_page + LARGE_PAGE_SIZE).step_by(PAGE_SIZE);
+        if !pages
+            .clone()
+            .any(|page| Section::of(VirtAddr::from(page)).is_some())
+        {
+            pdt_entry.set_value(0);
+            continue;
+        }
+
+        // filled before it replaces the large page, the code running here is in one of them
+        let page_table = PageTable::new();
+        for (index, page) in pages.enumerate() {
+            if let Some(section) = Section::of(VirtAddr::from(page)) {
+                page_table[index].set(PhyAddr::new(page - kaslr::kernel_base()), section.flags());
+            }
+        }
+
+        let page_table = VirtAddr::from(Box::into_raw(page_table) as usize);
+        pdt_entry.set(
+            get_physical_address(page_table).expect("the heap is mapped"),
+            PageTable::default_flags(),
+        );
+    }
+
+    // the large pages of the boot stub aren't global, reloading cr3 drops them
+    purge_tlb();
+
+    for section in Section::ALL.iter() {
+        let range = section.range();
+        kdebug!(
+            "kernel {:?}: {:?}..{:?}, {:?}",
+            section,
+            range.start,
+            range.end,
+            section.flags()
+        );
+    }
+}
+
+/// Set EFER.NXE for non-executable pages & CR0.WP so that read-only pages also apply to the
+/// kernel
+unsafe fn enable_protection() {
+    let [edx, eax] = readmsr!(EFER);
+    if eax & EFER_NXE_BIT == 0 {
+        writemsr!(EFER, [edx, eax | EFER_NXE_BIT]);
+    }
+
+    let cr0 = get_cr0!();
+    if cr0 & CR0_WP_BIT == 0 {
+        set_cr0!(cr0 | CR0_WP_BIT);
+    }
+}
+
+/// Explain a page fault on `addr` caused by the protection of the kernel image
+pub fn violation(addr: VirtAddr, error_code: u64) -> Option<&'static str> {
+    if error_code & FAULT_PRESENT == 0 {
+        return None;
+    }
+
+    match Section::of(addr)? {
+        Section::Code if error_code & FAULT_WRITE != 0 => Some("write to kernel code"),
+        Section::ReadOnlyData if error_code & FAULT_WRITE != 0 => {
+            Some("write to read-only kernel data")
+        }
+        Section::ReadOnlyData | Section::Data if error_code & FAULT_INSTRUCTION != 0 => {
+            Some("execution of kernel data")
+        }
+        _ => None,
+    }
+}
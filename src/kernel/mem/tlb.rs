@@ -0,0 +1,86 @@
+use crate::kernel::apic::ipi;
+use crate::kernel::cpu;
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::pcid;
+
+/// Above that many pages, flushing the whole TLB is cheaper than invalidating each page
+const BATCH_SIZE: usize = 32;
+
+/// Pages whose translation has been removed & that have to be invalidated on every CPU.
+///
+/// Invalidation is done once for the whole batch, with a single round of IPIs, when calling
+/// `flush` or when the batch is dropped.
+pub struct TlbBatch {
+    pages: [VirtAddr; BATCH_SIZE],
+    len: usize,
+    full_flush: bool,
+}
+
+impl TlbBatch {
+    pub const fn new() -> TlbBatch {
+        TlbBatch {
+            pages: [VirtAddr::null(); BATCH_SIZE],
+            len: 0,
+            full_flush: false,
+        }
+    }
+
+    pub fn add(&mut self, vaddr: VirtAddr) {
+        if self.len < BATCH_SIZE {
+            self.pages[self.len] = vaddr;
+            self.len += 1;
+        } else {
+            self.full_flush = true;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Invalidate the pages of the batch on every online CPU & empty it
+    pub fn flush(&mut self) {
+        if self.is_empty() {
+            return;
+        }
+
+        let (pages, len, full_flush) = (self.pages, self.len, self.full_flush);
+        invalidate_local(&pages[..len], full_flush);
+        if cpu::count() > 1 {
+            ipi::call_function_others(move || invalidate_local(&pages[..len], full_flush), true);
+        }
+
+        self.len = 0;
+        self.full_flush = false;
+    }
+}
+
+impl Default for TlbBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TlbBatch {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Invalidate a single page on every online CPU
+pub fn shootdown(vaddr: VirtAddr) {
+    let mut batch = TlbBatch::new();
+    batch.add(vaddr);
+    batch.flush();
+}
+
+fn invalidate_local(pages: &[VirtAddr], full_flush: bool) {
+    // unmapped pages are kernel memory, shared by every address space
+    if full_flush {
+        pcid::flush_all();
+    } else {
+        for &page in pages {
+            pcid::invalidate_page_all_contexts(page);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::boot::kaslr;
 use crate::kernel::config::*;
 
 use lib::sync::StaticSpinlock;
@@ -93,7 +94,7 @@ impl Drop for VMem {
 pub unsafe fn init() {
     let mut allocator = VALLOC.lock();
     if allocator.is_none() {
-        let vrange = (VMALLOC_BASE as _)..(VMALLOC_END as _);
+        let vrange = (kaslr::vmalloc_base() as _)..(VMALLOC_END as _);
         *allocator = Some(VAllocator::new(vrange));
     }
 }
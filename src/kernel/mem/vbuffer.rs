@@ -3,6 +3,7 @@ use super::valloc::VMem;
 use crate::kernel::mem::addr::*;
 use crate::kernel::mem::paging::Flags;
 use crate::kernel::mem::paging::*;
+use crate::kernel::mem::tlb::TlbBatch;
 
 use alloc::alloc::Layout;
 
@@ -82,12 +83,22 @@ impl Drop for VBuffer {
         let addr = VirtAddr::from(self.as_ptr::<u8>());
 
         let base_page_addr = addr & low_mask;
-        let last_page_end = addr.align_to(PAGE_SIZE);
+        let last_page_end = addr.wrapping_add(self.size).align_to(PAGE_SIZE);
         let page_count =
-            usize::from(base_page_addr).wrapping_sub(usize::from(last_page_end)) / PAGE_SIZE;
+            usize::from(last_page_end).wrapping_sub(usize::from(base_page_addr)) / PAGE_SIZE;
+
+        let mut batch = TlbBatch::new();
+        for i in 0..page_count {
+            let vaddr = base_page_addr.wrapping_add(i * PAGE_SIZE);
+            unsafe { unmap4k_batched(vaddr, &mut batch).expect("buffer page wasn't mapped") };
+        }
+        batch.flush();
 
         unsafe {
-            core::mem::drop(VMem::from_raw_parts(addr.as_mut_ptr(), page_count));
+            core::mem::drop(VMem::from_raw_parts(
+                base_page_addr.as_mut_ptr(),
+                page_count,
+            ));
         }
     }
 }
@@ -0,0 +1,203 @@
+use crate::drivers::acpi::tables::{self, GenericAddress};
+use crate::drivers::pci;
+use crate::kernel::deferred::queue_work;
+use crate::kernel::mem::addr::*;
+use crate::kernel::mem::vbuffer::VBuffer;
+use crate::kernel::mem::Flags;
+use crate::kernel::shell::{self, Command, CommandError};
+
+use acpica::*;
+use lib::*;
+
+use core::ffi::c_void;
+use core::fmt::Write;
+
+const KEYBOARD_STATUS_PORT: u16 = 0x64;
+const KEYBOARD_COMMAND_PORT: u16 = 0x64;
+const KEYBOARD_INPUT_FULL: u8 = 1 << 1;
+const KEYBOARD_PULSE_RESET: u8 = 0xfe;
+
+/// Writing to this port takes about a microsecond, it's used to wait without relying on timers
+const IO_DELAY_PORT: u16 = 0x80;
+
+const S5_SLEEP_STATE: u8 = 5;
+
+kernel_param! {
+    /// Seconds to wait before rebooting after a panic, 0 means never reboot
+    static PANIC_REBOOT_DELAY: u64 = 0, "panic_reboot";
+}
+
+/// Listen to power button presses, ACPICA has to be enabled
+pub fn init() {
+    shell::register(&Shutdown);
+    shell::register(&Reboot);
+
+    let status = unsafe {
+        AcpiInstallFixedEventHandler(
+            ACPI_EVENT_POWER_BUTTON,
+            Some(power_button_pressed),
+            core::ptr::null_mut(),
+        )
+    };
+
+    if status != 0 {
+        kwarn!("power: can't handle the power button, status {:#x}", status);
+    }
+}
+
+/// Turn the machine off by entering the ACPI S5 sleep state
+pub fn poweroff() -> ! {
+    kinfo!("power: powering off");
+
+    unsafe {
+        let status = AcpiEnterSleepStatePrep(S5_SLEEP_STATE);
+        if status == 0 {
+            disable_interrupts!();
+            let status = AcpiEnterSleepState(S5_SLEEP_STATE);
+            kerr!("power: failed to enter S5, status {:#x}", status);
+        } else {
+            kerr!("power: failed to prepare S5, status {:#x}", status);
+        }
+    }
+
+    halt()
+}
+
+/// Reboot using the FADT reset register, then the keyboard controller, then a triple fault
+pub fn reboot() -> ! {
+    kinfo!("power: rebooting");
+    unsafe { disable_interrupts!() };
+
+    if let Some((register, value)) = tables::fadt().and_then(|fadt| fadt.reset_register()) {
+        write_reset_register(register, value);
+        io_delay(500_000);
+    }
+
+    unsafe {
+        for _ in 0..0x10000 {
+            if io_read_port!(u8, KEYBOARD_STATUS_PORT) & KEYBOARD_INPUT_FULL == 0 {
+                break;
+            }
+            io_delay(1);
+        }
+        io_write_port!(u8, KEYBOARD_COMMAND_PORT, KEYBOARD_PULSE_RESET);
+    }
+    io_delay(500_000);
+
+    triple_fault()
+}
+
+/// Stop the current CPU for good
+pub fn halt() -> ! {
+    loop {
+        unsafe {
+            disable_interrupts!();
+            core::arch::asm!("hlt");
+        }
+    }
+}
+
+/// Reboot `delay_secs` seconds after a panic, 0 to stay halted
+pub fn set_panic_reboot_delay(delay_secs: u64) {
+    PANIC_REBOOT_DELAY.set(delay_secs);
+}
+
+/// Called at the end of the panic handler
+pub fn panic_reboot() -> ! {
+    let delay = PANIC_REBOOT_DELAY.get();
+    if delay == 0 {
+        halt();
+    }
+
+    early_kprintln!("rebooting in {} seconds", delay);
+    io_delay(delay * 1_000_000);
+    reboot()
+}
+
+extern "C" fn power_button_pressed(_context: *mut c_void) -> UINT32 {
+    // called from the SCI handler, ACPICA can't be used from there
+    queue_work(|| poweroff());
+    ACPI_INTERRUPT_HANDLED
+}
+
+fn write_reset_register(register: GenericAddress, value: u8) {
+    match register.address_space {
+        GenericAddress::SYSTEM_IO => unsafe {
+            io_write_port!(u8, register.address as u16, value);
+        },
+        GenericAddress::SYSTEM_MEMORY => unsafe {
+            let address = PhyAddr::new(usize::try_from(register.address).unwrap());
+            let flags = Flags::READ_WRITE | Flags::NO_EXECUTE | Flags::CACHE_DISABLE;
+            if let Ok(buffer) = VBuffer::with_flags(address, 1, flags) {
+                core::ptr::write_volatile(buffer.as_mut_ptr::<u8>(), value);
+            }
+        },
+        GenericAddress::PCI_CONFIGURATION => {
+            // the register is on bus 0, the address encodes the device, function & offset
+            let device = (register.address >> 32) as u8;
+            let function = (register.address >> 16) as u8;
+            let offset = register.address as u8;
+            pci::config_write(
+                pci::Address::new(0, device, function),
+                offset,
+                pci::Width::Byte,
+                u32::from(value),
+            );
+        }
+        _ => (),
+    }
+}
+
+/// Load an empty IDT & raise an exception, the CPU resets on the resulting triple fault
+fn triple_fault() -> ! {
+    #[repr(C, packed)]
+    struct EmptyIdt {
+        limit: u16,
+        base: u64,
+    }
+
+    let idt = EmptyIdt { limit: 0, base: 0 };
+    unsafe {
+        core::arch::asm!("lidt [{}]", "int3", in(reg) &idt);
+    }
+
+    halt()
+}
+
+fn io_delay(micros: u64) {
+    for _ in 0..micros {
+        unsafe { io_write_port!(u8, IO_DELAY_PORT, 0) };
+    }
+}
+
+struct Shutdown;
+
+impl Command for Shutdown {
+    fn name(&self) -> &'static str {
+        "shutdown"
+    }
+
+    fn description(&self) -> &'static str {
+        "power the machine off"
+    }
+
+    fn run(&self, _args: &[&str], _out: &mut dyn Write) -> Result<(), CommandError> {
+        poweroff()
+    }
+}
+
+struct Reboot;
+
+impl Command for Reboot {
+    fn name(&self) -> &'static str {
+        "reboot"
+    }
+
+    fn description(&self) -> &'static str {
+        "reset the machine"
+    }
+
+    fn run(&self, _args: &[&str], _out: &mut dyn Write) -> Result<(), CommandError> {
+        reboot()
+    }
+}
@@ -0,0 +1,219 @@
+//! Debug shell on a character device, the first serial port by default. Subsystems register
+//! their commands with `register` from their init, the main loop feeds the shell the input with
+//! `poll`. Commands run in the main loop, with interrupts enabled.
+
+mod builtins;
+
+use crate::drivers::chardev::{self, CharDevice};
+
+use ::alloc::vec::Vec;
+use core::fmt::{self, Write};
+use lib::sync::StaticSpinlock;
+
+const LINE_SIZE: usize = 256;
+const PROMPT: &str = "lambix> ";
+
+const BACKSPACE: u8 = 0x08;
+const DELETE: u8 = 0x7f;
+
+kernel_param! {
+    /// Character device the debug shell reads its commands from, `none` to disable it
+    static SHELL_DEVICE: &'static str = "ttyS0", "shell";
+}
+
+static COMMANDS: StaticSpinlock<Vec<&'static dyn Command>> = StaticSpinlock::new(Vec::new());
+
+/// Only taken by `poll`, from the main loop
+static SHELL: StaticSpinlock<Option<Shell>> = StaticSpinlock::new(None);
+
+pub trait Command: Sync {
+    fn name(&self) -> &'static str;
+
+    /// Arguments of the command, like `<address> [length]`
+    fn usage(&self) -> &'static str {
+        ""
+    }
+
+    /// What the command does, in a few words
+    fn description(&self) -> &'static str;
+
+    fn run(&self, args: &[&str], out: &mut dyn Write) -> Result<(), CommandError>;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CommandError {
+    /// The arguments don't match the usage of the command, which is printed
+    Usage,
+    /// The command failed & printed why
+    Failed,
+    Write,
+}
+
+impl From<fmt::Error> for CommandError {
+    fn from(_: fmt::Error) -> CommandError {
+        CommandError::Write
+    }
+}
+
+/// Make a command available, commands are looked up by name
+pub fn register(command: &'static dyn Command) {
+    let mut commands = COMMANDS.lock();
+    if commands
+        .iter()
+        .any(|existing| existing.name() == command.name())
+    {
+        drop(commands);
+        kwarn!("shell: {} is already registered", command.name());
+        return;
+    }
+
+    commands.push(command);
+}
+
+/// The registered commands, sorted by name
+pub fn commands() -> Vec<&'static dyn Command> {
+    let mut commands = COMMANDS.lock().clone();
+    commands.sort_unstable_by_key(|command| command.name());
+    commands
+}
+
+/// Decimal or `0x` hexadecimal number argument
+pub fn parse_number(arg: &str) -> Result<usize, CommandError> {
+    match arg.strip_prefix("0x").or_else(|| arg.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => arg.parse::<usize>(),
+    }
+    .map_err(|_| CommandError::Usage)
+}
+
+/// Start the shell on the device given by the `shell` parameter, the device has to be registered
+pub fn init() {
+    builtins::register();
+
+    let name = SHELL_DEVICE.get();
+    if name == "none" {
+        return;
+    }
+
+    let device = match chardev::find(name) {
+        Some(device) => device,
+        None => {
+            kwarn!("shell: no device {}, the shell is disabled", name);
+            return;
+        }
+    };
+
+    kinfo!("shell: listening on {}, type help for the commands", name);
+    let _ = Output(device).write_str(PROMPT);
+    *SHELL.lock() = Some(Shell {
+        device,
+        line: [0; LINE_SIZE],
+        length: 0,
+        last: 0,
+    });
+}
+
+/// Handle the input received since the last call, called from the main loop
+pub fn poll() {
+    // a command polling again finds the shell taken
+    let mut shell = match SHELL.try_lock() {
+        Some(shell) => shell,
+        None => return,
+    };
+
+    let shell = match shell.as_mut() {
+        Some(shell) => shell,
+        None => return,
+    };
+
+    let mut input = [0; 64];
+    loop {
+        let count = shell.device.read(&mut input);
+        if count == 0 {
+            break;
+        }
+
+        for &byte in &input[..count] {
+            shell.handle(byte);
+        }
+    }
+}
+
+struct Shell {
+    device: &'static dyn CharDevice,
+    line: [u8; LINE_SIZE],
+    length: usize,
+    /// Previous byte received, to take CR LF as a single line end
+    last: u8,
+}
+
+impl Shell {
+    fn handle(&mut self, byte: u8) {
+        let mut out = Output(self.device);
+        let last = core::mem::replace(&mut self.last, byte);
+
+        match byte {
+            b'\n' if last == b'\r' => (),
+            b'\r' | b'\n' => {
+                let _ = out.write_str("\n");
+                let line = self.line;
+                let length = core::mem::replace(&mut self.length, 0);
+                // only printable ASCII goes in the line
+                if let Ok(line) = core::str::from_utf8(&line[..length]) {
+                    execute(line, &mut out);
+                }
+                let _ = out.write_str(PROMPT);
+            }
+            BACKSPACE | DELETE => {
+                if self.length > 0 {
+                    self.length -= 1;
+                    let _ = out.write_str("\x08 \x08");
+                }
+            }
+            b' '..=b'~' if self.length < LINE_SIZE => {
+                self.line[self.length] = byte;
+                self.length += 1;
+                self.device.write(&[byte]);
+            }
+            _ => (),
+        }
+    }
+}
+
+fn execute(line: &str, out: &mut Output) {
+    let mut words = line.split_whitespace();
+    let name = match words.next() {
+        Some(name) => name,
+        None => return,
+    };
+    let args = words.collect::<Vec<_>>();
+
+    let command = COMMANDS
+        .lock()
+        .iter()
+        .find(|command| command.name() == name)
+        .copied();
+
+    let _ = match command {
+        Some(command) => match command.run(&args, out) {
+            Err(CommandError::Usage) => writeln!(out, "usage: {} {}", name, command.usage()),
+            Ok(()) | Err(CommandError::Failed) | Err(CommandError::Write) => Ok(()),
+        },
+        None => writeln!(out, "{}: unknown command, try help", name),
+    };
+}
+
+/// Writes to the device, with the line feeds terminals expect
+struct Output(&'static dyn CharDevice);
+
+impl Write for Output {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for (i, line) in s.split('\n').enumerate() {
+            if i > 0 {
+                self.0.write(b"\r\n");
+            }
+            self.0.write(line.as_bytes());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+//! Commands of the shell itself
+
+use super::{commands, register as register_command, Command, CommandError};
+use crate::kernel::mem::frame;
+use crate::kernel::mem::vbuffer::VBuffer;
+use crate::kernel::mem::Flags;
+use crate::kernel::time;
+use crate::kernel::unwind::{Frames, Symbol};
+
+use ::alloc::format;
+use ::alloc::vec::Vec;
+use core::fmt::Write;
+
+type SelfTest = fn() -> Result<(), &'static str>;
+
+/// Quick checks of the kernel services, in the order they are run
+const SELF_TESTS: [(&str, SelfTest); 5] = [
+    ("heap", test_heap),
+    ("frames", test_frames),
+    ("clock", test_clock),
+    ("backtrace", test_backtrace),
+    ("symbols", test_symbols),
+];
+
+pub(super) fn register() {
+    register_command(&Help);
+    register_command(&SelfTestCommand);
+}
+
+struct Help;
+
+impl Command for Help {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn description(&self) -> &'static str {
+        "list the commands"
+    }
+
+    fn run(&self, _args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        let commands = commands();
+        let width = commands
+            .iter()
+            .map(|command| command.name().len() + command.usage().len() + 1)
+            .max()
+            .unwrap_or(0);
+
+        for command in commands {
+            let synopsis = format!("{} {}", command.name(), command.usage());
+            writeln!(
+                out,
+                "  {:width$}  {}",
+                synopsis,
+                command.description(),
+                width = width
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct SelfTestCommand;
+
+impl Command for SelfTestCommand {
+    fn name(&self) -> &'static str {
+        "selftest"
+    }
+
+    fn description(&self) -> &'static str {
+        "run quick checks of the kernel services"
+    }
+
+    fn run(&self, _args: &[&str], out: &mut dyn Write) -> Result<(), CommandError> {
+        let mut failures = 0;
+        for (name, test) in SELF_TESTS.iter() {
+            match test() {
+                Ok(()) => writeln!(out, "  {:<10} ok", name)?,
+                Err(reason) => {
+                    failures += 1;
+                    writeln!(out, "  {:<10} FAILED, {}", name, reason)?;
+                }
+            }
+        }
+
+        writeln!(
+            out,
+            "{} passed, {} failed",
+            SELF_TESTS.len() - failures,
+            failures
+        )?;
+        if failures == 0 {
+            Ok(())
+        } else {
+            Err(CommandError::Failed)
+        }
+    }
+}
+
+fn test_heap() -> Result<(), &'static str> {
+    let values = (0..1024u64).collect::<Vec<_>>();
+    if values.iter().sum::<u64>() != 1023 * 1024 / 2 {
+        return Err("the allocation doesn't hold what was written");
+    }
+    Ok(())
+}
+
+/// Map a fresh frame, write it & read it back
+fn test_frames() -> Result<(), &'static str> {
+    let address = frame::alloc_frame().ok_or("no free frame")?;
+
+    let result = match unsafe {
+        VBuffer::with_flags(
+            address,
+            frame::FRAME_SIZE,
+            Flags::READ_WRITE | Flags::NO_EXECUTE,
+        )
+    } {
+        Ok(buffer) => {
+            let words = frame::FRAME_SIZE / core::mem::size_of::<u64>();
+            let pointer = buffer.as_mut_ptr::<u64>();
+            unsafe {
+                for i in 0..words {
+                    pointer
+                        .add(i)
+                        .write_volatile(i as u64 ^ 0x5555_5555_5555_5555);
+                }
+                if (0..words)
+                    .all(|i| pointer.add(i).read_volatile() == i as u64 ^ 0x5555_5555_5555_5555)
+                {
+                    Ok(())
+                } else {
+                    Err("the frame doesn't hold what was written")
+                }
+            }
+        }
+        Err(_) => Err("can't map the frame"),
+    };
+
+    frame::free_frame(address);
+    result
+}
+
+fn test_clock() -> Result<(), &'static str> {
+    let before = time::monotonic();
+    let after = time::monotonic();
+    if after < before {
+        return Err("the monotonic clock went backward");
+    }
+    Ok(())
+}
+
+#[inline(never)]
+fn test_backtrace() -> Result<(), &'static str> {
+    // this function, the command & the shell at least
+    if Frames::current().count() < 3 {
+        return Err("the stack can't be unwound");
+    }
+    Ok(())
+}
+
+fn test_symbols() -> Result<(), &'static str> {
+    let symbol = format!("{}", Symbol::of(test_symbols as SelfTest as usize as u64));
+    if !symbol.contains("test_symbols") {
+        return Err("the kernel functions can't be named");
+    }
+    Ok(())
+}
@@ -1,11 +1,13 @@
 mod default;
 mod entry;
+mod exception;
 mod handler;
 mod table;
 mod vector;
 
 pub use entry::Entry;
 pub use entry::*;
+pub use exception::{ExceptionFrame, EXCEPTION_COUNT};
 pub use handler::*;
 pub use table::IDT;
 pub use vector::*;
@@ -12,7 +12,7 @@ pub struct Entry<T> {
 impl<T> Entry<T> {
     const PRESENT_BIT: u8 = 1 << 7;
 
-    fn set_raw(&mut self, handler: usize, gate_type: GateType, dpl: DPL, selector: u16) {
+    pub(super) fn set_raw(&mut self, handler: usize, gate_type: GateType, dpl: DPL, selector: u16) {
         self.entry.offset_low = (handler & 0xffff).try_into().unwrap();
         self.entry.offset_mid = ((handler >> 16) & 0xffff).try_into().unwrap();
         self.entry.offset_high = ((handler >> 32) & 0xffffffff).try_into().unwrap();
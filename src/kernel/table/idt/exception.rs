@@ -0,0 +1,435 @@
+//! Architectural exceptions, the vectors below 32. The stubs of `exception.S` save the general
+//! purpose registers next to the frame pushed by the CPU, `exception_handler` prints a report of
+//! the state of the CPU from them, like a Linux oops, then panics.
+
+use super::*;
+use crate::kernel::cpu;
+use crate::kernel::mem::addr::VirtAddr;
+use crate::kernel::mem::paging::is_mapped;
+use crate::kernel::mem::protect;
+use crate::kernel::unwind::{self, Registers, Symbol};
+
+use core::convert::TryFrom;
+use core::fmt;
+use lib::*;
+
+pub const EXCEPTION_COUNT: usize = 32;
+
+/// Bytes of code printed around the faulting instruction
+const CODE_BYTES_BEFORE: u64 = 21;
+const CODE_BYTES_AFTER: u64 = 21;
+
+const EFER: u32 = 0xc000_0080;
+const MCG_CAP: u32 = 0x179;
+const MCG_STATUS: u32 = 0x17a;
+/// Every bank has 4 MSRs from there: MCi_CTL, MCi_STATUS, MCi_ADDR & MCi_MISC
+const MC0_CTL: u32 = 0x400;
+const CPUID_MCA_BIT: u32 = 1 << 14;
+
+/// MCG_STATUS bits
+const MCG_RIPV: u64 = 1 << 0;
+const MCG_EIPV: u64 = 1 << 1;
+const MCG_MCIP: u64 = 1 << 2;
+
+/// MCi_STATUS bits
+const MCI_VAL: u64 = 1 << 63;
+const MCI_OVER: u64 = 1 << 62;
+const MCI_UC: u64 = 1 << 61;
+const MCI_EN: u64 = 1 << 60;
+const MCI_MISCV: u64 = 1 << 59;
+const MCI_ADDRV: u64 = 1 << 58;
+const MCI_PCC: u64 = 1 << 57;
+
+/// Page fault error code bits
+const PF_PRESENT: u64 = 1 << 0;
+const PF_WRITE: u64 = 1 << 1;
+const PF_USER: u64 = 1 << 2;
+const PF_RESERVED: u64 = 1 << 3;
+const PF_INSTRUCTION: u64 = 1 << 4;
+const PF_PROTECTION_KEY: u64 = 1 << 5;
+const PF_SHADOW_STACK: u64 = 1 << 6;
+const PF_SGX: u64 = 1 << 15;
+
+/// Selector error code bits
+const SELECTOR_EXTERNAL: u64 = 1 << 0;
+const SELECTOR_IDT: u64 = 1 << 1;
+const SELECTOR_LDT: u64 = 1 << 2;
+
+extern "C" {
+    static exception_stubs: [usize; EXCEPTION_COUNT];
+}
+
+/// Address of the stub of `vector`, for the IDT
+pub(super) fn stub(vector: usize) -> usize {
+    unsafe { exception_stubs[vector] }
+}
+
+/// Registers saved by the stubs, the vector & error code, then the frame pushed by the CPU
+#[repr(C)]
+#[derive(Debug)]
+pub struct ExceptionFrame {
+    rax: u64,
+    rbx: u64,
+    rcx: u64,
+    rdx: u64,
+    rsi: u64,
+    rdi: u64,
+    rbp: u64,
+    r8: u64,
+    r9: u64,
+    r10: u64,
+    r11: u64,
+    r12: u64,
+    r13: u64,
+    r14: u64,
+    r15: u64,
+    vector: u64,
+    /// 0 for the exceptions without one
+    error_code: u64,
+    frame: InterruptStackFrame,
+}
+
+impl ExceptionFrame {
+    pub fn vector(&self) -> usize {
+        self.vector as usize
+    }
+
+    pub fn error_code(&self) -> u64 {
+        self.error_code
+    }
+
+    pub fn stack_frame(&self) -> &InterruptStackFrame {
+        &self.frame
+    }
+
+    /// Registers of the interrupted code
+    pub fn registers(&self) -> Registers {
+        let values = [
+            (Registers::RAX, self.rax),
+            (Registers::RDX, self.rdx),
+            (Registers::RCX, self.rcx),
+            (Registers::RBX, self.rbx),
+            (Registers::RSI, self.rsi),
+            (Registers::RDI, self.rdi),
+            (Registers::RBP, self.rbp),
+            (Registers::RSP, self.frame.rsp()),
+            (Registers::R8, self.r8),
+            (Registers::R8 + 1, self.r9),
+            (Registers::R8 + 2, self.r10),
+            (Registers::R8 + 3, self.r11),
+            (Registers::R8 + 4, self.r12),
+            (Registers::R8 + 5, self.r13),
+            (Registers::R8 + 6, self.r14),
+            (Registers::R15, self.r15),
+            (Registers::RIP, self.frame.rip()),
+        ];
+
+        let mut registers = Registers::default();
+        for &(register, value) in values.iter() {
+            registers.set(register, Some(value));
+        }
+        registers
+    }
+}
+
+#[no_mangle]
+extern "C" fn exception_handler(frame: &mut ExceptionFrame) {
+    let exception = Exception::try_from(frame.vector()).ok();
+    let cr2 = get_cr2();
+
+    print_report(frame, exception, cr2);
+    unwind::print_backtrace_from(frame.registers());
+
+    let rip = frame.stack_frame().rip();
+    match exception {
+        Some(Exception::WithError(VectorWithError::PageFault)) => {
+            if let Some(violation) = protect::violation(VirtAddr::from(cr2), frame.error_code()) {
+                panic!("{} at 0x{:x}, aborting!", violation, cr2);
+            }
+            panic!("page fault at 0x{:x} from 0x{:x}, aborting!", cr2, rip);
+        }
+        Some(exception) => panic!(
+            "uncaught {} ({}) at 0x{:x}, aborting!",
+            exception.mnemonic(),
+            exception.name(),
+            rip
+        ),
+        None => panic!(
+            "reserved exception {} at 0x{:x}, aborting!",
+            frame.vector(),
+            rip
+        ),
+    }
+}
+
+fn print_report(frame: &ExceptionFrame, exception: Option<Exception>, cr2: usize) {
+    let stack_frame = frame.stack_frame();
+    let (mnemonic, name) = exception.map_or(("#??", "reserved exception"), |exception| {
+        (exception.mnemonic(), exception.name())
+    });
+
+    early_kprintln!("\x1B\x74----- [EXCEPTION START HERE] -----");
+    early_kprintln!(
+        "oops: {} {}, vector {}, error code 0x{:04x}",
+        mnemonic,
+        name,
+        frame.vector(),
+        frame.error_code()
+    );
+
+    match exception {
+        Some(Exception::WithError(VectorWithError::PageFault)) => early_kprintln!(
+            "{}: {} at 0x{:016x}",
+            mnemonic,
+            PageFaultError(frame.error_code()),
+            cr2
+        ),
+        Some(Exception::WithError(vector)) if vector.has_selector_error_code() => {
+            early_kprintln!("{}: {}", mnemonic, SelectorError(frame.error_code()))
+        }
+        Some(Exception::Vector(Vector::MachineCheck)) => print_machine_check(),
+        _ => (),
+    }
+
+    early_kprintln!("CPU: {}", cpu::current());
+    early_kprintln!(
+        "RIP: {:04x}:{:016x} {}",
+        stack_frame.cs(),
+        stack_frame.rip(),
+        Symbol::of(stack_frame.rip())
+    );
+    print_code(stack_frame.rip());
+    early_kprintln!(
+        "RSP: {:04x}:{:016x} RFLAGS: {:08x}",
+        stack_frame.ss(),
+        stack_frame.rsp(),
+        stack_frame.rflags()
+    );
+
+    let registers = [
+        ("RAX", frame.rax),
+        ("RBX", frame.rbx),
+        ("RCX", frame.rcx),
+        ("RDX", frame.rdx),
+        ("RSI", frame.rsi),
+        ("RDI", frame.rdi),
+        ("RBP", frame.rbp),
+        ("R08", frame.r8),
+        ("R09", frame.r9),
+        ("R10", frame.r10),
+        ("R11", frame.r11),
+        ("R12", frame.r12),
+        ("R13", frame.r13),
+        ("R14", frame.r14),
+        ("R15", frame.r15),
+    ];
+    for line in registers.chunks(3) {
+        for (name, value) in line {
+            early_kprint!("{}: {:016x} ", name, value);
+        }
+        early_kprintln!();
+    }
+
+    let (ds, es, fs, gs) = get_data_segments();
+    early_kprintln!(
+        "CS: {:04x} SS: {:04x} DS: {:04x} ES: {:04x} FS: {:04x} GS: {:04x}",
+        stack_frame.cs(),
+        stack_frame.ss(),
+        ds,
+        es,
+        fs,
+        gs
+    );
+
+    let (cr0, cr3, cr4) = unsafe { (get_cr0!(), get_cr3!(), get_cr4!()) };
+    early_kprintln!("CR0: {:016x} CR2: {:016x} CR3: {:016x}", cr0, cr2, cr3);
+    early_kprintln!("CR4: {:016x} EFER: {:016x}", cr4, read_msr(EFER));
+    early_kprintln!("----- [EXCEPTION  END  HERE] -----\x1B\0");
+}
+
+/// Bytes around `rip`, like the `Code:` line of Linux, the faulting one is between brackets
+fn print_code(rip: u64) {
+    early_kprint!("Code:");
+    for address in rip.wrapping_sub(CODE_BYTES_BEFORE)..rip.wrapping_add(CODE_BYTES_AFTER) {
+        let byte = usize::try_from(address)
+            .ok()
+            .filter(|&address| unsafe { is_mapped(VirtAddr::from(address)) })
+            .map(|address| unsafe { *(address as *const u8) });
+
+        match (byte, address == rip) {
+            (Some(byte), true) => early_kprint!(" <{:02x}>", byte),
+            (Some(byte), false) => early_kprint!(" {:02x}", byte),
+            (None, true) => early_kprint!(" <??>"),
+            (None, false) => early_kprint!(" ??"),
+        }
+    }
+    early_kprintln!();
+}
+
+/// Banks of the machine check architecture holding an error
+fn print_machine_check() {
+    let [_, _, _, edx] = cpuid!(1);
+    if edx & CPUID_MCA_BIT == 0 {
+        early_kprintln!("#MC: no machine check architecture, the banks can't be read");
+        return;
+    }
+
+    let status = read_msr(MCG_STATUS);
+    early_kprintln!(
+        "#MC: MCG_STATUS {:x}{}{}{}",
+        status,
+        if status & MCG_RIPV != 0 {
+            " restartable"
+        } else {
+            ""
+        },
+        if status & MCG_EIPV != 0 {
+            " error-ip"
+        } else {
+            ""
+        },
+        if status & MCG_MCIP != 0 {
+            " in-progress"
+        } else {
+            ""
+        }
+    );
+
+    let bank_count = read_msr(MCG_CAP) & 0xff;
+    for bank in 0..bank_count as u32 {
+        let status = read_msr(MC0_CTL + 4 * bank + 1);
+        if status & MCI_VAL == 0 {
+            continue;
+        }
+
+        early_kprint!(
+            "#MC: bank {}: status {:016x}, MCA error code 0x{:04x}, model specific 0x{:04x}",
+            bank,
+            status,
+            status & 0xffff,
+            (status >> 16) & 0xffff
+        );
+
+        let flags = [
+            (MCI_OVER, "overflow"),
+            (MCI_UC, "uncorrected"),
+            (MCI_EN, "enabled"),
+            (MCI_PCC, "context-corrupt"),
+        ];
+        for &(bit, flag) in flags.iter() {
+            if status & bit != 0 {
+                early_kprint!(" {}", flag);
+            }
+        }
+
+        if status & MCI_ADDRV != 0 {
+            early_kprint!(", address {:x}", read_msr(MC0_CTL + 4 * bank + 2));
+        }
+        if status & MCI_MISCV != 0 {
+            early_kprint!(", misc {:x}", read_msr(MC0_CTL + 4 * bank + 3));
+        }
+        early_kprintln!();
+    }
+}
+
+/// Access that caused a page fault
+struct PageFaultError(u64);
+
+impl fmt::Display for PageFaultError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let access = if self.0 & PF_INSTRUCTION != 0 {
+            "instruction fetch"
+        } else if self.0 & PF_WRITE != 0 {
+            "write"
+        } else {
+            "read"
+        };
+
+        write!(
+            f,
+            "{} {} access to a {} page",
+            if self.0 & PF_USER != 0 {
+                "user"
+            } else {
+                "supervisor"
+            },
+            access,
+            if self.0 & PF_PRESENT != 0 {
+                "present"
+            } else {
+                "not-present"
+            }
+        )?;
+
+        let causes = [
+            (PF_RESERVED, "reserved bit set"),
+            (PF_PROTECTION_KEY, "protection key"),
+            (PF_SHADOW_STACK, "shadow stack"),
+            (PF_SGX, "SGX"),
+        ];
+        for &(bit, cause) in causes.iter() {
+            if self.0 & bit != 0 {
+                write!(f, ", {}", cause)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Segment selector or IDT entry the exception is about
+struct SelectorError(u64);
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return f.write_str("no selector");
+        }
+
+        let index = (self.0 >> 3) & 0x1fff;
+        if self.0 & SELECTOR_IDT != 0 {
+            write!(f, "IDT vector {}", index)?;
+        } else if self.0 & SELECTOR_LDT != 0 {
+            write!(f, "LDT selector 0x{:04x}", self.0 & 0xfff8)?;
+        } else {
+            write!(f, "GDT selector 0x{:04x}, entry {}", self.0 & 0xfff8, index)?;
+        }
+
+        if self.0 & SELECTOR_EXTERNAL != 0 {
+            f.write_str(", external event")?;
+        }
+        Ok(())
+    }
+}
+
+fn read_msr(msr: u32) -> u64 {
+    let [edx, eax] = readmsr!(msr);
+    (u64::from(edx) << 32) | u64::from(eax)
+}
+
+fn get_cr2() -> usize {
+    let cr2: usize;
+    unsafe {
+        core::arch::asm!("mov {}, cr2", out(reg) cr2, options(nomem, nostack, preserves_flags));
+    }
+    cr2
+}
+
+/// The handler runs with the data segments of the interrupted code
+fn get_data_segments() -> (u16, u16, u16, u16) {
+    let (ds, es, fs, gs): (u16, u16, u16, u16);
+    unsafe {
+        core::arch::asm!(
+            "mov {0:x}, ds",
+            "mov {1:x}, es",
+            "mov {2:x}, fs",
+            "mov {3:x}, gs",
+            out(reg) ds,
+            out(reg) es,
+            out(reg) fs,
+            out(reg) gs,
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+    (ds, es, fs, gs)
+}
@@ -12,3 +12,27 @@ pub struct InterruptStackFrame {
     rsp: *const (),
     ss: usize,
 }
+
+impl InterruptStackFrame {
+    /// Instruction the interrupt stopped at, or the next one for traps
+    pub fn rip(&self) -> u64 {
+        self.rip as u64
+    }
+
+    pub fn cs(&self) -> u16 {
+        self.cs as u16
+    }
+
+    pub fn rflags(&self) -> u64 {
+        self.rflags as u64
+    }
+
+    pub fn ss(&self) -> u16 {
+        self.ss as u16
+    }
+
+    /// Stack pointer of the interrupted code
+    pub fn rsp(&self) -> u64 {
+        self.rsp as u64
+    }
+}
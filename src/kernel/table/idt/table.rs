@@ -20,38 +20,15 @@ impl IDT {
         use crate::kernel::table::idt::default::*;
 
         let mut idt = IDT::empty();
-        idt.set_entry_new(0, panic_on_0);
-        idt.set_entry_new(1, panic_on_1);
-        idt.set_entry_new(2, panic_on_2);
-        idt.set_entry_new(3, panic_on_3);
-        idt.set_entry_new(4, panic_on_4);
-        idt.set_entry_new(5, panic_on_5);
-        idt.set_entry_new(6, panic_on_6);
-        idt.set_entry_new(7, panic_on_7);
-        idt.set_entry_new(8, panic_on_8);
-        idt.set_entry_new(9, panic_on_9);
-        idt.set_entry_new(10, panic_on_10);
-        idt.set_entry_new(11, panic_on_11);
-        idt.set_entry_new(12, panic_on_12);
-        idt.set_entry_new(13, panic_on_13);
-        idt.set_entry_new(14, panic_on_14);
-        idt.set_entry_new(15, panic_on_15);
-        idt.set_entry_new(16, panic_on_16);
-        idt.set_entry_new(17, panic_on_17);
-        idt.set_entry_new(18, panic_on_18);
-        idt.set_entry_new(19, panic_on_19);
-        idt.set_entry_new(20, panic_on_20);
-        idt.set_entry_new(21, panic_on_21);
-        idt.set_entry_new(22, panic_on_22);
-        idt.set_entry_new(23, panic_on_23);
-        idt.set_entry_new(24, panic_on_24);
-        idt.set_entry_new(25, panic_on_25);
-        idt.set_entry_new(26, panic_on_26);
-        idt.set_entry_new(27, panic_on_27);
-        idt.set_entry_new(28, panic_on_28);
-        idt.set_entry_new(29, panic_on_29);
-        idt.set_entry_new(30, panic_on_30);
-        idt.set_entry_new(31, panic_on_31);
+        for vector in 0..EXCEPTION_COUNT {
+            idt.get_entry_mut::<Handler>(vector).set_raw(
+                exception::stub(vector),
+                GateType::INTERRUPT,
+                DPL::PRIVILEGE0,
+                0x20,
+            );
+        }
+
         idt.set_entry_new(32, panic_on_32);
         idt.set_entry_new(33, panic_on_33);
         idt.set_entry_new(34, panic_on_34);
@@ -279,13 +256,9 @@ impl IDT {
         idt
     }
 
-    fn set_entry_new(&mut self, idx: usize, handler: HandlerWithError) {
-        self.get_entry_mut::<HandlerWithError>(idx).set(
-            handler,
-            GateType::INTERRUPT,
-            DPL::PRIVILEGE0,
-            0x20,
-        );
+    fn set_entry_new(&mut self, idx: usize, handler: Handler) {
+        self.get_entry_mut::<Handler>(idx)
+            .set(handler, GateType::INTERRUPT, DPL::PRIVILEGE0, 0x20);
     }
 }
 
@@ -306,6 +279,22 @@ impl IDT {
         old_reg.addr
     }
 
+    /// Get the IDT currently loaded on this CPU
+    ///
+    /// # Safety
+    /// The table has to have been loaded with `set_for_this_cpu`, and interrupts must be disabled
+    /// while it's being modified.
+    pub unsafe fn current<'a>() -> &'a mut IDT {
+        let mut reg: IDTRegister = IDTRegister {
+            size: 0,
+            addr: 0 as _,
+        };
+        core::arch::asm!("sidt [{}]", in(reg) &mut reg);
+
+        let addr = reg.addr;
+        &mut *addr
+    }
+
     fn get_entry<T>(&self, idx: usize) -> &Entry<T> {
         unsafe { transmute(&self.entries[idx]) }
     }
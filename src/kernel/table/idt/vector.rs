@@ -1,7 +1,8 @@
 use core::convert::From;
 use core::convert::TryFrom;
 
-#[derive(Debug)]
+/// Exceptions for which the CPU doesn't push an error code
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Vector {
     DivideByZero,
     Debug,
@@ -11,11 +12,52 @@ pub enum Vector {
     BoundRange,
     InvalidOpCode,
     DeviceNotAvailable,
+    CoprocessorSegmentOverrun,
     X87FloatingPoint,
     MachineCheck,
     SIMDFloatingPoint,
-    VMMCommunication,
-    Security,
+    Virtualization,
+    HypervisorInjection,
+}
+
+impl Vector {
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Vector::DivideByZero => "#DE",
+            Vector::Debug => "#DB",
+            Vector::NMI => "NMI",
+            Vector::Breakpoint => "#BP",
+            Vector::Overflow => "#OF",
+            Vector::BoundRange => "#BR",
+            Vector::InvalidOpCode => "#UD",
+            Vector::DeviceNotAvailable => "#NM",
+            Vector::CoprocessorSegmentOverrun => "#CSO",
+            Vector::X87FloatingPoint => "#MF",
+            Vector::MachineCheck => "#MC",
+            Vector::SIMDFloatingPoint => "#XM",
+            Vector::Virtualization => "#VE",
+            Vector::HypervisorInjection => "#HV",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Vector::DivideByZero => "divide error",
+            Vector::Debug => "debug",
+            Vector::NMI => "non-maskable interrupt",
+            Vector::Breakpoint => "breakpoint",
+            Vector::Overflow => "overflow",
+            Vector::BoundRange => "bound range exceeded",
+            Vector::InvalidOpCode => "invalid opcode",
+            Vector::DeviceNotAvailable => "device not available",
+            Vector::CoprocessorSegmentOverrun => "coprocessor segment overrun",
+            Vector::X87FloatingPoint => "x87 floating-point exception",
+            Vector::MachineCheck => "machine check",
+            Vector::SIMDFloatingPoint => "SIMD floating-point exception",
+            Vector::Virtualization => "virtualization exception",
+            Vector::HypervisorInjection => "hypervisor injection exception",
+        }
+    }
 }
 
 impl From<Vector> for usize {
@@ -29,11 +71,12 @@ impl From<Vector> for usize {
             Vector::BoundRange => 5,
             Vector::InvalidOpCode => 6,
             Vector::DeviceNotAvailable => 7,
+            Vector::CoprocessorSegmentOverrun => 9,
             Vector::X87FloatingPoint => 16,
             Vector::MachineCheck => 18,
             Vector::SIMDFloatingPoint => 19,
-            Vector::VMMCommunication => 29,
-            Vector::Security => 30,
+            Vector::Virtualization => 20,
+            Vector::HypervisorInjection => 28,
         }
     }
 }
@@ -50,17 +93,19 @@ impl TryFrom<usize> for Vector {
             5 => Ok(Vector::BoundRange),
             6 => Ok(Vector::InvalidOpCode),
             7 => Ok(Vector::DeviceNotAvailable),
+            9 => Ok(Vector::CoprocessorSegmentOverrun),
             16 => Ok(Vector::X87FloatingPoint),
             18 => Ok(Vector::MachineCheck),
             19 => Ok(Vector::SIMDFloatingPoint),
-            29 => Ok(Vector::VMMCommunication),
-            30 => Ok(Vector::Security),
+            20 => Ok(Vector::Virtualization),
+            28 => Ok(Vector::HypervisorInjection),
             _ => Err(()),
         }
     }
 }
 
-#[derive(Debug)]
+/// Exceptions for which the CPU pushes an error code
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum VectorWithError {
     DoubleFault,
     InvalidTSS,
@@ -69,6 +114,52 @@ pub enum VectorWithError {
     GeneralProtection,
     PageFault,
     AlignmentCheck,
+    ControlProtection,
+    VMMCommunication,
+    Security,
+}
+
+impl VectorWithError {
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            VectorWithError::DoubleFault => "#DF",
+            VectorWithError::InvalidTSS => "#TS",
+            VectorWithError::SegmentNotPresent => "#NP",
+            VectorWithError::Stack => "#SS",
+            VectorWithError::GeneralProtection => "#GP",
+            VectorWithError::PageFault => "#PF",
+            VectorWithError::AlignmentCheck => "#AC",
+            VectorWithError::ControlProtection => "#CP",
+            VectorWithError::VMMCommunication => "#VC",
+            VectorWithError::Security => "#SX",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VectorWithError::DoubleFault => "double fault",
+            VectorWithError::InvalidTSS => "invalid TSS",
+            VectorWithError::SegmentNotPresent => "segment not present",
+            VectorWithError::Stack => "stack fault",
+            VectorWithError::GeneralProtection => "general protection fault",
+            VectorWithError::PageFault => "page fault",
+            VectorWithError::AlignmentCheck => "alignment check",
+            VectorWithError::ControlProtection => "control protection exception",
+            VectorWithError::VMMCommunication => "VMM communication exception",
+            VectorWithError::Security => "security exception",
+        }
+    }
+
+    /// The error code is a segment selector, or an IDT entry
+    pub fn has_selector_error_code(self) -> bool {
+        matches!(
+            self,
+            VectorWithError::InvalidTSS
+                | VectorWithError::SegmentNotPresent
+                | VectorWithError::Stack
+                | VectorWithError::GeneralProtection
+        )
+    }
 }
 
 impl From<VectorWithError> for usize {
@@ -81,6 +172,9 @@ impl From<VectorWithError> for usize {
             VectorWithError::GeneralProtection => 13,
             VectorWithError::PageFault => 14,
             VectorWithError::AlignmentCheck => 17,
+            VectorWithError::ControlProtection => 21,
+            VectorWithError::VMMCommunication => 29,
+            VectorWithError::Security => 30,
         }
     }
 }
@@ -96,7 +190,42 @@ impl TryFrom<usize> for VectorWithError {
             13 => Ok(VectorWithError::GeneralProtection),
             14 => Ok(VectorWithError::PageFault),
             17 => Ok(VectorWithError::AlignmentCheck),
+            21 => Ok(VectorWithError::ControlProtection),
+            29 => Ok(VectorWithError::VMMCommunication),
+            30 => Ok(VectorWithError::Security),
             _ => Err(()),
         }
     }
 }
+
+/// An architectural exception, one of the vectors below 32
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Exception {
+    Vector(Vector),
+    WithError(VectorWithError),
+}
+
+impl Exception {
+    pub fn mnemonic(self) -> &'static str {
+        match self {
+            Exception::Vector(vector) => vector.mnemonic(),
+            Exception::WithError(vector) => vector.mnemonic(),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Exception::Vector(vector) => vector.name(),
+            Exception::WithError(vector) => vector.name(),
+        }
+    }
+}
+
+impl TryFrom<usize> for Exception {
+    type Error = ();
+    fn try_from(vector: usize) -> Result<Exception, ()> {
+        Vector::try_from(vector)
+            .map(Exception::Vector)
+            .or_else(|_| VectorWithError::try_from(vector).map(Exception::WithError))
+    }
+}
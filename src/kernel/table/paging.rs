@@ -1,4 +1,4 @@
-use crate::kernel::config::*;
+use crate::boot::kaslr;
 use crate::kernel::mem::addr::*;
 use ::alloc::boxed::Box;
 use bitflags::*;
@@ -67,8 +67,9 @@ impl PageTable {
     /// Size of a normal page
     pub const PAGE_SIZE: usize = 1 << Self::PAGE_BITS;
 
+    /// Flags of the entries pointing to a table, the entries of the last level restrict the access
     pub fn default_flags() -> Flags {
-        Flags::PRESENT
+        Flags::PRESENT | Flags::READ_WRITE
     }
 
     /// Return the table of type `table_type` associated with the address `addr`
@@ -112,14 +113,15 @@ impl PageTable {
     }
 
     fn table_addr(pdpt: usize, pdt: usize, pt: usize) -> VirtAddr {
-        VirtAddr::from(PAGE_MAP_BASE)
+        VirtAddr::from(kaslr::page_map_base())
             | (pdpt << Self::get_shift(PageTableType::PDPT))
             | (pdt << Self::get_shift(PageTableType::PDT))
             | (pt << Self::get_shift(PageTableType::PT))
     }
 
     fn get_table_addr(table_type: PageTableType, addr: VirtAddr) -> VirtAddr {
-        let root_idx = Self::get_index(PageTableType::PML4T, VirtAddr::from(PAGE_MAP_BASE));
+        let root_idx =
+            Self::get_index(PageTableType::PML4T, VirtAddr::from(kaslr::page_map_base()));
         match table_type {
             PageTableType::PML4T => Self::table_addr(root_idx, root_idx, root_idx),
 
@@ -0,0 +1,86 @@
+use crate::drivers::rtc;
+
+use lib::*;
+
+use core::convert::TryFrom;
+use core::sync::atomic::*;
+use core::time::Duration;
+
+const PIT_FREQUENCY: u64 = 1_193_182;
+const PIT_CHANNEL2_PORT: u16 = 0x42;
+const PIT_COMMAND_PORT: u16 = 0x43;
+const PIT_GATE_PORT: u16 = 0x61;
+
+const PIT_GATE_BIT: u8 = 1 << 0;
+const PIT_SPEAKER_BIT: u8 = 1 << 1;
+const PIT_OUTPUT_BIT: u8 = 1 << 5;
+
+const CALIBRATION_MS: u64 = 10;
+
+static TSC_FREQUENCY: AtomicU64 = AtomicU64::new(0);
+static BOOT_TSC: AtomicU64 = AtomicU64::new(0);
+
+/// Wall-clock time in nanoseconds since the UNIX epoch when the monotonic clock was at zero
+static BOOT_REALTIME: AtomicU64 = AtomicU64::new(0);
+
+/// Calibrate the monotonic clock & read the wall-clock time from the RTC
+pub fn init() {
+    let frequency = calibrate_tsc();
+    BOOT_TSC.store(rdtsc!(), Ordering::Relaxed);
+    TSC_FREQUENCY.store(frequency, Ordering::Release);
+
+    rtc::init();
+    let now = rtc::read();
+    let boot_time = Duration::from_secs(now.to_unix()).saturating_sub(monotonic());
+    BOOT_REALTIME.store(
+        u64::try_from(boot_time.as_nanos()).unwrap(),
+        Ordering::Release,
+    );
+
+    kinfo!(
+        "time: TSC at {} kHz, wall clock is {:?}",
+        frequency / 1000,
+        now
+    );
+}
+
+/// Time elapsed since the clock was initialized, never goes backward
+pub fn monotonic() -> Duration {
+    let frequency = TSC_FREQUENCY.load(Ordering::Acquire);
+    if frequency == 0 {
+        return Duration::ZERO;
+    }
+
+    let ticks = rdtsc!().wrapping_sub(BOOT_TSC.load(Ordering::Relaxed));
+    let nanos = u128::from(ticks) * 1_000_000_000 / u128::from(frequency);
+    Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+}
+
+/// Current wall-clock time, as a duration since the UNIX epoch
+pub fn realtime_now() -> Duration {
+    Duration::from_nanos(BOOT_REALTIME.load(Ordering::Acquire)) + monotonic()
+}
+
+/// Measure the TSC frequency against a one-shot countdown of the PIT channel 2
+fn calibrate_tsc() -> u64 {
+    let count = PIT_FREQUENCY * CALIBRATION_MS / 1000;
+
+    unsafe {
+        let gate = io_read_port!(u8, PIT_GATE_PORT);
+        io_write_port!(u8, PIT_GATE_PORT, (gate & !PIT_SPEAKER_BIT) | PIT_GATE_BIT);
+
+        // channel 2, lobyte/hibyte access, mode 0 (interrupt on terminal count), binary
+        io_write_port!(u8, PIT_COMMAND_PORT, 0b1011_0000);
+        io_write_port!(u8, PIT_CHANNEL2_PORT, count & 0xff);
+        io_write_port!(u8, PIT_CHANNEL2_PORT, count >> 8);
+
+        let start = rdtsc!();
+        while io_read_port!(u8, PIT_GATE_PORT) & PIT_OUTPUT_BIT == 0 {
+            core::hint::spin_loop();
+        }
+        let end = rdtsc!();
+
+        io_write_port!(u8, PIT_GATE_PORT, gate);
+        (end - start) * 1000 / CALIBRATION_MS
+    }
+}
@@ -0,0 +1,313 @@
+//! Backtraces of the kernel stack.
+//!
+//! Frames are unwound with the call frame information of `.eh_frame`, found through the search
+//! table of `.eh_frame_hdr`. Code without it, like the assembly of the boot stub, is unwound by
+//! following the frame pointers. Addresses are resolved to function names with the symbol table
+//! the bootloader loads with the kernel.
+
+mod cfi;
+mod symbols;
+
+use crate::kernel::mem::addr::VirtAddr;
+use crate::kernel::mem::paging::is_mapped;
+use crate::kernel::mem::protect::Section;
+use crate::kernel::table::idt::InterruptStackFrame;
+
+use core::convert::TryFrom;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+pub use symbols::init;
+
+/// Frames printed before giving up, the stack may be corrupted
+const MAX_FRAMES: usize = 64;
+
+/// Only the first backtrace is printed: exceptions print the one of the interrupted code before
+/// panicking, & a fault while unwinding would print another one
+static PRINTED: AtomicBool = AtomicBool::new(false);
+
+/// General purpose registers & instruction pointer of a frame, with the DWARF numbering.
+/// Registers the unwinder couldn't recover are `None`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Registers {
+    values: [Option<u64>; Registers::COUNT],
+}
+
+impl Registers {
+    pub const RAX: usize = 0;
+    pub const RDX: usize = 1;
+    pub const RCX: usize = 2;
+    pub const RBX: usize = 3;
+    pub const RSI: usize = 4;
+    pub const RDI: usize = 5;
+    pub const RBP: usize = 6;
+    pub const RSP: usize = 7;
+    pub const R8: usize = 8;
+    pub const R15: usize = 15;
+    /// Column of the return address in the call frame information
+    pub const RIP: usize = 16;
+    pub const COUNT: usize = 17;
+
+    const NAMES: [&'static str; Registers::COUNT] = [
+        "rax", "rdx", "rcx", "rbx", "rsi", "rdi", "rbp", "rsp", "r8", "r9", "r10", "r11", "r12",
+        "r13", "r14", "r15", "rip",
+    ];
+
+    /// Registers at the place this is inlined
+    #[inline(always)]
+    pub fn current() -> Registers {
+        let mut values = [0u64; Registers::COUNT];
+        unsafe {
+            core::arch::asm!(
+                "mov [{values} + 0x00], rax",
+                "mov [{values} + 0x08], rdx",
+                "mov [{values} + 0x10], rcx",
+                "mov [{values} + 0x18], rbx",
+                "mov [{values} + 0x20], rsi",
+                "mov [{values} + 0x28], rdi",
+                "mov [{values} + 0x30], rbp",
+                "mov [{values} + 0x38], rsp",
+                "mov [{values} + 0x40], r8",
+                "mov [{values} + 0x48], r9",
+                "mov [{values} + 0x50], r10",
+                "mov [{values} + 0x58], r11",
+                "mov [{values} + 0x60], r12",
+                "mov [{values} + 0x68], r13",
+                "mov [{values} + 0x70], r14",
+                "mov [{values} + 0x78], r15",
+                "lea {rip}, [rip]",
+                "mov [{values} + 0x80], {rip}",
+                values = in(reg) values.as_mut_ptr(),
+                rip = out(reg) _,
+                options(nostack, preserves_flags)
+            );
+        }
+
+        let mut registers = Registers::default();
+        for (register, &value) in values.iter().enumerate() {
+            registers.set(register, Some(value));
+        }
+        registers
+    }
+
+    pub fn get(&self, register: usize) -> Option<u64> {
+        self.values.get(register).copied().flatten()
+    }
+
+    pub fn set(&mut self, register: usize, value: Option<u64>) {
+        if let Some(slot) = self.values.get_mut(register) {
+            *slot = value;
+        }
+    }
+
+    pub fn pc(&self) -> Option<u64> {
+        self.get(Registers::RIP)
+    }
+
+    pub fn name(register: usize) -> &'static str {
+        Registers::NAMES.get(register).copied().unwrap_or("?")
+    }
+}
+
+/// A frame of the stack, its registers are the ones of when it called the next frame
+#[derive(Copy, Clone, Debug)]
+pub struct Frame {
+    pub registers: Registers,
+    /// The instruction pointer is the return address of a call, not the current instruction
+    pub is_return_address: bool,
+}
+
+impl Frame {
+    pub fn pc(&self) -> u64 {
+        self.registers.pc().unwrap_or(0)
+    }
+
+    /// Address inside the instruction running in this frame, the return address can be the
+    /// start of the next function when the call is the last instruction
+    fn lookup_pc(&self) -> u64 {
+        if self.is_return_address {
+            self.pc().wrapping_sub(1)
+        } else {
+            self.pc()
+        }
+    }
+}
+
+/// Frames of the stack, from the innermost one
+pub struct Frames<'a> {
+    next: Option<Frame>,
+    count: usize,
+    /// Frames of the interrupt handler are skipped until the one interrupting the code
+    interrupt: Option<&'a InterruptStackFrame>,
+}
+
+impl<'a> Frames<'a> {
+    /// Frames of the caller of this function
+    #[inline(always)]
+    pub fn current() -> Frames<'a> {
+        Frames::from_registers(Registers::current())
+    }
+
+    pub fn from_registers(registers: Registers) -> Frames<'a> {
+        Frames {
+            next: Some(Frame {
+                registers,
+                is_return_address: false,
+            }),
+            count: 0,
+            interrupt: None,
+        }
+    }
+
+    /// Frames of the code stopped by an interrupt or an exception. Has to be called by the
+    /// handler of the interrupt, the frames are unwound from there up to `frame`.
+    #[inline(always)]
+    pub fn from_interrupt(frame: &'a InterruptStackFrame) -> Frames<'a> {
+        Frames {
+            interrupt: Some(frame),
+            ..Frames::current()
+        }
+    }
+
+    fn unwind(&mut self, frame: &Frame) -> Option<Frame> {
+        let rsp = frame.registers.get(Registers::RSP)?;
+        let registers = cfi::unwind(frame.lookup_pc(), &frame.registers)
+            .or_else(|| unwind_frame_pointer(&frame.registers))?;
+
+        // the stack only grows down, anything else is a loop in a corrupted stack
+        let caller_rsp = registers.get(Registers::RSP)?;
+        if caller_rsp <= rsp {
+            return None;
+        }
+
+        let mut caller = Frame {
+            registers,
+            is_return_address: true,
+        };
+
+        // the interrupt frame is above the frame of the handler, the other registers are
+        // restored from where the handler saved them
+        if let Some(interrupt) = self.interrupt {
+            if caller_rsp >= interrupt as *const InterruptStackFrame as u64 {
+                caller.registers.set(Registers::RIP, Some(interrupt.rip()));
+                caller.registers.set(Registers::RSP, Some(interrupt.rsp()));
+                caller.is_return_address = false;
+                self.interrupt = None;
+            }
+        }
+
+        Some(caller)
+    }
+}
+
+impl Iterator for Frames<'_> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        loop {
+            let frame = self.next.take()?;
+            let in_code = usize::try_from(frame.pc()).map_or(false, |pc| {
+                Section::of(VirtAddr::from(pc)) == Some(Section::Code)
+            });
+            if self.count == MAX_FRAMES || !in_code {
+                return None;
+            }
+
+            let skipped = self.interrupt.is_some();
+            self.count += 1;
+            self.next = self.unwind(&frame);
+            if !skipped {
+                return Some(frame);
+            }
+        }
+    }
+}
+
+/// Print the frames of the caller
+#[inline(never)]
+pub fn print_backtrace() {
+    print(Frames::current());
+}
+
+/// Print the frames of the code stopped by an interrupt, from its handler
+#[inline(never)]
+pub fn print_interrupt_backtrace(frame: &InterruptStackFrame) {
+    print(Frames::from_interrupt(frame));
+}
+
+/// Print the frames from the one running with `registers`
+pub fn print_backtrace_from(registers: Registers) {
+    print(Frames::from_registers(registers));
+}
+
+fn print(frames: Frames) {
+    if PRINTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    early_kprintln!("backtrace:");
+    for (index, frame) in frames.enumerate() {
+        early_kprintln!(
+            "  #{:<2} 0x{:016x} {}",
+            index,
+            frame.pc(),
+            Symbol::new(frame.pc(), frame.lookup_pc())
+        );
+    }
+}
+
+/// Function name of an address & the offset in it, for the reports
+pub struct Symbol {
+    address: u64,
+    function: Option<(&'static str, u64)>,
+}
+
+impl Symbol {
+    /// `lookup` is an address of the same instruction, cf. `Frame::lookup_pc`
+    fn new(address: u64, lookup: u64) -> Symbol {
+        Symbol {
+            address,
+            function: symbols::resolve(lookup),
+        }
+    }
+
+    pub fn of(address: u64) -> Symbol {
+        Symbol::new(address, address)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.function {
+            Some((name, start)) => write!(
+                f,
+                "{}+0x{:x}",
+                symbols::Demangle(name),
+                self.address - start
+            ),
+            None => f.write_str("<unknown>"),
+        }
+    }
+}
+
+/// Caller of a frame without call frame information, the frame pointer points to the saved one
+/// with the return address above it
+fn unwind_frame_pointer(registers: &Registers) -> Option<Registers> {
+    let rbp = registers.get(Registers::RBP).filter(|&rbp| rbp != 0)?;
+
+    let mut caller = *registers;
+    caller.set(Registers::RBP, Some(read_u64(rbp)?));
+    caller.set(Registers::RIP, Some(read_u64(rbp.checked_add(8)?)?));
+    caller.set(Registers::RSP, Some(rbp.checked_add(16)?));
+    Some(caller)
+}
+
+/// Read a value saved on the stack, without faulting if the frame is corrupted
+fn read_u64(address: u64) -> Option<u64> {
+    let address = usize::try_from(address).ok()?;
+    if address % 8 != 0 || !unsafe { is_mapped(VirtAddr::from(address)) } {
+        return None;
+    }
+
+    Some(unsafe { *(address as *const u64) })
+}
@@ -0,0 +1,602 @@
+//! Call frame information of `.eh_frame`. The FDE of an address is found with the binary search
+//! table of `.eh_frame_hdr`, then the instructions of its CIE & its own are run up to the address
+//! to know where the registers of the caller are saved.
+
+use super::{read_u64, Registers};
+use crate::kernel::{get_eh_frame, get_eh_frame_hdr};
+
+use core::convert::TryFrom;
+
+const EH_FRAME_HDR_VERSION: u8 = 1;
+
+/// Pointer encodings, the low bits give the format & the high ones what it's relative to
+const DW_EH_PE_ABSPTR: u8 = 0x00;
+const DW_EH_PE_ULEB128: u8 = 0x01;
+const DW_EH_PE_UDATA2: u8 = 0x02;
+const DW_EH_PE_UDATA4: u8 = 0x03;
+const DW_EH_PE_UDATA8: u8 = 0x04;
+const DW_EH_PE_SLEB128: u8 = 0x09;
+const DW_EH_PE_SDATA2: u8 = 0x0a;
+const DW_EH_PE_SDATA4: u8 = 0x0b;
+const DW_EH_PE_SDATA8: u8 = 0x0c;
+const DW_EH_PE_PCREL: u8 = 0x10;
+const DW_EH_PE_DATAREL: u8 = 0x30;
+const DW_EH_PE_INDIRECT: u8 = 0x80;
+const DW_EH_PE_OMIT: u8 = 0xff;
+
+const DW_CFA_ADVANCE_LOC: u8 = 0x40;
+const DW_CFA_OFFSET: u8 = 0x80;
+const DW_CFA_RESTORE: u8 = 0xc0;
+const DW_CFA_NOP: u8 = 0x00;
+const DW_CFA_SET_LOC: u8 = 0x01;
+const DW_CFA_ADVANCE_LOC1: u8 = 0x02;
+const DW_CFA_ADVANCE_LOC2: u8 = 0x03;
+const DW_CFA_ADVANCE_LOC4: u8 = 0x04;
+const DW_CFA_OFFSET_EXTENDED: u8 = 0x05;
+const DW_CFA_RESTORE_EXTENDED: u8 = 0x06;
+const DW_CFA_UNDEFINED: u8 = 0x07;
+const DW_CFA_SAME_VALUE: u8 = 0x08;
+const DW_CFA_REGISTER: u8 = 0x09;
+const DW_CFA_REMEMBER_STATE: u8 = 0x0a;
+const DW_CFA_RESTORE_STATE: u8 = 0x0b;
+const DW_CFA_DEF_CFA: u8 = 0x0c;
+const DW_CFA_DEF_CFA_REGISTER: u8 = 0x0d;
+const DW_CFA_DEF_CFA_OFFSET: u8 = 0x0e;
+const DW_CFA_EXPRESSION: u8 = 0x10;
+const DW_CFA_OFFSET_EXTENDED_SF: u8 = 0x11;
+const DW_CFA_DEF_CFA_SF: u8 = 0x12;
+const DW_CFA_DEF_CFA_OFFSET_SF: u8 = 0x13;
+const DW_CFA_VAL_OFFSET: u8 = 0x14;
+const DW_CFA_VAL_OFFSET_SF: u8 = 0x15;
+const DW_CFA_VAL_EXPRESSION: u8 = 0x16;
+const DW_CFA_GNU_ARGS_SIZE: u8 = 0x2e;
+const DW_CFA_GNU_NEGATIVE_OFFSET_EXTENDED: u8 = 0x2f;
+
+/// Rows saved by `DW_CFA_remember_state`, compilers only nest them for the epilogues
+const STATE_STACK_SIZE: usize = 4;
+
+/// Registers of the caller of the function running `pc`, `None` if it has no call frame
+/// information or it can't be used
+pub fn unwind(pc: u64, registers: &Registers) -> Option<Registers> {
+    let eh_frame = get_eh_frame();
+    let fde = Fde::parse(eh_frame, find_fde(pc)?)?;
+    if !(fde.start..fde.end).contains(&pc) {
+        return None;
+    }
+
+    let mut machine = Machine::new(&fde);
+    let initial = machine.run(fde.cie.instructions, u64::MAX)?;
+    machine.initial = initial;
+    machine.row = initial;
+    let row = machine.run(fde.instructions, pc)?;
+
+    // the CFA is the stack pointer of the caller, before the call pushed the return address
+    let cfa = registers
+        .get(row.cfa_register)?
+        .wrapping_add(row.cfa_offset as u64);
+
+    let mut caller = Registers::default();
+    for register in 0..Registers::COUNT {
+        let value = match row.rules[register] {
+            Rule::Undefined => None,
+            Rule::SameValue => registers.get(register),
+            Rule::Offset(offset) => read_u64(cfa.wrapping_add(offset as u64)),
+            Rule::ValOffset(offset) => Some(cfa.wrapping_add(offset as u64)),
+            Rule::Register(other) => registers.get(other),
+        };
+        caller.set(register, value);
+    }
+
+    let return_address = caller.get(fde.cie.return_address)?;
+    caller.set(Registers::RSP, Some(cfa));
+    caller.set(Registers::RIP, Some(return_address));
+    Some(caller)
+}
+
+/// Offset in `.eh_frame` of the FDE of the function holding `pc`
+fn find_fde(pc: u64) -> Option<usize> {
+    let header = get_eh_frame_hdr();
+    let eh_frame = get_eh_frame();
+    let base = header.as_ptr() as u64;
+
+    let mut reader = Reader::new(header, 0);
+    if reader.u8()? != EH_FRAME_HDR_VERSION {
+        return None;
+    }
+
+    let eh_frame_encoding = reader.u8()?;
+    let count_encoding = reader.u8()?;
+    let table_encoding = reader.u8()?;
+    if reader.pointer(eh_frame_encoding, base)? != eh_frame.as_ptr() as u64 {
+        return None;
+    }
+
+    // the linker always makes a table of 32 bits offsets from the header
+    if count_encoding == DW_EH_PE_OMIT || table_encoding != DW_EH_PE_DATAREL | DW_EH_PE_SDATA4 {
+        return None;
+    }
+
+    let count = usize::try_from(reader.pointer(count_encoding, base)?).ok()?;
+    let table = reader.offset;
+    let entry = |index: usize| {
+        let mut reader = Reader::new(header, table + index * 8);
+        Some((
+            reader.pointer(table_encoding, base)?,
+            reader.pointer(table_encoding, base)?,
+        ))
+    };
+
+    // entries are sorted by the first address of their function, find the last one before `pc`
+    if count == 0 {
+        return None;
+    }
+
+    let (mut low, mut high) = (0, count);
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if entry(middle)?.0 <= pc {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    let (start, fde) = entry(low)?;
+    if start > pc {
+        return None;
+    }
+
+    let offset = usize::try_from(fde.checked_sub(eh_frame.as_ptr() as u64)?).ok()?;
+    if offset < eh_frame.len() {
+        Some(offset)
+    } else {
+        None
+    }
+}
+
+#[derive(Clone)]
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], offset: usize) -> Reader<'a> {
+        Reader { data, offset }
+    }
+
+    /// Address of the next byte, for the pc-relative pointers
+    fn address(&self) -> u64 {
+        self.data.as_ptr() as u64 + self.offset as u64
+    }
+
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.offset..self.offset.checked_add(length)?)?;
+        self.offset += length;
+        Some(bytes)
+    }
+
+    fn field<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.bytes(N)
+            .and_then(|bytes| <[u8; N]>::try_from(bytes).ok())
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.field().map(u8::from_le_bytes)
+    }
+
+    fn u16(&mut self) -> Option<u16> {
+        self.field().map(u16::from_le_bytes)
+    }
+
+    fn u32(&mut self) -> Option<u32> {
+        self.field().map(u32::from_le_bytes)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.field().map(u64::from_le_bytes)
+    }
+
+    fn uleb128(&mut self) -> Option<u64> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= u64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    }
+
+    fn sleb128(&mut self) -> Option<i64> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= i64::from(byte & 0x7f) << shift;
+            }
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                // sign extend from the last bit read
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Some(value);
+            }
+        }
+    }
+
+    /// Nul-terminated string, without the nul character
+    fn c_str(&mut self) -> Option<&'a [u8]> {
+        let rest = self.data.get(self.offset..)?;
+        let length = rest.iter().position(|&c| c == 0)?;
+        self.offset += length + 1;
+        Some(&rest[..length])
+    }
+
+    /// Pointer with the `encoding` of the `DW_EH_PE_*` constants, `data_base` is the base of the
+    /// data-relative ones
+    fn pointer(&mut self, encoding: u8, data_base: u64) -> Option<u64> {
+        let address = self.address();
+        let value = match encoding & 0x0f {
+            DW_EH_PE_ABSPTR | DW_EH_PE_UDATA8 => self.u64()?,
+            DW_EH_PE_ULEB128 => self.uleb128()?,
+            DW_EH_PE_UDATA2 => u64::from(self.u16()?),
+            DW_EH_PE_UDATA4 => u64::from(self.u32()?),
+            DW_EH_PE_SLEB128 => self.sleb128()? as u64,
+            DW_EH_PE_SDATA2 => self.u16()? as i16 as u64,
+            DW_EH_PE_SDATA4 => self.u32()? as i32 as u64,
+            DW_EH_PE_SDATA8 => self.u64()?,
+            _ => return None,
+        };
+
+        let base = match encoding & 0x70 {
+            0 => 0,
+            DW_EH_PE_PCREL => address,
+            DW_EH_PE_DATAREL => data_base,
+            _ => return None,
+        };
+
+        let pointer = base.wrapping_add(value);
+        if encoding & DW_EH_PE_INDIRECT != 0 {
+            read_u64(pointer)
+        } else {
+            Some(pointer)
+        }
+    }
+
+    /// Read the length of a CIE or FDE, returns the offset of its end
+    fn entry_length(&mut self) -> Option<usize> {
+        let length = match self.u32()? {
+            0xffff_ffff => self.u64()?,
+            length => u64::from(length),
+        };
+
+        self.offset.checked_add(usize::try_from(length).ok()?)
+    }
+}
+
+/// Common information entry, shared by the FDEs of a compilation unit
+struct Cie<'a> {
+    code_alignment: u64,
+    data_alignment: i64,
+    return_address: usize,
+    pointer_encoding: u8,
+    /// The FDEs have augmentation data, which is skipped
+    has_augmentation_data: bool,
+    instructions: &'a [u8],
+}
+
+impl<'a> Cie<'a> {
+    fn parse(eh_frame: &'a [u8], offset: usize) -> Option<Cie<'a>> {
+        let mut reader = Reader::new(eh_frame, offset);
+        let end = reader.entry_length()?;
+        if reader.u32()? != 0 {
+            return None;
+        }
+
+        let version = reader.u8()?;
+        if version != 1 && version != 3 {
+            return None;
+        }
+
+        let augmentation = reader.c_str()?;
+        let code_alignment = reader.uleb128()?;
+        let data_alignment = reader.sleb128()?;
+        let return_address = if version == 1 {
+            u64::from(reader.u8()?)
+        } else {
+            reader.uleb128()?
+        };
+
+        let mut cie = Cie {
+            code_alignment,
+            data_alignment,
+            return_address: usize::try_from(return_address).ok()?,
+            pointer_encoding: DW_EH_PE_ABSPTR,
+            has_augmentation_data: false,
+            instructions: &[],
+        };
+
+        match augmentation.split_first() {
+            Some((b'z', letters)) => {
+                let length = usize::try_from(reader.uleb128()?).ok()?;
+                let data_end = reader.offset.checked_add(length)?;
+                for letter in letters {
+                    match letter {
+                        // the encoding of the LSDA pointers of the FDEs
+                        b'L' => {
+                            reader.u8()?;
+                        }
+                        b'P' => {
+                            let encoding = reader.u8()?;
+                            reader.pointer(encoding & !DW_EH_PE_INDIRECT, 0)?;
+                        }
+                        b'R' => cie.pointer_encoding = reader.u8()?,
+                        // signal frames & unknown letters, the rest of the data is skipped
+                        _ => break,
+                    }
+                }
+
+                reader.offset = data_end;
+                cie.has_augmentation_data = true;
+            }
+            Some(_) => return None,
+            None => (),
+        }
+
+        cie.instructions = eh_frame.get(reader.offset..end)?;
+        Some(cie)
+    }
+}
+
+/// Frame description entry, for a function
+struct Fde<'a> {
+    cie: Cie<'a>,
+    start: u64,
+    end: u64,
+    instructions: &'a [u8],
+}
+
+impl<'a> Fde<'a> {
+    fn parse(eh_frame: &'a [u8], offset: usize) -> Option<Fde<'a>> {
+        let mut reader = Reader::new(eh_frame, offset);
+        let end = reader.entry_length()?;
+
+        // the CIE is given by its distance to this field
+        let cie_pointer_offset = reader.offset;
+        let cie_pointer = usize::try_from(reader.u32()?).ok()?;
+        if cie_pointer == 0 {
+            return None;
+        }
+
+        let cie = Cie::parse(eh_frame, cie_pointer_offset.checked_sub(cie_pointer)?)?;
+        let start = reader.pointer(cie.pointer_encoding, 0)?;
+        // the size of the function isn't relative to anything
+        let size = reader.pointer(cie.pointer_encoding & 0x0f, 0)?;
+        if cie.has_augmentation_data {
+            let length = usize::try_from(reader.uleb128()?).ok()?;
+            reader.bytes(length)?;
+        }
+
+        Some(Fde {
+            start,
+            end: start.wrapping_add(size),
+            instructions: eh_frame.get(reader.offset..end)?,
+            cie,
+        })
+    }
+}
+
+/// Where the value of a register of the caller is
+#[derive(Copy, Clone)]
+enum Rule {
+    Undefined,
+    SameValue,
+    /// Saved at the CFA plus the offset
+    Offset(i64),
+    /// Is the CFA plus the offset
+    ValOffset(i64),
+    /// In another register
+    Register(usize),
+}
+
+/// Rules for a range of addresses of a function, the CFA is a register plus an offset
+#[derive(Copy, Clone)]
+struct Row {
+    cfa_register: usize,
+    cfa_offset: i64,
+    rules: [Rule; Registers::COUNT],
+}
+
+struct Machine<'a> {
+    cie: &'a Cie<'a>,
+    location: u64,
+    row: Row,
+    /// Row after the instructions of the CIE, for `DW_CFA_restore`
+    initial: Row,
+    stack: [Row; STATE_STACK_SIZE],
+    depth: usize,
+}
+
+impl<'a> Machine<'a> {
+    fn new(fde: &'a Fde<'a>) -> Machine<'a> {
+        // registers the CIE doesn't mention are callee-saved ones the function doesn't touch
+        let row = Row {
+            cfa_register: Registers::RSP,
+            cfa_offset: 0,
+            rules: [Rule::SameValue; Registers::COUNT],
+        };
+
+        Machine {
+            cie: &fde.cie,
+            location: fde.start,
+            row,
+            initial: row,
+            stack: [row; STATE_STACK_SIZE],
+            depth: 0,
+        }
+    }
+
+    /// Run `instructions` until the row of `pc` is known
+    fn run(&mut self, instructions: &[u8], pc: u64) -> Option<Row> {
+        let mut reader = Reader::new(instructions, 0);
+        while reader.offset < instructions.len() {
+            let opcode = reader.u8()?;
+            let operand = opcode & 0x3f;
+
+            let location = match opcode & 0xc0 {
+                DW_CFA_ADVANCE_LOC => Some(self.advance(u64::from(operand))),
+                DW_CFA_OFFSET => {
+                    let offset = self.factored(reader.uleb128()?)?;
+                    self.set_rule(usize::from(operand), Rule::Offset(offset));
+                    None
+                }
+                DW_CFA_RESTORE => {
+                    self.restore(usize::from(operand));
+                    None
+                }
+                _ => self.execute(opcode, &mut reader)?,
+            };
+
+            if let Some(location) = location {
+                if location > pc {
+                    break;
+                }
+                self.location = location;
+            }
+        }
+
+        Some(self.row)
+    }
+
+    /// Run an instruction without an operand in its opcode, returns the new location if it
+    /// changes
+    fn execute(&mut self, opcode: u8, reader: &mut Reader) -> Option<Option<u64>> {
+        match opcode {
+            DW_CFA_NOP => (),
+            DW_CFA_SET_LOC => return Some(Some(reader.pointer(self.cie.pointer_encoding, 0)?)),
+            DW_CFA_ADVANCE_LOC1 => return Some(Some(self.advance(u64::from(reader.u8()?)))),
+            DW_CFA_ADVANCE_LOC2 => return Some(Some(self.advance(u64::from(reader.u16()?)))),
+            DW_CFA_ADVANCE_LOC4 => return Some(Some(self.advance(u64::from(reader.u32()?)))),
+            DW_CFA_OFFSET_EXTENDED => {
+                let register = self.register(reader)?;
+                let offset = self.factored(reader.uleb128()?)?;
+                self.set_rule(register, Rule::Offset(offset));
+            }
+            DW_CFA_RESTORE_EXTENDED => {
+                let register = self.register(reader)?;
+                self.restore(register);
+            }
+            DW_CFA_UNDEFINED => {
+                let register = self.register(reader)?;
+                self.set_rule(register, Rule::Undefined);
+            }
+            DW_CFA_SAME_VALUE => {
+                let register = self.register(reader)?;
+                self.set_rule(register, Rule::SameValue);
+            }
+            DW_CFA_REGISTER => {
+                let register = self.register(reader)?;
+                let other = self.register(reader)?;
+                self.set_rule(register, Rule::Register(other));
+            }
+            DW_CFA_REMEMBER_STATE => {
+                *self.stack.get_mut(self.depth)? = self.row;
+                self.depth += 1;
+            }
+            DW_CFA_RESTORE_STATE => {
+                self.depth = self.depth.checked_sub(1)?;
+                self.row = self.stack[self.depth];
+            }
+            DW_CFA_DEF_CFA => {
+                self.row.cfa_register = self.register(reader)?;
+                self.row.cfa_offset = i64::try_from(reader.uleb128()?).ok()?;
+            }
+            DW_CFA_DEF_CFA_REGISTER => self.row.cfa_register = self.register(reader)?,
+            DW_CFA_DEF_CFA_OFFSET => {
+                self.row.cfa_offset = i64::try_from(reader.uleb128()?).ok()?;
+            }
+            DW_CFA_OFFSET_EXTENDED_SF => {
+                let register = self.register(reader)?;
+                let offset = reader.sleb128()?.wrapping_mul(self.cie.data_alignment);
+                self.set_rule(register, Rule::Offset(offset));
+            }
+            DW_CFA_DEF_CFA_SF => {
+                self.row.cfa_register = self.register(reader)?;
+                self.row.cfa_offset = reader.sleb128()?.wrapping_mul(self.cie.data_alignment);
+            }
+            DW_CFA_DEF_CFA_OFFSET_SF => {
+                self.row.cfa_offset = reader.sleb128()?.wrapping_mul(self.cie.data_alignment);
+            }
+            DW_CFA_VAL_OFFSET => {
+                let register = self.register(reader)?;
+                let offset = self.factored(reader.uleb128()?)?;
+                self.set_rule(register, Rule::ValOffset(offset));
+            }
+            DW_CFA_VAL_OFFSET_SF => {
+                let register = self.register(reader)?;
+                let offset = reader.sleb128()?.wrapping_mul(self.cie.data_alignment);
+                self.set_rule(register, Rule::ValOffset(offset));
+            }
+            // DWARF expressions aren't evaluated, the registers they describe are lost
+            DW_CFA_EXPRESSION | DW_CFA_VAL_EXPRESSION => {
+                let register = self.register(reader)?;
+                let length = usize::try_from(reader.uleb128()?).ok()?;
+                reader.bytes(length)?;
+                self.set_rule(register, Rule::Undefined);
+            }
+            DW_CFA_GNU_ARGS_SIZE => {
+                reader.uleb128()?;
+            }
+            DW_CFA_GNU_NEGATIVE_OFFSET_EXTENDED => {
+                let register = self.register(reader)?;
+                let offset = self.factored(reader.uleb128()?)?;
+                self.set_rule(register, Rule::Offset(offset.wrapping_neg()));
+            }
+            // the CFA can't be computed from an expression either
+            _ => return None,
+        }
+
+        Some(None)
+    }
+
+    fn advance(&self, delta: u64) -> u64 {
+        self.location
+            .wrapping_add(delta.wrapping_mul(self.cie.code_alignment))
+    }
+
+    fn register(&self, reader: &mut Reader) -> Option<usize> {
+        usize::try_from(reader.uleb128()?).ok()
+    }
+
+    fn factored(&self, offset: u64) -> Option<i64> {
+        Some(
+            i64::try_from(offset)
+                .ok()?
+                .wrapping_mul(self.cie.data_alignment),
+        )
+    }
+
+    /// Registers above the general purpose ones, like the SSE ones, are ignored
+    fn set_rule(&mut self, register: usize, rule: Rule) {
+        if let Some(slot) = self.row.rules.get_mut(register) {
+            *slot = rule;
+        }
+    }
+
+    fn restore(&mut self, register: usize) {
+        if let Some(&rule) = self.initial.rules.get(register) {
+            self.set_rule(register, rule);
+        }
+    }
+}
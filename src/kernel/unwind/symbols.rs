@@ -0,0 +1,209 @@
+//! Names of the kernel functions, from the ELF symbol table the bootloader loads with the
+//! multiboot `ELFSymbols` tag. Rust names are demangled, in the legacy `_ZN` mangling.
+
+use crate::boot::kaslr;
+use crate::boot::multiboot::{self, ElfSection, TagType};
+use crate::kernel::config::KERNEL_START;
+use crate::kernel::mem::addr::PhyAddr;
+use crate::kernel::mem::vbuffer::VBuffer;
+use crate::kernel::mem::Flags;
+
+use core::convert::TryFrom;
+use core::fmt::{self, Write};
+use lib::sync::StaticSpinlock;
+
+const SYMBOL_SIZE: usize = 24;
+const STT_FUNC: u8 = 2;
+const SHN_UNDEF: u16 = 0;
+
+static SYMBOLS: StaticSpinlock<Option<SymbolTable>> = StaticSpinlock::new(None);
+
+#[derive(Copy, Clone)]
+struct SymbolTable {
+    symbols: &'static [u8],
+    names: &'static [u8],
+}
+
+/// Map the symbol table, the heap has to be set up
+pub fn init() {
+    let boot_info = multiboot::get_boot_info();
+    let table = boot_info
+        .get_tag(TagType::ELFSymbols)
+        .and_then(|tag| tag.as_elf_symbols())
+        .and_then(|symbols| symbols.symbol_table());
+
+    let (symbols, names) = match table {
+        Some(table) => table,
+        None => {
+            kwarn!("unwind: no symbol table, backtraces won't have function names");
+            return;
+        }
+    };
+
+    match (map(&symbols), map(&names)) {
+        (Some(symbols), Some(names)) => {
+            kinfo!("unwind: {} symbols", symbols.len() / SYMBOL_SIZE);
+            *SYMBOLS.lock() = Some(SymbolTable { symbols, names });
+        }
+        _ => kwarn!("unwind: can't map the symbol table"),
+    }
+}
+
+/// The section stays mapped for the backtraces
+fn map(section: &ElfSection) -> Option<&'static [u8]> {
+    let range = section.range();
+    let size = range.end - range.start;
+    if size == 0 {
+        return None;
+    }
+
+    let start = PhyAddr::new(range.start);
+    let buffer = unsafe { VBuffer::with_flags(start, size, Flags::NO_EXECUTE) }.ok()?;
+    let (address, _) = VBuffer::leak(buffer);
+    Some(unsafe { core::slice::from_raw_parts(address, size) })
+}
+
+/// Name & address of the function holding `address`
+pub fn resolve(address: u64) -> Option<(&'static str, u64)> {
+    // a panic while the table is set up mustn't wait for it
+    let table = (*SYMBOLS.try_lock()?)?;
+
+    // the symbol table has the addresses the kernel is linked at
+    let slide = u64::try_from(kaslr::kernel_base() - KERNEL_START).ok()?;
+    let address = address.wrapping_sub(slide);
+
+    let mut best: Option<(u64, u32)> = None;
+    for symbol in table.symbols.chunks_exact(SYMBOL_SIZE) {
+        let name = u32::from_le_bytes(<[u8; 4]>::try_from(&symbol[0..4]).ok()?);
+        let kind = symbol[4] & 0xf;
+        let section = u16::from_le_bytes(<[u8; 2]>::try_from(&symbol[6..8]).ok()?);
+        let value = u64::from_le_bytes(<[u8; 8]>::try_from(&symbol[8..16]).ok()?);
+        let size = u64::from_le_bytes(<[u8; 8]>::try_from(&symbol[16..24]).ok()?);
+
+        if kind != STT_FUNC || section == SHN_UNDEF || address < value {
+            continue;
+        }
+
+        // functions of the assembly code have no size
+        if size != 0 && address - value >= size {
+            continue;
+        }
+
+        if best.map_or(true, |(best, _)| value > best) {
+            best = Some((value, name));
+        }
+    }
+
+    let (value, name) = best?;
+    let name = table.names.get(usize::try_from(name).ok()?..)?;
+    let length = name.iter().position(|&c| c == 0)?;
+    let name = core::str::from_utf8(&name[..length]).ok()?;
+    Some((name, value.wrapping_add(slide)))
+}
+
+/// Display a symbol name demangled, names that aren't in the legacy Rust mangling are kept
+pub struct Demangle<'a>(pub &'a str);
+
+impl fmt::Display for Demangle<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = match legacy_path(self.0) {
+            Some(path) => path,
+            None => return f.write_str(self.0),
+        };
+
+        let mut rest = path;
+        let mut first = true;
+        while let Some((component, next)) = split_component(rest) {
+            rest = next;
+            // the last component is a hash of the crate & its dependencies
+            if rest.is_empty() && is_hash(component) {
+                break;
+            }
+
+            if !first {
+                f.write_str("::")?;
+            }
+            first = false;
+            write_component(f, component)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Components of a `_ZN<length><component>...E` name, with the suffixes LLVM adds after `E`
+fn legacy_path(name: &str) -> Option<&str> {
+    let path = name.strip_prefix("_ZN")?;
+    let mut rest = path;
+    while let Some((_, next)) = split_component(rest) {
+        rest = next;
+    }
+
+    if rest.starts_with('E') {
+        Some(&path[..path.len() - rest.len()])
+    } else {
+        None
+    }
+}
+
+fn split_component(path: &str) -> Option<(&str, &str)> {
+    let digits = path.bytes().take_while(u8::is_ascii_digit).count();
+    let length = path[..digits].parse::<usize>().ok()?;
+    let component = path.get(digits..digits + length)?;
+    Some((component, &path[digits + length..]))
+}
+
+fn is_hash(component: &str) -> bool {
+    component.len() == 17
+        && component.starts_with('h')
+        && component[1..].bytes().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Write a component with its escapes, like `$LT$` for `<` or `..` for `::`
+fn write_component(f: &mut fmt::Formatter<'_>, component: &str) -> fmt::Result {
+    // components starting with an escape get an underscore first
+    let mut rest = if component.starts_with("_$") {
+        &component[1..]
+    } else {
+        component
+    };
+    while let Some(c) = rest.chars().next() {
+        if let Some(next) = rest.strip_prefix("..") {
+            f.write_str("::")?;
+            rest = next;
+            continue;
+        }
+
+        if c == '$' {
+            if let Some(end) = rest[1..].find('$') {
+                if let Some(unescaped) = unescape(&rest[1..end + 1]) {
+                    f.write_char(unescaped)?;
+                    rest = &rest[end + 2..];
+                    continue;
+                }
+            }
+        }
+
+        f.write_char(c)?;
+        rest = &rest[c.len_utf8()..];
+    }
+
+    Ok(())
+}
+
+fn unescape(escape: &str) -> Option<char> {
+    match escape {
+        "SP" => Some('@'),
+        "BP" => Some('*'),
+        "RF" => Some('&'),
+        "LT" => Some('<'),
+        "GT" => Some('>'),
+        "LP" => Some('('),
+        "RP" => Some(')'),
+        "C" => Some(','),
+        _ => {
+            let code = u32::from_str_radix(escape.strip_prefix('u')?, 16).ok()?;
+            char::from_u32(code)
+        }
+    }
+}
@@ -12,33 +12,35 @@
     const_mut_refs
 )]
 
-use lib::disable_interrupts;
+use lib::{disable_interrupts, enable_interrupts};
 
 extern crate acpica;
 extern crate alloc;
 
 #[macro_use]
 pub mod boot;
-pub mod drivers;
+#[macro_use]
 pub mod kernel;
+pub mod drivers;
 pub mod panic;
 
-fn shutdown() {
-    unsafe {
-        acpica::AcpiEnterSleepStatePrep(5);
-        disable_interrupts!();
-        acpica::AcpiEnterSleepState(5);
-    }
-}
-
 #[no_mangle]
 pub fn kernel_main() -> ! {
-    early_kprintln!("kernel_main reached");
+    kinfo!("kernel_main reached");
+    unsafe { enable_interrupts!() };
 
     loop {
-        early_kprintln!("tick");
+        kernel::deferred::workqueue::run_pending();
+        drivers::acpi::thermal::poll();
+        kernel::shell::poll();
+
         unsafe {
-            core::arch::asm!("hlt");
+            disable_interrupts!();
+            if kernel::deferred::workqueue::has_pending() {
+                enable_interrupts!();
+            } else {
+                kernel::idle::idle();
+            }
         };
     }
 }
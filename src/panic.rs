@@ -3,6 +3,9 @@ use core::panic::PanicInfo;
 #[cold]
 #[panic_handler]
 pub fn panic(info: &PanicInfo) -> ! {
+    // the records logged before the panic come first
+    crate::kernel::log::flush();
+
     early_kprintln!("\x1B\x74----- [PANIC START HERE] -----");
     early_kprint!("kernel panicked ");
 
@@ -16,7 +19,10 @@ pub fn panic(info: &PanicInfo) -> ! {
         early_kprintln!("Reason: {:?}", message);
     }
 
+    crate::kernel::unwind::print_backtrace();
+
     early_kprintln!("----- [PANIC  END  HERE] -----\x1B\0");
+    crate::kernel::log::print_dmesg();
 
-    loop {}
+    crate::kernel::power::panic_reboot()
 }
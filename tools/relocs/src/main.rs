@@ -0,0 +1,337 @@
+//! Fill the relocation table the boot stub uses to move the kernel to a random address.
+//!
+//! The kernel is linked with `--emit-relocs`, every absolute relocation pointing into the kernel
+//! image is written to the `.kaslr_relocs` section, as the physical address of the value to
+//! adjust. The table is:
+//! - the `RELS` magic
+//! - the number of 64 bits relocations, then of 32 bits sign-extended ones, as u32
+//! - the physical addresses of the 64 bits relocations, then of the 32 bits ones, as u32
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::fs;
+use std::process::exit;
+
+const TABLE_SECTION: &str = ".kaslr_relocs";
+const TABLE_MAGIC: &[u8; 4] = b"RELS";
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_RELA: u32 = 4;
+const SHF_ALLOC: u64 = 1 << 1;
+const PT_LOAD: u32 = 1;
+
+const R_X86_64_NONE: u32 = 0;
+const R_X86_64_64: u32 = 1;
+const R_X86_64_PC32: u32 = 2;
+const R_X86_64_PLT32: u32 = 4;
+const R_X86_64_32S: u32 = 11;
+const R_X86_64_PC64: u32 = 24;
+
+#[derive(Debug)]
+enum Error {
+    Io(std::io::Error),
+    InvalidElf(&'static str),
+    MissingSymbol(&'static str),
+    MissingSection(&'static str),
+    UnsupportedRelocation { kind: u32, offset: u64 },
+    NotLoaded(u64),
+    TableTooSmall { needed: usize, size: usize },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{}", error),
+            Error::InvalidElf(reason) => write!(f, "invalid ELF file, {}", reason),
+            Error::MissingSymbol(name) => write!(f, "no {} symbol", name),
+            Error::MissingSection(name) => write!(f, "no {} section", name),
+            Error::UnsupportedRelocation { kind, offset } => write!(
+                f,
+                "unsupported relocation type {} at {:#x} pointing into the kernel",
+                kind, offset
+            ),
+            Error::NotLoaded(offset) => write!(f, "relocation at {:#x} isn't loaded", offset),
+            Error::TableTooSmall { needed, size } => write!(
+                f,
+                "the relocation table needs {} bytes, {} only has {}",
+                needed, TABLE_SECTION, size
+            ),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Error {
+        Error::Io(error)
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+struct Elf<'a> {
+    data: &'a [u8],
+    sections: Vec<Section>,
+    segments: Vec<Segment>,
+}
+
+struct Section {
+    name: String,
+    kind: u32,
+    flags: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+    info: u32,
+    entry_size: u64,
+}
+
+struct Segment {
+    kind: u32,
+    vaddr: u64,
+    paddr: u64,
+    memsz: u64,
+}
+
+struct Symbol {
+    name: u32,
+    value: u64,
+}
+
+fn read<const N: usize>(data: &[u8], offset: u64) -> Result<[u8; N]> {
+    let offset = usize::try_from(offset).map_err(|_| Error::InvalidElf("offset too large"))?;
+    data.get(offset..offset + N)
+        .map(|bytes| <[u8; N]>::try_from(bytes).unwrap())
+        .ok_or(Error::InvalidElf("truncated"))
+}
+
+fn read_u16(data: &[u8], offset: u64) -> Result<u16> {
+    Ok(u16::from_le_bytes(read(data, offset)?))
+}
+
+fn read_u32(data: &[u8], offset: u64) -> Result<u32> {
+    Ok(u32::from_le_bytes(read(data, offset)?))
+}
+
+fn read_u64(data: &[u8], offset: u64) -> Result<u64> {
+    Ok(u64::from_le_bytes(read(data, offset)?))
+}
+
+impl<'a> Elf<'a> {
+    fn parse(data: &'a [u8]) -> Result<Elf<'a>> {
+        if !data.starts_with(b"\x7fELF\x02\x01") {
+            return Err(Error::InvalidElf("not a little-endian ELF64 file"));
+        }
+
+        let phoff = read_u64(data, 0x20)?;
+        let shoff = read_u64(data, 0x28)?;
+        let phentsize = u64::from(read_u16(data, 0x36)?);
+        let phnum = u64::from(read_u16(data, 0x38)?);
+        let shentsize = u64::from(read_u16(data, 0x3a)?);
+        let shnum = u64::from(read_u16(data, 0x3c)?);
+        let shstrndx = u64::from(read_u16(data, 0x3e)?);
+
+        let segments = (0..phnum)
+            .map(|index| {
+                let header = phoff + index * phentsize;
+                Ok(Segment {
+                    kind: read_u32(data, header)?,
+                    vaddr: read_u64(data, header + 0x10)?,
+                    paddr: read_u64(data, header + 0x18)?,
+                    memsz: read_u64(data, header + 0x28)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let names_offset = read_u64(data, shoff + shstrndx * shentsize + 0x18)?;
+        let sections = (0..shnum)
+            .map(|index| {
+                let header = shoff + index * shentsize;
+                let name = read_u32(data, header)?;
+                Ok(Section {
+                    name: string(data, names_offset + u64::from(name))?,
+                    kind: read_u32(data, header + 0x4)?,
+                    flags: read_u64(data, header + 0x8)?,
+                    offset: read_u64(data, header + 0x18)?,
+                    size: read_u64(data, header + 0x20)?,
+                    link: read_u32(data, header + 0x28)?,
+                    info: read_u32(data, header + 0x2c)?,
+                    entry_size: read_u64(data, header + 0x38)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Elf {
+            data,
+            sections,
+            segments,
+        })
+    }
+
+    fn section(&self, name: &'static str) -> Result<&Section> {
+        self.sections
+            .iter()
+            .find(|section| section.name == name)
+            .ok_or(Error::MissingSection(name))
+    }
+
+    fn symbols(&self) -> Result<(&Section, Vec<Symbol>)> {
+        let symtab = self
+            .sections
+            .iter()
+            .find(|section| section.kind == SHT_SYMTAB)
+            .ok_or(Error::MissingSection(".symtab"))?;
+
+        let symbols = (0..symtab.size / symtab.entry_size.max(1))
+            .map(|index| {
+                let entry = symtab.offset + index * symtab.entry_size;
+                Ok(Symbol {
+                    name: read_u32(self.data, entry)?,
+                    value: read_u64(self.data, entry + 0x8)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok((symtab, symbols))
+    }
+
+    fn symbol_value(&self, name: &'static str) -> Result<u64> {
+        let (symtab, symbols) = self.symbols()?;
+        let names = &self.sections[symtab.link as usize];
+        for symbol in symbols {
+            if string(self.data, names.offset + u64::from(symbol.name))? == name {
+                return Ok(symbol.value);
+            }
+        }
+        Err(Error::MissingSymbol(name))
+    }
+
+    fn physical_address(&self, vaddr: u64) -> Result<u64> {
+        self.segments
+            .iter()
+            .filter(|segment| segment.kind == PT_LOAD)
+            .find(|segment| (segment.vaddr..segment.vaddr + segment.memsz).contains(&vaddr))
+            .map(|segment| segment.paddr + (vaddr - segment.vaddr))
+            .ok_or(Error::NotLoaded(vaddr))
+    }
+}
+
+fn string(data: &[u8], offset: u64) -> Result<String> {
+    let start = usize::try_from(offset).map_err(|_| Error::InvalidElf("offset too large"))?;
+    let bytes = data.get(start..).ok_or(Error::InvalidElf("truncated"))?;
+    let length = bytes
+        .iter()
+        .position(|&c| c == 0)
+        .ok_or(Error::InvalidElf("unterminated string"))?;
+    Ok(String::from_utf8_lossy(&bytes[..length]).into_owned())
+}
+
+/// Physical addresses of the 64 bits & 32 bits sign-extended relocations into the kernel image
+fn relocations(elf: &Elf) -> Result<(Vec<u32>, Vec<u32>)> {
+    let image = elf.symbol_value("kernel_start_addr")?..=elf.symbol_value("kernel_end_addr")?;
+    let (_, symbols) = elf.symbols()?;
+
+    let mut relocations_64 = Vec::new();
+    let mut relocations_32 = Vec::new();
+    for rela in elf
+        .sections
+        .iter()
+        .filter(|section| section.kind == SHT_RELA)
+    {
+        // relocations of the debug information don't matter once loaded
+        let target = elf
+            .sections
+            .get(rela.info as usize)
+            .ok_or(Error::InvalidElf("relocations of a missing section"))?;
+        if target.flags & SHF_ALLOC == 0 {
+            continue;
+        }
+
+        for index in 0..rela.size / rela.entry_size.max(1) {
+            let entry = rela.offset + index * rela.entry_size;
+            let offset = read_u64(elf.data, entry)?;
+            let info = read_u64(elf.data, entry + 0x8)?;
+            let addend = read_u64(elf.data, entry + 0x10)?;
+
+            let kind = (info & 0xffff_ffff) as u32;
+            let symbol = symbols
+                .get((info >> 32) as usize)
+                .ok_or(Error::InvalidElf("relocation of a missing symbol"))?;
+
+            match kind {
+                R_X86_64_NONE | R_X86_64_PC32 | R_X86_64_PLT32 | R_X86_64_PC64 => continue,
+                _ => (),
+            }
+
+            // the boot stub & physical addresses don't move
+            if !image.contains(&symbol.value.wrapping_add(addend)) {
+                continue;
+            }
+
+            let address = u32::try_from(elf.physical_address(offset)?)
+                .map_err(|_| Error::NotLoaded(offset))?;
+            match kind {
+                R_X86_64_64 => relocations_64.push(address),
+                R_X86_64_32S => relocations_32.push(address),
+                // R_X86_64_32 can't hold an address of the higher half, nor can GOT relocations
+                // be used without a GOT
+                _ => return Err(Error::UnsupportedRelocation { kind, offset }),
+            }
+        }
+    }
+
+    relocations_64.sort_unstable();
+    relocations_32.sort_unstable();
+    Ok((relocations_64, relocations_32))
+}
+
+fn run(path: &str) -> Result<()> {
+    let mut data = fs::read(path)?;
+    let elf = Elf::parse(&data)?;
+    let (relocations_64, relocations_32) = relocations(&elf)?;
+
+    let table = elf.section(TABLE_SECTION)?;
+    let (table_offset, table_size) = (table.offset as usize, table.size as usize);
+
+    let mut content = Vec::new();
+    content.extend_from_slice(TABLE_MAGIC);
+    content.extend_from_slice(&(relocations_64.len() as u32).to_le_bytes());
+    content.extend_from_slice(&(relocations_32.len() as u32).to_le_bytes());
+    for address in relocations_64.iter().chain(relocations_32.iter()) {
+        content.extend_from_slice(&address.to_le_bytes());
+    }
+
+    if content.len() > table_size {
+        return Err(Error::TableTooSmall {
+            needed: content.len(),
+            size: table_size,
+        });
+    }
+
+    data[table_offset..table_offset + table_size].fill(0);
+    data[table_offset..table_offset + content.len()].copy_from_slice(&content);
+    fs::write(path, &data)?;
+
+    println!(
+        "{}: {} 64 bits & {} 32 bits relocations, {}/{} bytes",
+        path,
+        relocations_64.len(),
+        relocations_32.len(),
+        content.len(),
+        table_size
+    );
+    Ok(())
+}
+
+fn main() {
+    let path = match std::env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: relocs <kernel>");
+            exit(2);
+        }
+    };
+
+    if let Err(error) = run(&path) {
+        eprintln!("relocs: {}", error);
+        exit(1);
+    }
+}